@@ -0,0 +1,5 @@
+-- Revert: 001_create_videos
+-- Drop the videos table
+DROP TABLE IF EXISTS videos;
+DROP FUNCTION IF EXISTS update_updated_at_column();
+DROP TYPE IF EXISTS video_status;
@@ -1,15 +1,10 @@
--- Video Streaming Application Database Migrations
--- This file is used by SQLx for compile-time checking
-
 -- Migration: 001_create_videos
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 
-CREATE TYPE video_status AS ENUM (
-    'pending',
-    'processing', 
-    'ready',
-    'failed'
-);
+DO $$ BEGIN
+    CREATE TYPE video_status AS ENUM ('pending', 'processing', 'ready', 'failed');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
 
 CREATE TABLE IF NOT EXISTS videos (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -30,8 +25,8 @@ CREATE TABLE IF NOT EXISTS videos (
     processed_at TIMESTAMP WITH TIME ZONE
 );
 
-CREATE INDEX idx_videos_status ON videos(status);
-CREATE INDEX idx_videos_created_at ON videos(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_videos_status ON videos(status);
+CREATE INDEX IF NOT EXISTS idx_videos_created_at ON videos(created_at DESC);
 
 CREATE OR REPLACE FUNCTION update_updated_at_column()
 RETURNS TRIGGER AS $$
@@ -41,6 +36,7 @@ BEGIN
 END;
 $$ language 'plpgsql';
 
+DROP TRIGGER IF EXISTS update_videos_updated_at ON videos;
 CREATE TRIGGER update_videos_updated_at
     BEFORE UPDATE ON videos
     FOR EACH ROW
@@ -0,0 +1,8 @@
+-- Revert: 002_soft_delete_videos
+-- Remove soft deletion of videos
+DROP INDEX IF EXISTS idx_videos_deleted_at;
+
+ALTER TABLE videos DROP COLUMN IF EXISTS purge_error;
+ALTER TABLE videos DROP COLUMN IF EXISTS purge_attempts;
+ALTER TABLE videos DROP COLUMN IF EXISTS purge_after;
+ALTER TABLE videos DROP COLUMN IF EXISTS deleted_at;
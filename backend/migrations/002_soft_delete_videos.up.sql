@@ -0,0 +1,8 @@
+-- Migration: 002_soft_delete_videos
+-- Videos are soft-deleted and purged from storage by the garbage collector
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS purge_after TIMESTAMP WITH TIME ZONE;
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS purge_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS purge_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_videos_deleted_at ON videos(deleted_at) WHERE deleted_at IS NOT NULL;
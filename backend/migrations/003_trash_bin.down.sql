@@ -0,0 +1,5 @@
+-- Revert: 003_trash_bin
+-- Remove the trash bin
+DROP TABLE IF EXISTS video_deletion_log;
+
+ALTER TABLE videos DROP COLUMN IF EXISTS deleted_by;
@@ -0,0 +1,15 @@
+-- Migration: 003_trash_bin
+-- Who moved a video to the trash, and a permanent record of purged videos
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS deleted_by VARCHAR(255);
+
+CREATE TABLE IF NOT EXISTS video_deletion_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    video_id UUID NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    original_filename VARCHAR(255) NOT NULL,
+    deleted_by VARCHAR(255),
+    deleted_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    purged_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_deletion_log_video_id ON video_deletion_log(video_id);
@@ -0,0 +1,3 @@
+-- Revert: 004_private_videos
+-- Remove the private flag of videos
+ALTER TABLE videos DROP COLUMN IF EXISTS is_private;
@@ -0,0 +1,3 @@
+-- Migration: 004_private_videos
+-- Private videos can only be streamed with a signed playback token
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS is_private BOOLEAN NOT NULL DEFAULT FALSE;
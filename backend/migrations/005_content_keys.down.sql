@@ -0,0 +1,5 @@
+-- Revert: 005_content_keys
+-- Remove HLS content keys
+DROP TABLE IF EXISTS content_keys;
+
+ALTER TABLE videos DROP COLUMN IF EXISTS encryption;
@@ -0,0 +1,12 @@
+-- Migration: 005_content_keys
+-- Per-video HLS content keys, encrypted under the configured master key
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS encryption VARCHAR(20);
+
+CREATE TABLE IF NOT EXISTS content_keys (
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    key_index INTEGER NOT NULL,
+    encrypted_key BYTEA NOT NULL,
+    nonce BYTEA NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (video_id, key_index)
+);
@@ -0,0 +1,5 @@
+-- Revert: 006_clear_key_drm
+-- Remove the key IDs of content keys
+DROP INDEX IF EXISTS idx_content_keys_key_id;
+
+ALTER TABLE content_keys DROP COLUMN IF EXISTS key_id;
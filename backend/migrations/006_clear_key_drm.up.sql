@@ -0,0 +1,6 @@
+-- Migration: 006_clear_key_drm
+-- Key IDs (KIDs) of content keys, as signalled in CENC PSSH boxes and
+-- requested from the Clear Key license endpoint
+ALTER TABLE content_keys ADD COLUMN IF NOT EXISTS key_id UUID NOT NULL DEFAULT uuid_generate_v4();
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_keys_key_id ON content_keys(key_id);
@@ -0,0 +1,7 @@
+-- Revert: 007_users
+-- Remove user accounts
+DROP INDEX IF EXISTS idx_videos_owner_id;
+ALTER TABLE videos DROP COLUMN IF EXISTS owner_id;
+
+DROP TABLE IF EXISTS refresh_tokens;
+DROP TABLE IF EXISTS users;
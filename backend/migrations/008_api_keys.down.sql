@@ -0,0 +1,3 @@
+-- Revert: 008_api_keys
+-- Remove API keys
+DROP TABLE IF EXISTS api_keys;
@@ -0,0 +1,16 @@
+-- Revert: 009_rbac_visibility
+-- Remove roles, visibility levels, groups and shares
+DROP TABLE IF EXISTS video_shares;
+DROP TABLE IF EXISTS group_members;
+DROP TABLE IF EXISTS user_groups;
+
+-- Restore the private flag; unlisted videos become public
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS is_private BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE videos SET is_private = TRUE WHERE visibility = 'private';
+
+DROP INDEX IF EXISTS idx_videos_visibility;
+ALTER TABLE videos DROP COLUMN IF EXISTS visibility;
+ALTER TABLE users DROP COLUMN IF EXISTS role;
+
+DROP TYPE IF EXISTS video_visibility;
+DROP TYPE IF EXISTS user_role;
@@ -0,0 +1,68 @@
+-- Migration: 009_rbac_visibility
+-- User roles, per-video visibility, groups and video sharing
+DO $$ BEGIN
+    CREATE TYPE user_role AS ENUM ('viewer', 'uploader', 'editor', 'admin');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE video_visibility AS ENUM ('public', 'unlisted', 'private');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role user_role NOT NULL DEFAULT 'uploader';
+
+-- Make the oldest account the administrator of existing installations
+UPDATE users SET role = 'admin'
+WHERE id = (SELECT id FROM users ORDER BY created_at LIMIT 1)
+  AND NOT EXISTS (SELECT 1 FROM users WHERE role = 'admin');
+
+-- Replace the private flag with a visibility level
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS visibility video_visibility NOT NULL DEFAULT 'public';
+
+DO $$ BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'videos' AND column_name = 'is_private'
+    ) THEN
+        UPDATE videos SET visibility = 'private' WHERE is_private;
+        ALTER TABLE videos DROP COLUMN is_private;
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_videos_visibility ON videos(visibility);
+
+-- Groups of users private videos can be shared with
+CREATE TABLE IF NOT EXISTS user_groups (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_groups_name ON user_groups(LOWER(name));
+
+CREATE TABLE IF NOT EXISTS group_members (
+    group_id UUID NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_members_user_id ON group_members(user_id);
+
+-- Grants of access to a video, to either a user or a group
+CREATE TABLE IF NOT EXISTS video_shares (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    group_id UUID REFERENCES user_groups(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((user_id IS NULL) <> (group_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_video_shares_user ON video_shares(video_id, user_id) WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_video_shares_group ON video_shares(video_id, group_id) WHERE group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_video_shares_user_id ON video_shares(user_id);
+CREATE INDEX IF NOT EXISTS idx_video_shares_group_id ON video_shares(group_id);
@@ -0,0 +1,17 @@
+-- Revert: 010_tenants
+-- Remove tenants
+DROP INDEX IF EXISTS idx_videos_tenant_created_at;
+DROP INDEX IF EXISTS idx_users_tenant_username;
+DROP INDEX IF EXISTS idx_users_tenant_email;
+DROP INDEX IF EXISTS idx_user_groups_tenant_name;
+
+-- Fails when names taken in several tenants collide again
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username ON users(LOWER(username));
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users(LOWER(email));
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_groups_name ON user_groups(LOWER(name));
+
+ALTER TABLE user_groups DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE videos DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
+
+DROP TABLE IF EXISTS tenants;
@@ -0,0 +1,45 @@
+-- Migration: 010_tenants
+-- Tenants with isolated catalogs, accounts and storage locations
+CREATE TABLE IF NOT EXISTS tenants (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    slug VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    -- Host names resolving to the tenant for requests without a token
+    hostnames TEXT[] NOT NULL DEFAULT '{}',
+    -- Key prefix of the tenant's objects; NULL uses the slug, '' the bucket root
+    storage_prefix VARCHAR(100),
+    -- Bucket overrides; NULL uses the configured bucket
+    videos_bucket VARCHAR(63),
+    segments_bucket VARCHAR(63),
+    manifests_bucket VARCHAR(63),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenants_hostnames ON tenants USING GIN (hostnames);
+
+-- Existing objects live at the bucket root
+INSERT INTO tenants (slug, name, storage_prefix)
+VALUES ('default', 'Default', '')
+ON CONFLICT (slug) DO NOTHING;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE;
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE;
+ALTER TABLE user_groups ADD COLUMN IF NOT EXISTS tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE;
+
+UPDATE users SET tenant_id = (SELECT id FROM tenants WHERE slug = 'default') WHERE tenant_id IS NULL;
+UPDATE videos SET tenant_id = (SELECT id FROM tenants WHERE slug = 'default') WHERE tenant_id IS NULL;
+UPDATE user_groups SET tenant_id = (SELECT id FROM tenants WHERE slug = 'default') WHERE tenant_id IS NULL;
+
+ALTER TABLE users ALTER COLUMN tenant_id SET NOT NULL;
+ALTER TABLE videos ALTER COLUMN tenant_id SET NOT NULL;
+ALTER TABLE user_groups ALTER COLUMN tenant_id SET NOT NULL;
+
+-- Usernames, email addresses and group names are unique per tenant
+DROP INDEX IF EXISTS idx_users_username;
+DROP INDEX IF EXISTS idx_users_email;
+DROP INDEX IF EXISTS idx_user_groups_name;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_tenant_username ON users(tenant_id, LOWER(username));
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_tenant_email ON users(tenant_id, LOWER(email));
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_groups_tenant_name ON user_groups(tenant_id, LOWER(name));
+
+CREATE INDEX IF NOT EXISTS idx_videos_tenant_created_at ON videos(tenant_id, created_at DESC);
@@ -0,0 +1,6 @@
+-- Revert: 011_quotas
+-- Remove storage quotas
+DROP INDEX IF EXISTS idx_videos_tenant_owner;
+DROP TABLE IF EXISTS quotas;
+
+ALTER TABLE videos DROP COLUMN IF EXISTS rendition_size;
@@ -0,0 +1,23 @@
+-- Migration: 011_quotas
+-- Storage and transcoding quotas per user and per tenant
+-- Total size of the stored renditions, thumbnail and manifests
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS rendition_size BIGINT NOT NULL DEFAULT 0;
+
+-- Limits of a user, or of a tenant as a whole when user_id is NULL; a NULL
+-- limit is unlimited. Users without a row get the configured defaults.
+CREATE TABLE IF NOT EXISTS quotas (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    max_original_bytes BIGINT,
+    max_rendition_bytes BIGINT,
+    max_videos BIGINT,
+    max_upload_bytes BIGINT,
+    max_duration_seconds FLOAT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quotas_tenant ON quotas(tenant_id) WHERE user_id IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quotas_user ON quotas(user_id) WHERE user_id IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_videos_tenant_owner ON videos(tenant_id, owner_id);
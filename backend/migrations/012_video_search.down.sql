@@ -0,0 +1,9 @@
+-- Revert: 012_video_search
+-- Remove full-text search of videos
+DROP INDEX IF EXISTS idx_videos_search_vector;
+DROP TRIGGER IF EXISTS update_videos_search_vector ON videos;
+DROP FUNCTION IF EXISTS update_videos_search_vector();
+
+ALTER TABLE videos DROP COLUMN IF EXISTS search_vector;
+ALTER TABLE videos DROP COLUMN IF EXISTS language;
+ALTER TABLE videos DROP COLUMN IF EXISTS transcript;
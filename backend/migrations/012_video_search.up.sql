@@ -0,0 +1,32 @@
+-- Migration: 012_video_search
+-- Full-text search over titles, descriptions and transcripts
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS transcript TEXT;
+-- Text search configuration used to stem the video's text
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS language VARCHAR(64) NOT NULL DEFAULT 'english';
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+
+-- Titles rank above descriptions, descriptions above transcripts
+CREATE OR REPLACE FUNCTION update_videos_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector =
+        setweight(to_tsvector(NEW.language::regconfig, COALESCE(NEW.title, '')), 'A') ||
+        setweight(to_tsvector(NEW.language::regconfig, COALESCE(NEW.description, '')), 'B') ||
+        setweight(to_tsvector(NEW.language::regconfig, COALESCE(NEW.transcript, '')), 'C');
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS update_videos_search_vector ON videos;
+CREATE TRIGGER update_videos_search_vector
+    BEFORE INSERT OR UPDATE OF title, description, transcript, language ON videos
+    FOR EACH ROW
+    EXECUTE FUNCTION update_videos_search_vector();
+
+UPDATE videos SET search_vector =
+    setweight(to_tsvector(language::regconfig, COALESCE(title, '')), 'A') ||
+    setweight(to_tsvector(language::regconfig, COALESCE(description, '')), 'B') ||
+    setweight(to_tsvector(language::regconfig, COALESCE(transcript, '')), 'C')
+WHERE search_vector IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_videos_search_vector ON videos USING GIN (search_vector);
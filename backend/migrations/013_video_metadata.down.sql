@@ -0,0 +1,12 @@
+-- Revert: 013_video_metadata
+-- Remove tags, categories and custom metadata
+ALTER TABLE tenants DROP COLUMN IF EXISTS metadata_schema;
+
+DROP INDEX IF EXISTS idx_videos_custom_metadata;
+DROP INDEX IF EXISTS idx_videos_category_id;
+ALTER TABLE videos DROP COLUMN IF EXISTS custom_metadata;
+ALTER TABLE videos DROP COLUMN IF EXISTS category_id;
+
+DROP TABLE IF EXISTS categories;
+DROP TABLE IF EXISTS video_tags;
+DROP TABLE IF EXISTS tags;
@@ -0,0 +1,40 @@
+-- Migration: 013_video_metadata
+-- Tags, category hierarchy and schema-validated custom metadata
+CREATE TABLE IF NOT EXISTS tags (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_tenant_name ON tags(tenant_id, LOWER(name));
+
+CREATE TABLE IF NOT EXISTS video_tags (
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (video_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_tags_tag_id ON video_tags(tag_id);
+
+-- Categories nest under a parent; a category with subcategories cannot be deleted
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    parent_id UUID REFERENCES categories(id) ON DELETE RESTRICT,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_categories_tenant_parent_name
+    ON categories(tenant_id, COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'), LOWER(name));
+CREATE INDEX IF NOT EXISTS idx_categories_parent_id ON categories(parent_id);
+
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS category_id UUID REFERENCES categories(id) ON DELETE SET NULL;
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS custom_metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE INDEX IF NOT EXISTS idx_videos_category_id ON videos(category_id);
+CREATE INDEX IF NOT EXISTS idx_videos_custom_metadata ON videos USING GIN (custom_metadata);
+
+-- JSON schema custom metadata of the tenant's videos must match; NULL allows any object
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS metadata_schema JSONB;
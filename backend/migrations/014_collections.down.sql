@@ -0,0 +1,5 @@
+-- Revert: 014_collections
+-- Remove collections
+DROP TABLE IF EXISTS collection_videos;
+DROP TABLE IF EXISTS collections;
+DROP TYPE IF EXISTS collection_kind;
@@ -0,0 +1,41 @@
+-- Migration: 014_collections
+-- Ordered playlists and series of videos
+DO $$ BEGIN
+    CREATE TYPE collection_kind AS ENUM ('playlist', 'series');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE TABLE IF NOT EXISTS collections (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    owner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    kind collection_kind NOT NULL DEFAULT 'playlist',
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    visibility video_visibility NOT NULL DEFAULT 'public',
+    -- Video whose thumbnail is the cover; the first video's when NULL
+    cover_video_id UUID REFERENCES videos(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_collections_tenant_id ON collections(tenant_id);
+CREATE INDEX IF NOT EXISTS idx_collections_owner_id ON collections(owner_id);
+
+DROP TRIGGER IF EXISTS update_collections_updated_at ON collections;
+CREATE TRIGGER update_collections_updated_at
+    BEFORE UPDATE ON collections
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Videos of a collection; positions run from 0 without gaps
+CREATE TABLE IF NOT EXISTS collection_videos (
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    added_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (collection_id, video_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_videos_position ON collection_videos(collection_id, position);
+CREATE INDEX IF NOT EXISTS idx_collection_videos_video_id ON collection_videos(video_id);
@@ -0,0 +1,6 @@
+-- Revert: 015_video_version
+-- Remove the row version of videos
+DROP TRIGGER IF EXISTS bump_videos_version ON videos;
+DROP FUNCTION IF EXISTS bump_videos_version();
+
+ALTER TABLE videos DROP COLUMN IF EXISTS version;
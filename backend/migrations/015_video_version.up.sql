@@ -0,0 +1,17 @@
+-- Migration: 015_video_version
+-- Row version of videos, the ETag checked by conditional updates
+ALTER TABLE videos ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_videos_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS bump_videos_version ON videos;
+CREATE TRIGGER bump_videos_version
+    BEFORE UPDATE ON videos
+    FOR EACH ROW
+    EXECUTE FUNCTION bump_videos_version();
@@ -0,0 +1,9 @@
+-- Revert: 016_video_audit_log
+-- Remove the history of videos
+DROP TRIGGER IF EXISTS audit_video_changes ON videos;
+DROP FUNCTION IF EXISTS audit_video_changes();
+DROP FUNCTION IF EXISTS video_metadata_snapshot(videos);
+
+DROP TABLE IF EXISTS video_audit_log;
+DROP FUNCTION IF EXISTS reject_audit_log_changes();
+DROP TYPE IF EXISTS video_audit_action;
@@ -0,0 +1,146 @@
+-- Migration: 016_video_audit_log
+-- Append-only history of every change to a video
+DO $$ BEGIN
+    CREATE TYPE video_audit_action AS ENUM (
+        'created',
+        'metadata_updated',
+        'metadata_restored',
+        'status_changed',
+        'retranscoded',
+        'thumbnail_changed',
+        'processing_updated',
+        'deleted',
+        'restored',
+        'purged'
+    );
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+-- Entries outlive purged videos, so video_id has no foreign key
+CREATE TABLE IF NOT EXISTS video_audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL,
+    video_id UUID NOT NULL,
+    action video_audit_action NOT NULL,
+    -- NULL for changes made by background workers
+    actor_id UUID,
+    actor VARCHAR(255),
+    -- {"column": {"before": ..., "after": ...}}
+    changes JSONB NOT NULL DEFAULT '{}'::jsonb,
+    -- Metadata after the change, for entries that can be restored
+    snapshot JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_audit_log_video ON video_audit_log(video_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION reject_audit_log_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'video_audit_log is append-only';
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS reject_video_audit_log_changes ON video_audit_log;
+CREATE TRIGGER reject_video_audit_log_changes
+    BEFORE UPDATE OR DELETE ON video_audit_log
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_audit_log_changes();
+
+-- Editable metadata of a video, tags included
+CREATE OR REPLACE FUNCTION video_metadata_snapshot(v videos)
+RETURNS JSONB AS $$
+    SELECT jsonb_build_object(
+        'title', v.title,
+        'description', v.description,
+        'visibility', v.visibility,
+        'language', v.language,
+        'transcript', v.transcript,
+        'category_id', v.category_id,
+        'custom_metadata', v.custom_metadata,
+        'tags', COALESCE((
+            SELECT jsonb_agg(t.name ORDER BY LOWER(t.name))
+            FROM video_tags vt JOIN tags t ON t.id = vt.tag_id
+            WHERE vt.video_id = v.id
+        ), '[]'::jsonb)
+    )
+$$ language 'sql' STABLE;
+
+-- Record a change to a video. The API attributes its changes through the
+-- transaction-local settings videostream.actor_id and videostream.actor,
+-- and may add changes outside the row (tags) in videostream.audit_changes
+-- and override the action in videostream.audit_action.
+CREATE OR REPLACE FUNCTION audit_video_changes()
+RETURNS TRIGGER AS $$
+DECLARE
+    video videos;
+    entry_action video_audit_action;
+    entry_changes JSONB := '{}'::jsonb;
+    entry_snapshot JSONB;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        video := NEW;
+        entry_action := 'created';
+        entry_snapshot := video_metadata_snapshot(NEW);
+    ELSIF TG_OP = 'DELETE' THEN
+        video := OLD;
+        entry_action := 'purged';
+    ELSE
+        video := NEW;
+        SELECT COALESCE(jsonb_object_agg(n.key, jsonb_build_object('before', o.value, 'after', n.value)), '{}'::jsonb)
+        INTO entry_changes
+        FROM jsonb_each(to_jsonb(NEW)) n
+        JOIN jsonb_each(to_jsonb(OLD)) o ON o.key = n.key
+        WHERE n.value IS DISTINCT FROM o.value
+          AND n.key NOT IN ('updated_at', 'version', 'search_vector', 'deleted_by',
+                            'purge_after', 'purge_attempts', 'purge_error');
+        entry_changes := entry_changes
+            || COALESCE(NULLIF(current_setting('videostream.audit_changes', true), '')::jsonb, '{}'::jsonb);
+
+        -- Garbage collector leases and no-op updates are not changes
+        IF entry_changes = '{}'::jsonb THEN
+            RETURN NULL;
+        END IF;
+
+        IF entry_changes ? 'deleted_at' THEN
+            entry_action := CASE WHEN NEW.deleted_at IS NULL THEN 'restored' ELSE 'deleted' END;
+        ELSIF entry_changes ?| ARRAY['title', 'description', 'visibility', 'language', 'transcript',
+                                     'category_id', 'custom_metadata', 'tags'] THEN
+            entry_action := COALESCE(
+                NULLIF(current_setting('videostream.audit_action', true), '')::video_audit_action,
+                'metadata_updated'
+            );
+            entry_snapshot := video_metadata_snapshot(NEW);
+        ELSIF entry_changes ? 'status' THEN
+            entry_action := CASE
+                WHEN OLD.status IN ('ready', 'failed') AND NEW.status IN ('pending', 'processing')
+                    THEN 'retranscoded'
+                ELSE 'status_changed'
+            END;
+        ELSIF entry_changes ? 'thumbnail_path' THEN
+            entry_action := 'thumbnail_changed';
+        ELSE
+            entry_action := 'processing_updated';
+        END IF;
+    END IF;
+
+    INSERT INTO video_audit_log (tenant_id, video_id, action, actor_id, actor, changes, snapshot)
+    VALUES (
+        video.tenant_id,
+        video.id,
+        entry_action,
+        NULLIF(current_setting('videostream.actor_id', true), '')::uuid,
+        NULLIF(current_setting('videostream.actor', true), ''),
+        entry_changes,
+        entry_snapshot
+    );
+
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS audit_video_changes ON videos;
+CREATE TRIGGER audit_video_changes
+    AFTER INSERT OR UPDATE OR DELETE ON videos
+    FOR EACH ROW
+    EXECUTE FUNCTION audit_video_changes();
@@ -0,0 +1,4 @@
+-- Revert: 018_video_imports
+-- Remove video imports
+DROP TABLE IF EXISTS video_imports;
+DROP TYPE IF EXISTS import_status;
@@ -0,0 +1,39 @@
+-- Migration: 018_video_imports
+-- Videos imported from a URL, another bucket or a server-side path
+DO $$ BEGIN
+    CREATE TYPE import_status AS ENUM ('pending', 'downloading', 'completed', 'failed');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE TABLE IF NOT EXISTS video_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    video_id UUID NOT NULL UNIQUE REFERENCES videos(id) ON DELETE CASCADE,
+    -- http(s) URL, s3://bucket/key or absolute path
+    source TEXT NOT NULL,
+    status import_status NOT NULL DEFAULT 'pending',
+    -- Size and SHA-256 (hex) the file must have, when given with the request
+    expected_size BIGINT,
+    expected_sha256 CHAR(64),
+    -- Size announced by the source, NULL while unknown
+    total_bytes BIGINT,
+    bytes_received BIGINT NOT NULL DEFAULT 0,
+    -- SHA-256 (hex) of the imported file
+    sha256 CHAR(64),
+    error_message TEXT,
+    -- Claims of the import by a worker; a claim expires unless renewed
+    attempts INTEGER NOT NULL DEFAULT 0,
+    lease_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_imports_active
+    ON video_imports(created_at) WHERE status IN ('pending', 'downloading');
+
+DROP TRIGGER IF EXISTS update_video_imports_updated_at ON video_imports;
+CREATE TRIGGER update_video_imports_updated_at
+    BEFORE UPDATE ON video_imports
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,7 @@
+-- Revert: 019_bulk_operations
+-- Remove bulk operations
+DROP TABLE IF EXISTS bulk_operation_items;
+DROP TABLE IF EXISTS bulk_operations;
+DROP TYPE IF EXISTS bulk_item_status;
+DROP TYPE IF EXISTS bulk_operation_status;
+DROP TYPE IF EXISTS bulk_action;
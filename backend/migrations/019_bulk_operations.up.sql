@@ -0,0 +1,70 @@
+-- Migration: 019_bulk_operations
+-- Batch actions on many videos, run in the background with per-video results
+DO $$ BEGIN
+    CREATE TYPE bulk_action AS ENUM (
+        'delete', 'retranscode', 'set_visibility', 'add_tags', 'remove_tags', 'export'
+    );
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE bulk_operation_status AS ENUM ('pending', 'running', 'completed', 'cancelled', 'failed');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE bulk_item_status AS ENUM ('pending', 'succeeded', 'failed');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE TABLE IF NOT EXISTS bulk_operations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- The operation acts with the permissions of its creator, and of the API
+    -- key it was created with
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    api_key_id UUID,
+    scopes TEXT[],
+    action bulk_action NOT NULL,
+    -- Arguments of the action, e.g. the visibility or tags to set
+    params JSONB NOT NULL DEFAULT '{}',
+    status bulk_operation_status NOT NULL DEFAULT 'pending',
+    total INTEGER NOT NULL,
+    succeeded INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    -- Claims of the operation by a worker; a claim expires unless renewed
+    attempts INTEGER NOT NULL DEFAULT 0,
+    lease_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_bulk_operations_tenant_id ON bulk_operations(tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bulk_operations_active
+    ON bulk_operations(created_at) WHERE status IN ('pending', 'running');
+
+DROP TRIGGER IF EXISTS update_bulk_operations_updated_at ON bulk_operations;
+CREATE TRIGGER update_bulk_operations_updated_at
+    BEFORE UPDATE ON bulk_operations
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Videos of an operation in the order they are processed. Results outlive
+-- purged videos, so video_id has no foreign key.
+CREATE TABLE IF NOT EXISTS bulk_operation_items (
+    operation_id UUID NOT NULL REFERENCES bulk_operations(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    video_id UUID NOT NULL,
+    status bulk_item_status NOT NULL DEFAULT 'pending',
+    error TEXT,
+    -- Output of the action, e.g. the exported metadata
+    result JSONB,
+    processed_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (operation_id, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bulk_operation_items_pending
+    ON bulk_operation_items(operation_id, position) WHERE status = 'pending';
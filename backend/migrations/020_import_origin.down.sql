@@ -0,0 +1,4 @@
+-- Revert: 020_import_origin
+-- Remove the origin of imports
+ALTER TABLE video_imports DROP COLUMN IF EXISTS origin;
+DROP TYPE IF EXISTS import_origin;
@@ -0,0 +1,14 @@
+-- Migration: 020_import_origin
+-- Imports created by the ingest, whose sources skip the import allowlists
+DO $$ BEGIN
+    CREATE TYPE import_origin AS ENUM ('request', 'ingest');
+EXCEPTION WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE video_imports ADD COLUMN IF NOT EXISTS origin import_origin NOT NULL DEFAULT 'request';
+
+-- The ingest records its videos under its own actor
+UPDATE video_imports i SET origin = 'ingest'
+WHERE origin = 'request' AND EXISTS (
+    SELECT 1 FROM video_audit_log a WHERE a.video_id = i.video_id AND a.actor = 'ingest'
+);
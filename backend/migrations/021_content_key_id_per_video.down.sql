@@ -0,0 +1,5 @@
+-- Revert: 021_content_key_id_per_video
+-- Make key IDs globally unique again; fails while copies of a video share them
+DROP INDEX IF EXISTS idx_content_keys_video_key_id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_keys_key_id ON content_keys(key_id);
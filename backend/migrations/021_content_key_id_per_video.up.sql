@@ -0,0 +1,6 @@
+-- Migration: 021_content_key_id_per_video
+-- Key IDs only identify a key within its video, so that an imported copy of
+-- an encrypted video keeps the key IDs signalled in its media
+DROP INDEX IF EXISTS idx_content_keys_key_id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_keys_video_key_id ON content_keys(video_id, key_id);
@@ -0,0 +1,9 @@
+-- Revert: 022_video_version_on_edits
+-- Bump the row version of videos on every update again
+CREATE OR REPLACE FUNCTION bump_videos_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
@@ -0,0 +1,20 @@
+-- Migration: 022_video_version_on_edits
+-- Bump the row version of videos only when their editable metadata changes,
+-- so transcoding progress and garbage collection do not fail If-Match
+CREATE OR REPLACE FUNCTION bump_videos_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- The columns audit_video_changes records as metadata updates; tags live
+    -- outside the row and are reported through videostream.audit_changes
+    IF (NEW.title, NEW.description, NEW.visibility, NEW.language, NEW.transcript,
+        NEW.category_id, NEW.custom_metadata)
+       IS DISTINCT FROM
+       (OLD.title, OLD.description, OLD.visibility, OLD.language, OLD.transcript,
+        OLD.category_id, OLD.custom_metadata)
+       OR COALESCE(NULLIF(current_setting('videostream.audit_changes', true), '')::jsonb, '{}'::jsonb) ? 'tags'
+    THEN
+        NEW.version = OLD.version + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
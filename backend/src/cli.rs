@@ -0,0 +1,297 @@
+use clap::{Args, Parser, Subcommand};
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::{self, MIGRATOR};
+use crate::handlers::auth::validate_account;
+use crate::handlers::video::select_video_ids;
+use crate::models::{QuotaLimits, Role, Tenant, VideoFilter};
+use crate::services::{AuthService, CatalogService, KeyService, QuotaService, StorageService, TenantService};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Open-source video streaming backend; starts the server unless given a command
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Export videos, with their metadata and stored objects, to a catalog archive
+    Export(ExportArgs),
+    /// Import the videos of a catalog archive
+    Import(ImportArgs),
+    /// Create an admin account, e.g. the first one of a tenant
+    CreateAdmin(CreateAdminArgs),
+    /// Set the limits of a tenant as a whole; omitted limits are unlimited
+    SetQuota(SetQuotaArgs),
+    /// Show, apply or revert database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// List the migrations and whether each is applied
+    Status,
+    /// Apply the pending migrations, as the server does on startup
+    Up,
+    /// Revert applied migrations
+    Down(DownArgs),
+}
+
+#[derive(Args)]
+pub struct DownArgs {
+    /// Revert every migration newer than this version [default: only the latest]
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<i64>,
+}
+
+#[derive(Args)]
+pub struct CreateAdminArgs {
+    pub username: String,
+    pub email: String,
+    /// Slug of the tenant of the account [default: DEFAULT_TENANT]
+    #[arg(long)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SetQuotaArgs {
+    /// Slug of the tenant to limit [default: DEFAULT_TENANT]
+    #[arg(long)]
+    pub tenant: Option<String>,
+    /// Total size of uploaded originals
+    #[arg(long, value_name = "BYTES")]
+    pub max_original_bytes: Option<i64>,
+    /// Total size of transcoded renditions
+    #[arg(long, value_name = "BYTES")]
+    pub max_rendition_bytes: Option<i64>,
+    /// Number of videos, including those in the trash bin
+    #[arg(long, value_name = "COUNT")]
+    pub max_videos: Option<i64>,
+    /// Size of a single upload
+    #[arg(long, value_name = "BYTES")]
+    pub max_upload_bytes: Option<i64>,
+    /// Duration of a single video
+    #[arg(long, value_name = "SECONDS")]
+    pub max_duration_seconds: Option<f64>,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Archive to write (tar)
+    pub archive: PathBuf,
+    /// Slug of the tenant to export from [default: DEFAULT_TENANT]
+    #[arg(long)]
+    pub tenant: Option<String>,
+    /// Export this video; may be repeated [default: every video]
+    #[arg(long = "video", value_name = "ID")]
+    pub videos: Vec<Uuid>,
+    /// Export the videos matching a filter of `GET /videos`, as a JSON object
+    #[arg(long, value_name = "JSON", conflicts_with = "videos")]
+    pub filter: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Archive to read
+    pub archive: PathBuf,
+    /// Slug of the tenant to import into [default: DEFAULT_TENANT]
+    #[arg(long)]
+    pub tenant: Option<String>,
+    /// Username owning the videos whose owner has no account in the tenant
+    #[arg(long, value_name = "USERNAME")]
+    pub owner: Option<String>,
+}
+
+/// Run a command instead of the server
+pub async fn run(command: Command, config: &Config) -> Result<(), BoxError> {
+    let db_pool = db::create_pool(&config.database_url).await?;
+    let tenants = TenantService::new(config, db_pool.clone());
+    // Accounts, quotas and migrations need neither storage nor content keys
+    let command = match command {
+        Command::Migrate { action } => return migrate(action, &db_pool).await,
+        Command::CreateAdmin(args) => return create_admin(args, config, &db_pool, &tenants).await,
+        Command::SetQuota(args) => return set_quota(args, config, &db_pool, &tenants).await,
+        command => command,
+    };
+    let catalog = CatalogService::new(
+        config,
+        db_pool.clone(),
+        StorageService::new(config).await?,
+        KeyService::new(config, db_pool.clone())?,
+    );
+
+    match command {
+        Command::Export(args) => {
+            let tenant = tenant(&tenants, args.tenant.as_deref()).await?;
+            let video_ids = match args.filter {
+                Some(filter) => {
+                    let filter: VideoFilter = serde_json::from_str(&filter)
+                        .map_err(|e| format!("Invalid filter: {}", e))?;
+                    Some(select_video_ids(&db_pool, tenant.id, None, true, &filter, false, i64::MAX).await?)
+                }
+                None if args.videos.is_empty() => None,
+                None => Some(args.videos),
+            };
+
+            let manifest = catalog.export(&tenant, video_ids.as_deref(), &args.archive).await?;
+            println!("Exported {} videos to {}", manifest.videos.len(), args.archive.display());
+        }
+        Command::Import(args) => {
+            let tenant = tenant(&tenants, args.tenant.as_deref()).await?;
+            let owner_id = match &args.owner {
+                Some(username) => Some(
+                    sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE tenant_id = $1 AND username = $2")
+                        .bind(tenant.id)
+                        .bind(username)
+                        .fetch_optional(&db_pool)
+                        .await?
+                        .ok_or_else(|| format!("User {} not found", username))?,
+                ),
+                None => None,
+            };
+
+            let outcome = catalog.import(&tenant, owner_id, &args.archive).await?;
+            for (archived_id, id) in &outcome.imported {
+                if archived_id != id {
+                    println!("{} imported as {}", archived_id, id);
+                }
+            }
+            for (archived_id, error) in &outcome.failed {
+                eprintln!("{} not imported: {}", archived_id, error);
+            }
+            println!(
+                "Imported {} videos from {}",
+                outcome.imported.len(),
+                args.archive.display()
+            );
+            if !outcome.failed.is_empty() {
+                return Err(format!("{} videos could not be imported", outcome.failed.len()).into());
+            }
+        }
+        Command::CreateAdmin(_) | Command::SetQuota(_) | Command::Migrate { .. } => {
+            unreachable!("handled above")
+        }
+    }
+
+    Ok(())
+}
+
+async fn migrate(action: MigrateCommand, db_pool: &PgPool) -> Result<(), BoxError> {
+    match action {
+        MigrateCommand::Status => {
+            let applied = db::applied_migrations(db_pool).await?;
+            for migration in MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()) {
+                let state = match applied.iter().find(|a| a.version == migration.version) {
+                    Some(a) if a.checksum != *migration.checksum => {
+                        format!("modified since applied on {}", a.installed_on)
+                    }
+                    Some(a) => format!("applied on {}", a.installed_on),
+                    None => "pending".to_string(),
+                };
+                println!("{:03} {:<24} {}", migration.version, migration.description, state);
+            }
+            // Applied by a newer build
+            for a in applied.iter().filter(|a| !MIGRATOR.version_exists(a.version)) {
+                println!(
+                    "{:03} {:<24} applied on {}, unknown to this build",
+                    a.version, a.description, a.installed_on
+                );
+            }
+        }
+        MigrateCommand::Up => {
+            db::run_migrations(db_pool).await.map_err(|e| e.to_string())?;
+            println!("Database schema is up to date");
+        }
+        MigrateCommand::Down(args) => {
+            let applied = db::applied_migrations(db_pool).await?;
+            let target = match args.to {
+                Some(version) => version,
+                // Keep every migration but the latest
+                None => match applied.iter().rev().nth(1) {
+                    Some(previous) => previous.version,
+                    None if applied.is_empty() => {
+                        println!("No migration to revert");
+                        return Ok(());
+                    }
+                    None => 0,
+                },
+            };
+
+            db::revert_migrations(db_pool, target).await.map_err(|e| e.to_string())?;
+            for a in applied.iter().rev().filter(|a| a.version > target) {
+                println!("Reverted {:03} {}", a.version, a.description);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn create_admin(
+    args: CreateAdminArgs,
+    config: &Config,
+    db_pool: &PgPool,
+    tenants: &TenantService,
+) -> Result<(), BoxError> {
+    let tenant = tenant(tenants, args.tenant.as_deref()).await?;
+    // Read from stdin so the password stays out of the shell history
+    eprintln!("Password for {}:", args.username);
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+
+    let username = args.username.trim();
+    let email = args.email.trim();
+    validate_account(username, email, password).map_err(|e| e.detail())?;
+
+    let user = AuthService::new(config, db_pool.clone())?
+        .create_user(tenant.id, username, email, password, Role::Admin)
+        .await
+        .map_err(|e| e.to_string())?;
+    println!("Created admin {} ({}) in tenant {}", user.username, user.id, tenant.slug);
+    Ok(())
+}
+
+async fn set_quota(
+    args: SetQuotaArgs,
+    config: &Config,
+    db_pool: &PgPool,
+    tenants: &TenantService,
+) -> Result<(), BoxError> {
+    let tenant = tenant(tenants, args.tenant.as_deref()).await?;
+    let limits = QuotaLimits {
+        max_original_bytes: args.max_original_bytes,
+        max_rendition_bytes: args.max_rendition_bytes,
+        max_videos: args.max_videos,
+        max_upload_bytes: args.max_upload_bytes,
+        max_duration_seconds: args.max_duration_seconds,
+    };
+    if limits.any_negative() {
+        return Err("Limits cannot be negative".into());
+    }
+
+    QuotaService::new(config, db_pool.clone())
+        .set_tenant_limits(tenant.id, &limits)
+        .await?;
+    println!("Set the quota of tenant {}", tenant.slug);
+    Ok(())
+}
+
+async fn tenant(tenants: &TenantService, slug: Option<&str>) -> Result<Arc<Tenant>, BoxError> {
+    let tenant = match slug {
+        Some(slug) => tenants.by_slug(slug).await?,
+        None => tenants.resolve(None).await?,
+    };
+    Ok(tenant.ok_or("Tenant not found")?)
+}
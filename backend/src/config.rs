@@ -1,4 +1,47 @@
+use ipnet::IpNet;
 use std::env;
+use std::net::IpAddr;
+
+use crate::models::{QuotaLimits, Role};
+
+/// Encryption applied to HLS segments, selected by `HLS_ENCRYPTION`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HlsEncryption {
+    /// Segments are stored and served in clear
+    None,
+    /// Whole-segment AES-128-CBC with keys from the key server
+    Aes128,
+    /// CMAF common encryption, AES-CTR scheme, with Clear Key DRM
+    Cenc,
+    /// CMAF common encryption, AES-CBC pattern scheme, with Clear Key DRM
+    Cbcs,
+}
+
+impl HlsEncryption {
+    /// Value stored in `videos.encryption`
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Aes128 => Some("AES-128"),
+            Self::Cenc => Some("cenc"),
+            Self::Cbcs => Some("cbcs"),
+        }
+    }
+
+    /// Whether the CMAF output is packaged with common encryption (CENC)
+    pub fn is_common_encryption(&self) -> bool {
+        matches!(self, Self::Cenc | Self::Cbcs)
+    }
+}
+
+/// Object storage implementation selected by `STORAGE_BACKEND`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// MinIO or any S3-compatible service
+    S3,
+    /// Directories on the local filesystem, served by the backend
+    Local,
+}
 
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
@@ -6,6 +49,8 @@ pub struct Config {
     // Server
     pub server_host: String,
     pub server_port: u16,
+    pub cors_allowed_origins: Vec<String>,
+    pub trusted_proxies: Vec<IpNet>,
 
     // Database
     pub database_url: String,
@@ -13,6 +58,10 @@ pub struct Config {
     // Redis
     pub redis_url: String,
 
+    // Storage
+    pub storage_backend: StorageBackend,
+    pub local_storage_root: String,
+
     // MinIO/S3
     pub minio_endpoint: String,
     pub minio_access_key: String,
@@ -21,15 +70,69 @@ pub struct Config {
     pub minio_bucket_segments: String,
     pub minio_bucket_manifests: String,
 
+    // Authentication
+    pub jwt_secret: String,
+    pub access_token_ttl_secs: i64,
+    pub refresh_token_ttl_days: i64,
+    pub api_key_default_rate_limit: i32,
+    pub default_user_role: Role,
+    pub default_tenant: String,
+
+    // Quotas of users without limits of their own
+    pub default_quota: QuotaLimits,
+
+    // Playback tokens
+    pub playback_token_secret: String,
+    pub playback_token_ttl_secs: i64,
+
+    // Content encryption
+    pub hls_encryption: HlsEncryption,
+    pub hls_key_rotation_segments: u64,
+    pub content_key_master_key: Option<String>,
+
     // FFmpeg
     pub ffmpeg_path: String,
     pub ffprobe_path: String,
+    pub packager_path: String,
     pub temp_dir: String,
+
+    // Search
+    pub search_language: String,
+
+    // Garbage collection
+    pub gc_interval_secs: u64,
+    pub gc_batch_size: i64,
+    pub gc_processing_timeout_secs: u64,
+    pub trash_retention_days: i64,
+
+    // Webhooks
+    pub webhook_interval_secs: u64,
+    pub webhook_batch_size: i64,
+    pub webhook_max_attempts: i32,
+    pub webhook_timeout_secs: u64,
+
+    // Imports
+    pub import_allowed_hosts: Vec<String>,
+    pub import_allowed_paths: Vec<String>,
+    pub import_allowed_buckets: Vec<String>,
+    pub import_workers: usize,
+
+    // Ingest
+    pub ingest_watch_dir: Option<String>,
+    pub ingest_archive_dir: Option<String>,
+    pub ingest_scan_interval_secs: u64,
+    pub ingest_settle_secs: u64,
+    pub ingest_bucket: Option<String>,
+    pub ingest_prefix: String,
+    pub ingest_archive_prefix: String,
+    pub ingest_notify_token: Option<String>,
+    pub ingest_tenant: Option<String>,
+    pub ingest_owner: Option<String>,
 }
 
 impl Config {
     /// Load configuration from environment variables
-    pub fn from_env() -> Result<Self, env::VarError> {
+    pub fn from_env() -> Result<Self, String> {
         Ok(Self {
             server_host: env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
             server_port: env::var("SERVER_PORT")
@@ -37,10 +140,37 @@ impl Config {
                 .parse()
                 .unwrap_or(3000),
 
-            database_url: env::var("DATABASE_URL")?,
+            cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect(),
+
+            // Addresses or CIDR ranges; a bare address is a single host
+            trusted_proxies: env::var("TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter_map(|proxy| {
+                    proxy
+                        .parse::<IpNet>()
+                        .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+                        .ok()
+                })
+                .collect(),
+
+            database_url: required("DATABASE_URL")?,
 
             redis_url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string()),
 
+            storage_backend: match env::var("STORAGE_BACKEND").as_deref() {
+                Ok("local") => StorageBackend::Local,
+                _ => StorageBackend::S3,
+            },
+            local_storage_root: env::var("LOCAL_STORAGE_ROOT")
+                .unwrap_or_else(|_| "./storage".to_string()),
+
             minio_endpoint: env::var("MINIO_ENDPOINT")
                 .unwrap_or_else(|_| "http://localhost:9000".to_string()),
             minio_access_key: env::var("MINIO_ACCESS_KEY")
@@ -54,9 +184,163 @@ impl Config {
             minio_bucket_manifests: env::var("MINIO_BUCKET_MANIFESTS")
                 .unwrap_or_else(|_| "manifests".to_string()),
 
+            // Not generated when missing: every restart and every other
+            // replica would reject the tokens already issued
+            jwt_secret: required("JWT_SECRET")?,
+            access_token_ttl_secs: env::var("ACCESS_TOKEN_TTL_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .unwrap_or(900),
+            refresh_token_ttl_days: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            api_key_default_rate_limit: env::var("API_KEY_DEFAULT_RATE_LIMIT")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+
+            // Registration is open, so it never hands out more than upload
+            // rights; higher roles are granted by an admin
+            default_user_role: env::var("DEFAULT_USER_ROLE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|role| *role <= Role::Uploader)
+                .unwrap_or(Role::Viewer),
+            default_tenant: env::var("DEFAULT_TENANT").unwrap_or_else(|_| "default".to_string()),
+
+            default_quota: QuotaLimits {
+                max_original_bytes: env::var("QUOTA_MAX_ORIGINAL_BYTES").ok().and_then(|v| v.parse().ok()),
+                max_rendition_bytes: env::var("QUOTA_MAX_RENDITION_BYTES").ok().and_then(|v| v.parse().ok()),
+                max_videos: env::var("QUOTA_MAX_VIDEOS").ok().and_then(|v| v.parse().ok()),
+                max_upload_bytes: env::var("QUOTA_MAX_UPLOAD_BYTES").ok().and_then(|v| v.parse().ok()),
+                max_duration_seconds: env::var("QUOTA_MAX_DURATION_SECS").ok().and_then(|v| v.parse().ok()),
+            },
+
+            playback_token_secret: required("PLAYBACK_TOKEN_SECRET")?,
+            playback_token_ttl_secs: env::var("PLAYBACK_TOKEN_TTL_SECS")
+                .unwrap_or_else(|_| "14400".to_string())
+                .parse()
+                .unwrap_or(14400),
+
+            hls_encryption: hls_encryption(env::var("HLS_ENCRYPTION").ok().as_deref())?,
+            hls_key_rotation_segments: env::var("HLS_KEY_ROTATION_SEGMENTS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            content_key_master_key: env::var("CONTENT_KEY_MASTER_KEY").ok(),
+
             ffmpeg_path: env::var("FFMPEG_PATH").unwrap_or_else(|_| "ffmpeg".to_string()),
             ffprobe_path: env::var("FFPROBE_PATH").unwrap_or_else(|_| "ffprobe".to_string()),
+            packager_path: env::var("PACKAGER_PATH").unwrap_or_else(|_| "packager".to_string()),
             temp_dir: env::var("TEMP_DIR").unwrap_or_else(|_| "/tmp/uploads".to_string()),
+
+            search_language: env::var("SEARCH_LANGUAGE").unwrap_or_else(|_| "english".to_string()),
+
+            gc_interval_secs: env::var("GC_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            gc_batch_size: env::var("GC_BATCH_SIZE")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            gc_processing_timeout_secs: env::var("GC_PROCESSING_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "21600".to_string())
+                .parse()
+                .unwrap_or(21600),
+            trash_retention_days: env::var("TRASH_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+
+            webhook_interval_secs: env::var("WEBHOOK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            webhook_batch_size: env::var("WEBHOOK_BATCH_SIZE")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            webhook_max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            webhook_timeout_secs: env::var("WEBHOOK_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+
+            import_allowed_hosts: env::var("IMPORT_ALLOWED_HOSTS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|host| host.trim().to_ascii_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            import_allowed_paths: env::var("IMPORT_ALLOWED_PATHS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .collect(),
+            import_allowed_buckets: env::var("IMPORT_ALLOWED_BUCKETS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|bucket| bucket.trim().to_string())
+                .filter(|bucket| !bucket.is_empty())
+                .collect(),
+            import_workers: env::var("IMPORT_WORKERS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+
+            ingest_watch_dir: env::var("INGEST_WATCH_DIR").ok().filter(|dir| !dir.is_empty()),
+            ingest_archive_dir: env::var("INGEST_ARCHIVE_DIR").ok().filter(|dir| !dir.is_empty()),
+            ingest_scan_interval_secs: env::var("INGEST_SCAN_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            ingest_settle_secs: env::var("INGEST_SETTLE_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            ingest_bucket: env::var("INGEST_BUCKET").ok().filter(|bucket| !bucket.is_empty()),
+            ingest_prefix: env::var("INGEST_PREFIX").unwrap_or_default(),
+            ingest_archive_prefix: env::var("INGEST_ARCHIVE_PREFIX")
+                .unwrap_or_else(|_| "archive/".to_string()),
+            ingest_notify_token: env::var("INGEST_NOTIFY_TOKEN").ok().filter(|token| !token.is_empty()),
+            ingest_tenant: env::var("INGEST_TENANT").ok().filter(|tenant| !tenant.is_empty()),
+            ingest_owner: env::var("INGEST_OWNER").ok().filter(|owner| !owner.is_empty()),
         })
     }
 }
+
+/// Value of a variable the server cannot start without
+/// Parse `HLS_ENCRYPTION`, refusing methods that would otherwise leave
+/// segments in clear
+fn hls_encryption(value: Option<&str>) -> Result<HlsEncryption, String> {
+    match value.map(str::trim) {
+        None | Some("") | Some("none") => Ok(HlsEncryption::None),
+        Some("aes-128") => Ok(HlsEncryption::Aes128),
+        Some("cenc") => Ok(HlsEncryption::Cenc),
+        Some("cbcs") => Ok(HlsEncryption::Cbcs),
+        // SAMPLE-AES of MPEG-TS segments encrypts inside every audio and
+        // video frame, which the whole-segment AES-128 encryption cannot do;
+        // `cbcs` signals SAMPLE-AES for CMAF segments packaged that way
+        Some("sample-aes") => Err(
+            "HLS_ENCRYPTION=sample-aes is not supported; use cbcs for SAMPLE-AES encrypted CMAF segments"
+                .to_string(),
+        ),
+        Some(other) => Err(format!(
+            "Unknown HLS_ENCRYPTION {}; expected none, aes-128, cenc or cbcs",
+            other
+        )),
+    }
+}
+
+fn required(name: &str) -> Result<String, String> {
+    env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("{} must be set", name))
+}
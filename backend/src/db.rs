@@ -1,6 +1,37 @@
+use chrono::{DateTime, Utc};
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
 use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashSet;
 use std::time::Duration;
+use thiserror::Error;
+
+/// Migrations of `migrations/`, embedded at build time
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("database schema is at migration {0}, newer than this build; upgrade the backend or run `migrate down` with the newer build")]
+    SchemaTooNew(i64),
+
+    #[error(transparent)]
+    Migrate(#[from] MigrateError),
+}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(e: sqlx::Error) -> Self {
+        MigrationError::Migrate(e.into())
+    }
+}
+
+/// Migration recorded as applied in the database
+#[derive(Debug, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: DateTime<Utc>,
+    pub checksum: Vec<u8>,
+}
 
 /// Create a PostgreSQL connection pool
 pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
@@ -13,14 +44,75 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
         .await
 }
 
-/// Run database migrations
-pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::Error> {
-    // The migrations are handled by the init-db.sql script in Docker
-    // This function ensures the connection is working
-    sqlx::query("SELECT 1")
-        .execute(pool)
-        .await?;
-    
-    tracing::info!("Database connection verified");
+/// Apply pending migrations, refusing a schema migrated by a newer build.
+/// Replicas starting together take turns on an advisory lock, so only the
+/// first applies them.
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrationError> {
+    let mut conn = pool.acquire().await?;
+    conn.lock().await?;
+
+    match migrate(&mut conn).await {
+        Ok(()) => {
+            conn.unlock().await?;
+            Ok(())
+        }
+        Err(e) => {
+            // Closing the session releases the locks a failed run keeps
+            let _ = conn.close().await;
+            Err(e)
+        }
+    }
+}
+
+async fn migrate(conn: &mut PgConnection) -> Result<(), MigrationError> {
+    conn.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+
+    if let Some(version) = applied.iter().copied().filter(|v| !MIGRATOR.version_exists(*v)).max() {
+        return Err(MigrationError::SchemaTooNew(version));
+    }
+
+    let pending = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration() && !applied.contains(&m.version))
+        .count();
+
+    MIGRATOR.run(&mut *conn).await?;
+
+    if pending > 0 {
+        tracing::info!("Applied {} database migrations", pending);
+    }
     Ok(())
 }
+
+/// Revert the applied migrations newer than `target`
+pub async fn revert_migrations(pool: &PgPool, target: i64) -> Result<(), MigrationError> {
+    let mut conn = pool.acquire().await?;
+    if let Err(e) = MIGRATOR.undo(&mut *conn, target).await {
+        let _ = conn.close().await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Migrations recorded in the database, oldest first
+pub async fn applied_migrations(pool: &PgPool) -> Result<Vec<AppliedMigration>, sqlx::Error> {
+    // Databases never migrated have no table yet
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !tracked {
+        return Ok(Vec::new());
+    }
+
+    sqlx::query_as(
+        "SELECT version, description, installed_on, checksum FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -1,10 +1,16 @@
 use axum::{
-    http::StatusCode,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
 use thiserror::Error;
+use uuid::Uuid;
+
+use crate::request_id;
+use crate::services::auth::AuthError;
+use crate::services::quotas::QuotaError;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -14,15 +20,42 @@ pub enum AppError {
     #[error("Storage error: {0}")]
     Storage(String),
 
-    #[error("Transcoding error: {0}")]
-    // Transcoding variant currently unused; remove to silence dead-code warning
-
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Video {0} not found")]
+    VideoNotFound(Uuid),
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Invalid request: {}", FieldError::join(.0))]
+    Validation(Vec<FieldError>),
+
+    #[error("Unsupported codec: {0}")]
+    UnsupportedCodec(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Rate limit exceeded, retry in {0} seconds")]
+    RateLimited(u64),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -30,46 +63,233 @@ pub enum AppError {
     Redis(#[from] redis::RedisError),
 }
 
+/// Invalid member of a request, listed in the `errors` of its problem
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    /// JSON Pointer to the member, e.g. `/custom_metadata/genre`
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+
+    fn join(errors: &[FieldError]) -> String {
+        errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Body of error responses, an RFC 7807 problem details object
+#[derive(Serialize)]
+struct Problem<'a> {
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: &'a str,
+    code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "<[FieldError]>::is_empty")]
+    errors: &'a [FieldError],
+}
+
+impl AppError {
+    /// Error of a single invalid member of the request
+    pub fn invalid(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::Validation(vec![FieldError::new(field, message)])
+    }
+
+    /// HTTP status of the error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Database(_)
+            | AppError::Storage(_)
+            | AppError::Internal(_)
+            | AppError::Redis(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotFound(_) | AppError::VideoNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::UnsupportedCodec(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) | AppError::QuotaExceeded(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Stable, machine-readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "DATABASE_ERROR",
+            AppError::Storage(_) => "STORAGE_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::VideoNotFound(_) => "VIDEO_NOT_FOUND",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Validation(_) => "VALIDATION_FAILED",
+            AppError::UnsupportedCodec(_) => "UNSUPPORTED_CODEC",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::PreconditionFailed(_) => "PRECONDITION_FAILED",
+            AppError::PayloadTooLarge(_) => "UPLOAD_TOO_LARGE",
+            AppError::QuotaExceeded(_) => "QUOTA_EXCEEDED",
+            AppError::RateLimited(_) => "RATE_LIMITED",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+            AppError::Redis(_) => "QUEUE_ERROR",
+        }
+    }
+
+    /// Description of the error shown to clients; server errors are
+    /// described generically, their details only go to the logs
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::Database(_) => "Database error occurred".to_string(),
+            AppError::Storage(_) => "Storage error occurred".to_string(),
+            AppError::Internal(_) => "Internal server error".to_string(),
+            AppError::Redis(_) => "Queue error occurred".to_string(),
+            AppError::RateLimited(_) => "Rate limit exceeded".to_string(),
+            AppError::Validation(errors) => FieldError::join(errors),
+            AppError::VideoNotFound(_) => self.to_string(),
+            AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::UnsupportedCodec(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg)
+            | AppError::PreconditionFailed(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::QuotaExceeded(msg) => msg.clone(),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match &self {
-            AppError::Database(e) => {
-                tracing::error!("Database error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error occurred")
-            }
-            AppError::Storage(msg) => {
-                tracing::error!("Storage error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Storage error occurred")
-            }
-            AppError::Transcoding(msg) => {
-                tracing::error!("Transcoding error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Transcoding error occurred")
+        let status = self.status();
+        match &self {
+            AppError::Database(e) => tracing::error!("Database error: {:?}", e),
+            AppError::Redis(e) => tracing::error!("Redis error: {:?}", e),
+            _ if status.is_server_error() => tracing::error!("{}", self),
+            _ => tracing::warn!("{}", self),
+        }
+
+        let detail = self.detail();
+        let errors = match &self {
+            AppError::Validation(errors) => errors.as_slice(),
+            _ => &[],
+        };
+        let problem = Problem {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: &detail,
+            code: self.code(),
+            request_id: request_id::current(),
+            errors,
+        };
+
+        let mut response = (status, Json(problem)).into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        match self {
+            AppError::Unauthorized(_) => {
+                headers.insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
             }
-            AppError::NotFound(msg) => {
-                tracing::warn!("Not found: {}", msg);
-                (StatusCode::NOT_FOUND, msg.as_str())
+            AppError::RateLimited(retry_after) => {
+                headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
             }
-            AppError::BadRequest(msg) => {
-                tracing::warn!("Bad request: {}", msg);
-                (StatusCode::BAD_REQUEST, msg.as_str())
+            _ => {}
+        }
+        response
+    }
+}
+
+impl From<AuthError> for AppError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::InvalidCredentials | AuthError::InvalidToken => {
+                AppError::Unauthorized(e.to_string())
             }
-            AppError::Internal(msg) => {
-                tracing::error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            AuthError::AlreadyRegistered => AppError::Conflict(e.to_string()),
+            AuthError::RateLimited(retry_after) => AppError::RateLimited(retry_after),
+            AuthError::Database(e) => AppError::Database(e),
+            AuthError::Hashing(_) | AuthError::Signing(_) => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<QuotaError> for AppError {
+    fn from(e: QuotaError) -> Self {
+        match e {
+            QuotaError::UploadTooLarge { .. } => AppError::PayloadTooLarge(e.to_string()),
+            QuotaError::Exceeded(_) => AppError::QuotaExceeded(e.to_string()),
+            QuotaError::Database(e) => AppError::Database(e),
+        }
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match &rejection {
+            // A body of the wrong shape is reported against the offending member
+            JsonRejection::JsonDataError(e) => {
+                let mut source: Option<&dyn std::error::Error> = Some(e);
+                while let Some(error) = source {
+                    if let Some(error) =
+                        error.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>()
+                    {
+                        return AppError::invalid(json_pointer(error.path()), error.inner().to_string());
+                    }
+                    source = error.source();
+                }
+                AppError::BadRequest(rejection.body_text())
             }
-            AppError::Redis(e) => {
-                tracing::error!("Redis error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Queue error occurred")
+            _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                AppError::PayloadTooLarge(rejection.body_text())
             }
-        };
+            _ => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
 
-        let body = Json(json!({
-            "error": error_message,
-            "success": false
-        }));
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        match rejection.status() {
+            StatusCode::INTERNAL_SERVER_ERROR => AppError::Internal(rejection.body_text()),
+            _ => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
 
-        (status, body).into_response()
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
     }
 }
 
+/// JSON Pointer to the member of a request body at `path`
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Seq { index } => format!("/{}", index),
+            Segment::Map { key } => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+            Segment::Enum { variant } => format!("/{}", variant),
+            Segment::Unknown => String::new(),
+        })
+        .collect()
+}
+
 pub type Result<T> = std::result::Result<T, AppError>;
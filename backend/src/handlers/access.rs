@@ -0,0 +1,129 @@
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{Scope, Tenant, Video, Visibility};
+
+/// Condition on `videos` selecting the videos listed to a caller.
+///
+/// `$1` is the caller's user ID (NULL when anonymous), `$2` whether the
+/// caller sees every video of the tenant and `$3` the tenant ID. Unlisted
+/// videos are only listed to their owner and to users they are shared with.
+pub(crate) const LISTED_TO_CALLER: &str = r#"
+    videos.tenant_id = $3 AND ($2 OR videos.visibility = 'public' OR videos.owner_id = $1 OR EXISTS (
+        SELECT 1 FROM video_shares s
+        LEFT JOIN group_members m ON m.group_id = s.group_id
+        WHERE s.video_id = videos.id AND (s.user_id = $1 OR m.user_id = $1)
+    ))
+"#;
+
+/// Condition on `videos` selecting the videos a caller may watch, with the
+/// bind parameters of [`LISTED_TO_CALLER`]; see [`can_view`].
+pub(crate) const VIEWABLE_BY_CALLER: &str = r#"
+    videos.tenant_id = $3 AND ($2 OR videos.visibility <> 'private' OR videos.owner_id = $1 OR EXISTS (
+        SELECT 1 FROM video_shares s
+        LEFT JOIN group_members m ON m.group_id = s.group_id
+        WHERE s.video_id = videos.id AND (s.user_id = $1 OR m.user_id = $1)
+    ))
+"#;
+
+/// Columns of a [`Video`] row: those of `videos` and the tag names
+pub(crate) const VIDEO_COLUMNS: &str = r#"
+    videos.*, ARRAY(
+        SELECT t.name::text FROM video_tags vt JOIN tags t ON t.id = vt.tag_id
+        WHERE vt.video_id = videos.id ORDER BY LOWER(t.name)
+    ) AS tags
+"#;
+
+/// Bind parameters of [`LISTED_TO_CALLER`] for a caller
+pub(crate) fn listing_params(user: Option<&AuthUser>) -> Result<(Option<Uuid>, bool)> {
+    match user {
+        Some(user) => {
+            user.require(Scope::VideosRead)?;
+            Ok((Some(user.id), user.sees_all_videos()))
+        }
+        None => Ok((None, false)),
+    }
+}
+
+/// Whether a caller may watch a video.
+///
+/// Public and unlisted videos are open to anyone; private ones to their
+/// owner, editors, and users the video is shared with directly or through
+/// a group.
+pub(crate) async fn can_view(state: &AppState, video: &Video, user: Option<&AuthUser>) -> Result<bool> {
+    if video.visibility != Visibility::Private {
+        return Ok(true);
+    }
+
+    let Some(user) = user else {
+        return Ok(false);
+    };
+    if user.can_manage(video.owner_id) {
+        return Ok(true);
+    }
+
+    let shared = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM video_shares s
+            LEFT JOIN group_members m ON m.group_id = s.group_id
+            WHERE s.video_id = $1 AND (s.user_id = $2 OR m.user_id = $2)
+        )
+        "#,
+    )
+    .bind(video.id)
+    .bind(user.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(shared)
+}
+
+/// Load a video of the tenant the caller may watch.
+///
+/// Private videos the caller has no access to are reported as missing, so
+/// their existence is not revealed.
+pub(crate) async fn viewable_video(
+    state: &AppState,
+    tenant: &Tenant,
+    id: Uuid,
+    user: Option<&AuthUser>,
+) -> Result<Video> {
+    if let Some(user) = user {
+        user.require(Scope::VideosRead)?;
+    }
+    load_visible(state, tenant, id, user).await
+}
+
+/// Load a video the caller may modify: their own, or any video for editors
+pub(crate) async fn manageable_video(state: &AppState, id: Uuid, user: &AuthUser) -> Result<Video> {
+    let video = load_visible(state, &user.tenant, id, Some(user)).await?;
+
+    if !user.can_manage(video.owner_id) {
+        return Err(AppError::Forbidden(format!("Not allowed to modify video {}", id)));
+    }
+
+    Ok(video)
+}
+
+async fn load_visible(
+    state: &AppState,
+    tenant: &Tenant,
+    id: Uuid,
+    user: Option<&AuthUser>,
+) -> Result<Video> {
+    let video = sqlx::query_as::<_, Video>(&format!(
+        "SELECT {} FROM videos WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+        VIDEO_COLUMNS
+    ))
+    .bind(id)
+    .bind(tenant.id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    match video {
+        Some(video) if can_view(state, &video, user).await? => Ok(video),
+        _ => Err(AppError::VideoNotFound(id)),
+    }
+}
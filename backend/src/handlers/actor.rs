@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+use std::convert::Infallible;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::handlers::AppState;
+use crate::models::{Role, Scope, Tenant};
+use crate::services::api_keys::API_KEY_PREFIX;
+
+/// Authenticated user, required by routes that modify data
+///
+/// Taken from the `Authorization: Bearer` header, which carries either a JWT
+/// access token or an API key; requests without a valid one are rejected
+/// with 401, as are tokens presented on a host assigned to another tenant.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub tenant: Arc<Tenant>,
+    pub username: String,
+    pub role: Role,
+    /// API key the request was made with, if any
+    pub api_key_id: Option<Uuid>,
+    /// Scopes of the API key; `None` for user sessions, which are unrestricted
+    pub scopes: Option<Vec<Scope>>,
+}
+
+impl AuthUser {
+    /// Reject API keys lacking `scope`; `admin` grants every scope
+    pub fn require(&self, scope: Scope) -> Result<(), AppError> {
+        match &self.scopes {
+            Some(scopes) if !scopes.contains(&scope) && !scopes.contains(&Scope::Admin) => Err(
+                AppError::Forbidden(format!("API key lacks the {} scope", scope.as_str())),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject users below `role`
+    pub fn require_role(&self, role: Role) -> Result<(), AppError> {
+        if self.role < role {
+            return Err(AppError::Forbidden(format!("Requires the {} role", role.as_str())));
+        }
+        Ok(())
+    }
+
+    /// Whether the user may see every video, including others' private ones
+    pub fn sees_all_videos(&self) -> bool {
+        self.role >= Role::Editor
+    }
+
+    /// Whether the user may modify a video with the given owner
+    pub fn can_manage(&self, owner_id: Option<Uuid>) -> bool {
+        self.sees_all_videos() || owner_id == Some(self.id)
+    }
+
+    async fn authenticate(token: &str, state: &AppState) -> Result<Self, AppError> {
+        let tenant = |id| async move {
+            state
+                .tenants
+                .get(id)
+                .await?
+                .ok_or_else(|| AppError::Unauthorized("Unknown tenant".to_string()))
+        };
+
+        if token.starts_with(API_KEY_PREFIX) {
+            let principal = state.api_keys.authenticate(token).await?;
+            return Ok(Self {
+                id: principal.user_id,
+                tenant: tenant(principal.tenant_id).await?,
+                username: principal.username,
+                role: principal.role,
+                api_key_id: Some(principal.key_id),
+                scopes: Some(principal.scopes),
+            });
+        }
+
+        let claims = state.auth.verify_access(token)?;
+        Ok(Self {
+            id: claims.sub,
+            tenant: tenant(claims.tid).await?,
+            username: claims.name,
+            role: claims.role,
+            api_key_id: None,
+            scopes: None,
+        })
+    }
+}
+
+impl fmt::Display for AuthUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.api_key_id {
+            Some(key_id) => write!(f, "{} (API key {})", self.username, key_id),
+            None => f.write_str(&self.username),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        // Extracted once per request, so API key uses are only counted once
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        let token = bearer_token(parts)
+            .ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
+        let user = Self::authenticate(token, state).await?;
+
+        if let Some(host) = request_host(parts) {
+            if let Some(tenant) = state.tenants.by_host(host).await? {
+                if tenant.id != user.tenant.id {
+                    return Err(AppError::Unauthorized(
+                        "Token was issued for another tenant".to_string(),
+                    ));
+                }
+            }
+        }
+
+        parts.extensions.insert(user.clone());
+        Ok(user)
+    }
+}
+
+/// Tenant a request is made for
+///
+/// The tenant of the authenticated user when a token is supplied, otherwise
+/// the tenant the `Host` header is assigned to, falling back to the default
+/// tenant.
+#[derive(Debug, Clone)]
+pub struct CurrentTenant(pub Arc<Tenant>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for CurrentTenant {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if bearer_token(parts).is_some() {
+            let user = AuthUser::from_request_parts(parts, state).await?;
+            return Ok(Self(user.tenant));
+        }
+
+        let tenant = state
+            .tenants
+            .resolve(request_host(parts))
+            .await?
+            .ok_or_else(|| AppError::Internal("Default tenant not found".to_string()))?;
+
+        Ok(Self(tenant))
+    }
+}
+
+/// Identity of the caller, if authenticated
+///
+/// Used on routes open to anonymous callers, to decide which videos they may
+/// see and to bind playback tokens. Anonymous requests yield `None`; an
+/// invalid token is still rejected.
+#[derive(Debug, Clone)]
+pub struct Actor(pub Option<AuthUser>);
+
+impl Actor {
+    /// User ID as a string, as bound into playback tokens
+    pub fn user_id(&self) -> Option<String> {
+        self.0.as_ref().map(|user| user.id.to_string())
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Actor {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        match bearer_token(parts) {
+            Some(_) => Ok(Self(Some(AuthUser::from_request_parts(parts, state).await?))),
+            None => Ok(Self(None)),
+        }
+    }
+}
+
+/// Host name of a request, without port
+fn request_host(parts: &Parts) -> Option<&str> {
+    let host = parts.headers.get(header::HOST)?.to_str().ok()?;
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    Some(host.trim_start_matches('[').trim_end_matches(']')).filter(|h| !h.is_empty())
+}
+
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Address of the client making a request
+///
+/// The peer address of the connection, unless the peer is one of
+/// `TRUSTED_PROXIES`: then the `X-Real-IP` set by the nginx reverse proxy,
+/// or else the last `X-Forwarded-For` hop not added by a trusted proxy.
+/// Anyone else could forge these headers.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let trusted = |ip: &IpAddr| state.trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        if !peer.as_ref().is_some_and(trusted) {
+            return Ok(Self(peer));
+        }
+
+        let header = |name: &str| parts.headers.get(name).and_then(|value| value.to_str().ok());
+        let real_ip = header("x-real-ip").and_then(|value| value.trim().parse::<IpAddr>().ok());
+        let forwarded_ip = || {
+            header("x-forwarded-for").and_then(|value| {
+                value
+                    .rsplit(',')
+                    .map_while(|hop| hop.trim().parse::<IpAddr>().ok())
+                    .find(|ip| !trusted(ip))
+            })
+        };
+
+        Ok(Self(real_ip.or_else(forwarded_ip).or(peer)))
+    }
+}
@@ -0,0 +1,112 @@
+use axum::{extract::State, http::StatusCode};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    ApiKeyListResponse, CreateApiKeyRequest, CreatedApiKeyResponse, Scope,
+};
+
+/// Highest per-minute rate limit a key can be given
+const MAX_RATE_LIMIT_PER_MINUTE: i32 = 10_000;
+
+/// Longest lifetime of a key with an expiry
+const MAX_EXPIRES_IN_DAYS: i64 = 3650;
+
+/// Create an API key; the key is only returned by this call
+/// POST /api-keys
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>)> {
+    user.require(Scope::Admin)?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(AppError::BadRequest(
+            "Name must be 1-100 characters".to_string(),
+        ));
+    }
+
+    if payload.scopes.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one scope is required".to_string(),
+        ));
+    }
+
+    let rate_limit = payload
+        .rate_limit_per_minute
+        .unwrap_or_else(|| state.api_keys.default_rate_limit());
+    if !(1..=MAX_RATE_LIMIT_PER_MINUTE).contains(&rate_limit) {
+        return Err(AppError::BadRequest(format!(
+            "rate_limit_per_minute must be between 1 and {}",
+            MAX_RATE_LIMIT_PER_MINUTE
+        )));
+    }
+
+    let expires_at = match payload.expires_in_days {
+        Some(days) if !(1..=MAX_EXPIRES_IN_DAYS).contains(&days) => {
+            return Err(AppError::BadRequest(format!(
+                "expires_in_days must be between 1 and {}",
+                MAX_EXPIRES_IN_DAYS
+            )));
+        }
+        Some(days) => Some(Utc::now() + Duration::days(days)),
+        None => None,
+    };
+
+    let mut scopes = payload.scopes;
+    scopes.sort();
+    scopes.dedup();
+
+    let (api_key, key) = state
+        .api_keys
+        .create(user.id, name, &scopes, rate_limit, expires_at)
+        .await?;
+
+    tracing::info!("API key created: {} ({}) by {}", api_key.id, api_key.prefix, user);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKeyResponse {
+            api_key: api_key.to_response(),
+            key,
+        }),
+    ))
+}
+
+/// List the API keys of the authenticated user
+/// GET /api-keys
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<ApiKeyListResponse>> {
+    user.require(Scope::Admin)?;
+
+    let api_keys = state.api_keys.list(user.id).await?;
+
+    Ok(Json(ApiKeyListResponse {
+        api_keys: api_keys.iter().map(|k| k.to_response()).collect(),
+    }))
+}
+
+/// Revoke an API key
+/// DELETE /api-keys/:id
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::Admin)?;
+
+    if !state.api_keys.revoke(user.id, id).await? {
+        return Err(AppError::NotFound(format!("API key {} not found", id)));
+    }
+
+    tracing::info!("API key revoked: {} by {}", id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
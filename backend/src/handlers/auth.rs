@@ -0,0 +1,134 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::error::{AppError, FieldError, Result};
+use crate::handlers::extract::Json;
+use crate::handlers::{AppState, AuthUser, CurrentTenant};
+use crate::models::{
+    LoginRequest, RefreshRequest, RegisterRequest, TokenResponse, User, UserResponse,
+};
+use crate::services::auth::IssuedTokens;
+
+/// Minimum length of a password
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Maximum length of a password, bounding the cost of hashing it
+const MAX_PASSWORD_LEN: usize = 1024;
+
+/// Create an account in the tenant of the request
+/// POST /auth/register
+pub async fn register(
+    State(state): State<AppState>,
+    CurrentTenant(tenant): CurrentTenant,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<(StatusCode, Json<UserResponse>)> {
+    let username = payload.username.trim();
+    let email = payload.email.trim();
+    validate_account(username, email, &payload.password)?;
+
+    let user = state.auth.register(tenant.id, username, email, &payload.password).await?;
+
+    tracing::info!("User registered: {} ({}) in tenant {}", user.username, user.id, tenant.slug);
+
+    Ok((StatusCode::CREATED, Json(user.to_response())))
+}
+
+/// Check the username, email address and password of a new account
+pub fn validate_account(username: &str, email: &str, password: &str) -> Result<()> {
+    let mut errors = Vec::new();
+    if username.len() < 3
+        || username.len() > 64
+        || !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        errors.push(FieldError::new(
+            "/username",
+            "Username must be 3-64 characters of letters, digits, '_', '-' or '.'",
+        ));
+    }
+
+    if email.len() > 255 || !email.contains('@') {
+        errors.push(FieldError::new("/email", "Invalid email address"));
+    }
+
+    if password.len() < MIN_PASSWORD_LEN || password.len() > MAX_PASSWORD_LEN {
+        errors.push(FieldError::new(
+            "/password",
+            format!("Password must be {} to {} characters", MIN_PASSWORD_LEN, MAX_PASSWORD_LEN),
+        ));
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+    Ok(())
+}
+
+/// Log in with a username or email address of the tenant of the request
+/// POST /auth/login
+pub async fn login(
+    State(state): State<AppState>,
+    CurrentTenant(tenant): CurrentTenant,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>> {
+    if payload.password.len() > MAX_PASSWORD_LEN {
+        return Err(AppError::Unauthorized("invalid username or password".to_string()));
+    }
+
+    let (user, tokens) = state
+        .auth
+        .login(tenant.id, payload.username.trim(), &payload.password)
+        .await?;
+
+    tracing::info!("User logged in: {}", user.id);
+
+    Ok(Json(token_response(user, tokens)))
+}
+
+/// Exchange a refresh token for a new access and refresh token
+/// POST /auth/refresh
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>> {
+    let (user, tokens) = state.auth.refresh(&payload.refresh_token).await?;
+
+    Ok(Json(token_response(user, tokens)))
+}
+
+/// Revoke a refresh token
+/// POST /auth/logout
+pub async fn logout(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<StatusCode> {
+    state.auth.logout(&payload.refresh_token).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the authenticated user
+/// GET /auth/me
+pub async fn get_current_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<UserResponse>> {
+    let user = state
+        .auth
+        .get_user(user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", user.id)))?;
+
+    Ok(Json(user.to_response()))
+}
+
+fn token_response(user: User, tokens: IssuedTokens) -> TokenResponse {
+    TokenResponse {
+        access_token: tokens.access_token,
+        token_type: "Bearer",
+        expires_in: tokens.access_ttl_secs,
+        refresh_token: tokens.refresh_token,
+        refresh_expires_at: tokens.refresh_expires_at,
+        user: user.to_response(),
+    }
+}
@@ -0,0 +1,309 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::metadata::normalize_tags;
+use crate::handlers::video::filtered_video_ids;
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    BulkAction, BulkItemStatus, BulkOperation, BulkOperationItem, BulkOperationItemListResponse,
+    BulkOperationListResponse, CreateBulkOperationRequest, Role, Scope,
+};
+use crate::services::bulk::BulkParams;
+
+/// Most videos a bulk operation can act on
+const MAX_BULK_VIDEOS: usize = 10_000;
+
+const OPERATION_COLUMNS: &str = "id, action, params, status, total, succeeded, failed, \
+    error_message, created_by, created_at, updated_at, started_at, completed_at";
+
+/// Query parameters for listing bulk operations
+#[derive(Debug, Deserialize)]
+pub struct ListBulkOperationsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Query parameters for listing the items of a bulk operation
+#[derive(Debug, Deserialize)]
+pub struct ListBulkItemsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Start an action on a list of videos or on the videos of a filter
+/// POST /bulk-operations
+///
+/// Returns at once; the operation runs in the background one video at a
+/// time, through the same code and with the same permission checks as the
+/// single-video endpoints, and records the outcome for each video.
+pub async fn create_bulk_operation(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateBulkOperationRequest>,
+) -> Result<(StatusCode, Json<BulkOperation>)> {
+    user.require(action_scope(payload.action))?;
+
+    let params = match payload.action {
+        BulkAction::SetVisibility => BulkParams {
+            visibility: Some(payload.visibility.ok_or_else(|| {
+                AppError::BadRequest("set_visibility requires a visibility".to_string())
+            })?),
+            ..Default::default()
+        },
+        BulkAction::AddTags | BulkAction::RemoveTags => {
+            let tags = normalize_tags(&payload.tags)?;
+            if tags.is_empty() {
+                return Err(AppError::BadRequest(
+                    "add_tags and remove_tags require tags".to_string(),
+                ));
+            }
+            BulkParams {
+                tags,
+                ..Default::default()
+            }
+        }
+        BulkAction::Delete | BulkAction::Retranscode | BulkAction::Export => BulkParams::default(),
+    };
+
+    let video_ids = match (payload.video_ids, &payload.filter) {
+        (Some(ids), None) => {
+            let mut seen = HashSet::with_capacity(ids.len());
+            ids.into_iter().filter(|id| seen.insert(*id)).collect()
+        }
+        (None, Some(filter)) => {
+            // Exports read videos; every other action modifies them
+            let managed = payload.action != BulkAction::Export;
+            let limit = MAX_BULK_VIDEOS as i64 + 1;
+            filtered_video_ids(&state, &user, filter, managed, limit).await?
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Give either video_ids or filter".to_string(),
+            ))
+        }
+    };
+    if video_ids.is_empty() {
+        return Err(AppError::BadRequest("No videos to act on".to_string()));
+    }
+    if video_ids.len() > MAX_BULK_VIDEOS {
+        return Err(AppError::BadRequest(format!(
+            "A bulk operation can act on at most {} videos",
+            MAX_BULK_VIDEOS
+        )));
+    }
+
+    let params = serde_json::to_value(&params)
+        .map_err(|e| AppError::Internal(format!("Failed to encode parameters: {}", e)))?;
+    let scopes: Option<Vec<String>> = user.scopes.as_ref().map(|scopes| {
+        scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect()
+    });
+
+    let mut tx = state.db.begin().await?;
+
+    let operation = sqlx::query_as::<_, BulkOperation>(&format!(
+        r#"
+        INSERT INTO bulk_operations (tenant_id, created_by, api_key_id, scopes, action, params, total)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {}
+        "#,
+        OPERATION_COLUMNS
+    ))
+    .bind(user.tenant.id)
+    .bind(user.id)
+    .bind(user.api_key_id)
+    .bind(&scopes)
+    .bind(payload.action)
+    .bind(&params)
+    .bind(video_ids.len() as i32)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO bulk_operation_items (operation_id, position, video_id)
+        SELECT $1, ordinality - 1, video_id FROM unnest($2::uuid[]) WITH ORDINALITY AS t(video_id, ordinality)
+        "#,
+    )
+    .bind(operation.id)
+    .bind(&video_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        "Bulk operation created: {} ({:?} on {} videos) by {}",
+        operation.id,
+        operation.action,
+        operation.total,
+        user
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(operation)))
+}
+
+/// List bulk operations, newest first: the caller's, or every one of the
+/// tenant for admins
+/// GET /bulk-operations
+pub async fn list_bulk_operations(
+    State(state): State<AppState>,
+    Query(params): Query<ListBulkOperationsQuery>,
+    user: AuthUser,
+) -> Result<Json<BulkOperationListResponse>> {
+    user.require(Scope::VideosRead)?;
+
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let sees_all = user.role >= Role::Admin;
+
+    let operations = sqlx::query_as::<_, BulkOperation>(&format!(
+        r#"
+        SELECT {} FROM bulk_operations
+        WHERE tenant_id = $1 AND ($2 OR created_by = $3)
+        ORDER BY created_at DESC, id
+        LIMIT $4 OFFSET $5
+        "#,
+        OPERATION_COLUMNS
+    ))
+    .bind(user.tenant.id)
+    .bind(sees_all)
+    .bind(user.id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM bulk_operations WHERE tenant_id = $1 AND ($2 OR created_by = $3)",
+    )
+    .bind(user.tenant.id)
+    .bind(sees_all)
+    .bind(user.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(BulkOperationListResponse { operations, total }))
+}
+
+/// Get the state and progress of a bulk operation
+/// GET /bulk-operations/:id
+pub async fn get_bulk_operation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<BulkOperation>> {
+    user.require(Scope::VideosRead)?;
+
+    Ok(Json(visible_operation(&state, &user, id).await?))
+}
+
+/// List the videos of a bulk operation in order, with their outcome
+/// GET /bulk-operations/:id/items
+pub async fn list_bulk_operation_items(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListBulkItemsQuery>,
+    user: AuthUser,
+) -> Result<Json<BulkOperationItemListResponse>> {
+    user.require(Scope::VideosRead)?;
+    visible_operation(&state, &user, id).await?;
+
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<BulkItemStatus>)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    let items = sqlx::query_as::<_, BulkOperationItem>(
+        r#"
+        SELECT position, video_id, status, error, result, processed_at
+        FROM bulk_operation_items
+        WHERE operation_id = $1 AND ($2::bulk_item_status IS NULL OR status = $2)
+        ORDER BY position
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM bulk_operation_items
+        WHERE operation_id = $1 AND ($2::bulk_item_status IS NULL OR status = $2)
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(BulkOperationItemListResponse { items, total }))
+}
+
+/// Stop a bulk operation; videos already processed keep the change
+/// POST /bulk-operations/:id/cancel
+pub async fn cancel_bulk_operation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<BulkOperation>> {
+    user.require(Scope::VideosWrite)?;
+    visible_operation(&state, &user, id).await?;
+
+    let operation = sqlx::query_as::<_, BulkOperation>(&format!(
+        r#"
+        UPDATE bulk_operations
+        SET status = 'cancelled', lease_until = NULL, completed_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND status IN ('pending', 'running')
+        RETURNING {}
+        "#,
+        OPERATION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("Bulk operation {} has already finished", id)))?;
+
+    tracing::info!("Bulk operation cancelled: {} by {}", id, user);
+
+    Ok(Json(operation))
+}
+
+/// Load a bulk operation the caller created, or any of the tenant's for admins
+async fn visible_operation(state: &AppState, user: &AuthUser, id: Uuid) -> Result<BulkOperation> {
+    sqlx::query_as::<_, BulkOperation>(&format!(
+        "SELECT {} FROM bulk_operations WHERE id = $1 AND tenant_id = $2 AND ($3 OR created_by = $4)",
+        OPERATION_COLUMNS
+    ))
+    .bind(id)
+    .bind(user.tenant.id)
+    .bind(user.role >= Role::Admin)
+    .bind(user.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Bulk operation {} not found", id)))
+}
+
+/// Scope an API key needs for an action
+fn action_scope(action: BulkAction) -> Scope {
+    match action {
+        BulkAction::Delete => Scope::VideosDelete,
+        BulkAction::Export => Scope::VideosRead,
+        _ => Scope::VideosWrite,
+    }
+}
@@ -0,0 +1,584 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+use sqlx::PgConnection;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::{listing_params, viewable_video, VIDEO_COLUMNS, VIEWABLE_BY_CALLER};
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::{Actor, AppState, AuthUser, CurrentTenant};
+use crate::models::{
+    AddCollectionVideoRequest, Collection, CollectionKind, CollectionListResponse,
+    CollectionResponse, CreateCollectionRequest, ReorderCollectionRequest, Role, Scope, Tenant,
+    UpdateCollectionRequest, Video, VideoListResponse, Visibility,
+};
+
+/// Most videos a collection can hold
+const MAX_COLLECTION_VIDEOS: i64 = 1000;
+
+/// Columns of a [`Collection`] row: those of `collections`, the video count
+/// and the cover thumbnail.
+///
+/// Without a cover video, the thumbnail of the first video that is not
+/// private stands in, so private videos do not leak through the cover.
+const COLLECTION_COLUMNS: &str = r#"
+    c.*,
+    (SELECT COUNT(*) FROM collection_videos cv WHERE cv.collection_id = c.id) AS video_count,
+    COALESCE(
+        (SELECT v.thumbnail_path FROM videos v WHERE v.id = c.cover_video_id AND v.deleted_at IS NULL),
+        (SELECT v.thumbnail_path FROM collection_videos cv JOIN videos v ON v.id = cv.video_id
+         WHERE cv.collection_id = c.id AND v.visibility <> 'private' AND v.thumbnail_path IS NOT NULL
+         ORDER BY cv.position LIMIT 1)
+    ) AS cover_path
+"#;
+
+/// Query parameters for listing collections
+#[derive(Debug, Deserialize)]
+pub struct ListCollectionsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub owner_id: Option<Uuid>,
+    pub kind: Option<CollectionKind>,
+}
+
+/// Query parameters for listing the videos of a collection
+#[derive(Debug, Deserialize)]
+pub struct ListCollectionVideosQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Create a playlist or series
+/// POST /collections
+pub async fn create_collection(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateCollectionRequest>,
+) -> Result<(StatusCode, Json<CollectionResponse>)> {
+    user.require(Scope::VideosWrite)?;
+    user.require_role(Role::Uploader)?;
+
+    let title = check_title(&payload.title)?;
+    if let Some(cover_video_id) = payload.cover_video_id {
+        viewable_video(&state, &user.tenant, cover_video_id, Some(&user)).await?;
+    }
+
+    let collection = sqlx::query_as::<_, Collection>(&format!(
+        r#"
+        WITH c AS (
+            INSERT INTO collections (tenant_id, owner_id, kind, title, description, visibility, cover_video_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+        )
+        SELECT {} FROM c
+        "#,
+        COLLECTION_COLUMNS
+    ))
+    .bind(user.tenant.id)
+    .bind(user.id)
+    .bind(payload.kind)
+    .bind(title)
+    .bind(&payload.description)
+    .bind(payload.visibility)
+    .bind(payload.cover_video_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!("Collection created: {} ({}) by {}", collection.id, title, user);
+
+    Ok((StatusCode::CREATED, Json(collection.to_response(&state.base_url))))
+}
+
+/// List the collections visible to the caller, most recently updated first
+/// GET /collections
+pub async fn list_collections(
+    State(state): State<AppState>,
+    Query(params): Query<ListCollectionsQuery>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<Json<CollectionListResponse>> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let (user_id, sees_all) = listing_params(user.as_ref())?;
+
+    // Like videos, unlisted collections are only listed to their owner
+    let filter = r#"
+        c.tenant_id = $3 AND ($2 OR c.visibility = 'public' OR c.owner_id = $1)
+          AND ($4::uuid IS NULL OR c.owner_id = $4)
+          AND ($5::collection_kind IS NULL OR c.kind = $5)
+    "#;
+
+    let collections = sqlx::query_as::<_, Collection>(&format!(
+        r#"
+        SELECT {} FROM collections c
+        WHERE {}
+        ORDER BY c.updated_at DESC, c.id
+        LIMIT $6 OFFSET $7
+        "#,
+        COLLECTION_COLUMNS, filter
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(params.owner_id)
+    .bind(params.kind)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM collections c WHERE {}",
+        filter
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(params.owner_id)
+    .bind(params.kind)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(CollectionListResponse {
+        collections: collections
+            .iter()
+            .map(|c| c.to_response(&state.base_url))
+            .collect(),
+        total,
+    }))
+}
+
+/// Get a single collection by ID
+/// GET /collections/:id
+pub async fn get_collection(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<Json<CollectionResponse>> {
+    let collection = viewable_collection(&state, &tenant, id, user.as_ref()).await?;
+
+    Ok(Json(collection.to_response(&state.base_url)))
+}
+
+/// Update a collection
+/// PUT /collections/:id
+pub async fn update_collection(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<UpdateCollectionRequest>,
+) -> Result<Json<CollectionResponse>> {
+    user.require(Scope::VideosWrite)?;
+
+    let existing = manageable_collection(&state, id, &user).await?;
+
+    let new_title = match &payload.title {
+        Some(title) => check_title(title)?.to_string(),
+        None => existing.title,
+    };
+    let new_description = payload.description.or(existing.description);
+    let new_kind = payload.kind.unwrap_or(existing.kind);
+    let new_visibility = payload.visibility.unwrap_or(existing.visibility);
+    let new_cover = match payload.cover_video_id {
+        Some(Some(cover_video_id)) => {
+            viewable_video(&state, &user.tenant, cover_video_id, Some(&user)).await?;
+            Some(cover_video_id)
+        }
+        Some(None) => None,
+        None => existing.cover_video_id,
+    };
+
+    let collection = sqlx::query_as::<_, Collection>(&format!(
+        r#"
+        WITH c AS (
+            UPDATE collections
+            SET title = $2, description = $3, kind = $4, visibility = $5, cover_video_id = $6
+            WHERE id = $1
+            RETURNING *
+        )
+        SELECT {} FROM c
+        "#,
+        COLLECTION_COLUMNS
+    ))
+    .bind(id)
+    .bind(&new_title)
+    .bind(&new_description)
+    .bind(new_kind)
+    .bind(new_visibility)
+    .bind(new_cover)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!("Collection updated: {} by {}", id, user);
+
+    Ok(Json(collection.to_response(&state.base_url)))
+}
+
+/// Delete a collection; its videos are left alone
+/// DELETE /collections/:id
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::VideosWrite)?;
+    manageable_collection(&state, id, &user).await?;
+
+    sqlx::query("DELETE FROM collections WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!("Collection deleted: {} by {}", id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the videos of a collection in order, leaving out those the caller may not watch
+/// GET /collections/:id/videos
+pub async fn list_collection_videos(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListCollectionVideosQuery>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<Json<VideoListResponse>> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    viewable_collection(&state, &tenant, id, user.as_ref()).await?;
+    let (user_id, sees_all) = listing_params(user.as_ref())?;
+
+    let videos = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        SELECT {} FROM collection_videos cv
+        JOIN videos ON videos.id = cv.video_id
+        WHERE cv.collection_id = $4 AND videos.deleted_at IS NULL AND {}
+        ORDER BY cv.position
+        LIMIT $5 OFFSET $6
+        "#,
+        VIDEO_COLUMNS, VIEWABLE_BY_CALLER
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        r#"
+        SELECT COUNT(*) FROM collection_videos cv
+        JOIN videos ON videos.id = cv.video_id
+        WHERE cv.collection_id = $4 AND videos.deleted_at IS NULL AND {}
+        "#,
+        VIEWABLE_BY_CALLER
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(id)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(VideoListResponse {
+        videos: videos
+            .iter()
+            .map(|v| v.to_response(&state.base_url))
+            .collect(),
+        total,
+        next_cursor: None,
+    }))
+}
+
+/// Add a video to a collection, at the end or at a position
+/// POST /collections/:id/videos
+pub async fn add_collection_video(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<AddCollectionVideoRequest>,
+) -> Result<(StatusCode, Json<CollectionResponse>)> {
+    user.require(Scope::VideosWrite)?;
+    manageable_collection(&state, id, &user).await?;
+    viewable_video(&state, &user.tenant, payload.video_id, Some(&user)).await?;
+
+    let mut tx = state.db.begin().await?;
+    let count = lock_collection(&mut tx, id).await?;
+
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM collection_videos WHERE collection_id = $1 AND video_id = $2)",
+    )
+    .bind(id)
+    .bind(payload.video_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if exists {
+        return Err(AppError::Conflict(format!(
+            "Video {} is already in collection {}",
+            payload.video_id, id
+        )));
+    }
+    if count >= MAX_COLLECTION_VIDEOS {
+        return Err(AppError::BadRequest(format!(
+            "A collection can hold at most {} videos",
+            MAX_COLLECTION_VIDEOS
+        )));
+    }
+
+    let position = payload.position.map_or(count, |p| i64::from(p).clamp(0, count));
+
+    sqlx::query(
+        "UPDATE collection_videos SET position = position + 1 WHERE collection_id = $1 AND position >= $2",
+    )
+    .bind(id)
+    .bind(position as i32)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("INSERT INTO collection_videos (collection_id, video_id, position) VALUES ($1, $2, $3)")
+        .bind(id)
+        .bind(payload.video_id)
+        .bind(position as i32)
+        .execute(&mut *tx)
+        .await?;
+
+    let collection = touch_collection(&mut tx, id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Video {} added to collection {} at {} by {}",
+        payload.video_id, id, position, user
+    );
+
+    Ok((StatusCode::CREATED, Json(collection.to_response(&state.base_url))))
+}
+
+/// Remove a video from a collection
+/// DELETE /collections/:id/videos/:video_id
+pub async fn remove_collection_video(
+    State(state): State<AppState>,
+    Path((id, video_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::VideosWrite)?;
+    manageable_collection(&state, id, &user).await?;
+
+    let mut tx = state.db.begin().await?;
+    lock_collection(&mut tx, id).await?;
+
+    if !unlink_video(&mut tx, video_id, Some(id)).await? {
+        return Err(AppError::NotFound(format!(
+            "Video {} is not in collection {}",
+            video_id, id
+        )));
+    }
+
+    touch_collection(&mut tx, id).await?;
+    tx.commit().await?;
+
+    tracing::info!("Video {} removed from collection {} by {}", video_id, id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reorder the videos of a collection
+/// PUT /collections/:id/videos
+pub async fn reorder_collection_videos(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<ReorderCollectionRequest>,
+) -> Result<Json<CollectionResponse>> {
+    user.require(Scope::VideosWrite)?;
+    manageable_collection(&state, id, &user).await?;
+
+    let mut tx = state.db.begin().await?;
+    lock_collection(&mut tx, id).await?;
+
+    let current: HashSet<Uuid> = sqlx::query_scalar::<_, Uuid>(
+        "SELECT video_id FROM collection_videos WHERE collection_id = $1",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    let requested: HashSet<Uuid> = payload.video_ids.iter().copied().collect();
+    if requested.len() != payload.video_ids.len() || requested != current {
+        return Err(AppError::BadRequest(
+            "video_ids must list every video of the collection exactly once".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE collection_videos cv
+        SET position = o.ordinality - 1
+        FROM unnest($2::uuid[]) WITH ORDINALITY AS o(video_id, ordinality)
+        WHERE cv.collection_id = $1 AND cv.video_id = o.video_id
+        "#,
+    )
+    .bind(id)
+    .bind(&payload.video_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    let collection = touch_collection(&mut tx, id).await?;
+    tx.commit().await?;
+
+    tracing::info!("Collection reordered: {} by {}", id, user);
+
+    Ok(Json(collection.to_response(&state.base_url)))
+}
+
+/// Remove a video from every collection, as when it is deleted, and stop
+/// using it as a cover
+pub(crate) async fn remove_from_collections(conn: &mut PgConnection, video_id: Uuid) -> Result<()> {
+    // Lock in a stable order so concurrent edits of the same collections cannot deadlock
+    sqlx::query(
+        r#"
+        SELECT id FROM collections
+        WHERE id IN (SELECT collection_id FROM collection_videos WHERE video_id = $1)
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind(video_id)
+    .execute(&mut *conn)
+    .await?;
+
+    unlink_video(conn, video_id, None).await?;
+
+    sqlx::query("UPDATE collections SET cover_video_id = NULL WHERE cover_video_id = $1")
+        .bind(video_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Unlink a video from one collection or from all of them, closing the gap
+/// it leaves in their order. Returns whether the video was in any.
+async fn unlink_video(
+    conn: &mut PgConnection,
+    video_id: Uuid,
+    collection_id: Option<Uuid>,
+) -> Result<bool> {
+    let removed = sqlx::query_as::<_, (Uuid, i32)>(
+        r#"
+        DELETE FROM collection_videos
+        WHERE video_id = $1 AND ($2::uuid IS NULL OR collection_id = $2)
+        RETURNING collection_id, position
+        "#,
+    )
+    .bind(video_id)
+    .bind(collection_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (collection_id, position) in &removed {
+        sqlx::query(
+            "UPDATE collection_videos SET position = position - 1 WHERE collection_id = $1 AND position > $2",
+        )
+        .bind(collection_id)
+        .bind(position)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(!removed.is_empty())
+}
+
+/// Lock a collection against concurrent changes to its videos, returning
+/// how many it holds
+async fn lock_collection(conn: &mut PgConnection, id: Uuid) -> Result<i64> {
+    sqlx::query("SELECT id FROM collections WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Collection {} not found", id)))?;
+
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM collection_videos WHERE collection_id = $1",
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(count)
+}
+
+/// Bump the update time of a collection whose videos changed
+async fn touch_collection(conn: &mut PgConnection, id: Uuid) -> Result<Collection> {
+    let collection = sqlx::query_as::<_, Collection>(&format!(
+        r#"
+        WITH c AS (
+            UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = $1 RETURNING *
+        )
+        SELECT {} FROM c
+        "#,
+        COLLECTION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(collection)
+}
+
+fn check_title(title: &str) -> Result<&str> {
+    let title = title.trim();
+    if title.is_empty() || title.chars().count() > 255 {
+        return Err(AppError::BadRequest(
+            "Title must be 1 to 255 characters".to_string(),
+        ));
+    }
+    Ok(title)
+}
+
+/// Load a collection of the tenant the caller may see.
+///
+/// Public and unlisted collections are open to anyone; private ones to
+/// their owner and editors. Others are reported as missing.
+async fn viewable_collection(
+    state: &AppState,
+    tenant: &Tenant,
+    id: Uuid,
+    user: Option<&AuthUser>,
+) -> Result<Collection> {
+    if let Some(user) = user {
+        user.require(Scope::VideosRead)?;
+    }
+
+    let collection = sqlx::query_as::<_, Collection>(&format!(
+        "SELECT {} FROM collections c WHERE c.id = $1 AND c.tenant_id = $2",
+        COLLECTION_COLUMNS
+    ))
+    .bind(id)
+    .bind(tenant.id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    match collection {
+        Some(c) if c.visibility != Visibility::Private => Ok(c),
+        Some(c) if user.is_some_and(|u| u.can_manage(c.owner_id)) => Ok(c),
+        _ => Err(AppError::NotFound(format!("Collection {} not found", id))),
+    }
+}
+
+/// Load a collection the caller may modify: their own, or any for editors
+async fn manageable_collection(state: &AppState, id: Uuid, user: &AuthUser) -> Result<Collection> {
+    let collection = viewable_collection(state, &user.tenant, id, Some(user)).await?;
+
+    if !user.can_manage(collection.owner_id) {
+        return Err(AppError::Forbidden(format!("Not allowed to modify collection {}", id)));
+    }
+
+    Ok(collection)
+}
@@ -0,0 +1,221 @@
+use axum::{extract::State, http::StatusCode};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    AddGroupMemberRequest, CreateGroupRequest, Group, GroupListResponse, GroupMember,
+    GroupMemberListResponse, Role, Scope,
+};
+
+/// Columns of a group with its member count
+const GROUP_COLUMNS: &str = r#"
+    g.id, g.name, g.owner_id, g.created_at,
+    (SELECT COUNT(*) FROM group_members m WHERE m.group_id = g.id) AS member_count
+"#;
+
+/// Create a group of users to share videos with
+/// POST /groups
+pub async fn create_group(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateGroupRequest>,
+) -> Result<(StatusCode, Json<Group>)> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Uploader)?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(AppError::BadRequest(
+            "Name must be 1-100 characters".to_string(),
+        ));
+    }
+
+    let result = sqlx::query_as::<_, Group>(&format!(
+        r#"
+        WITH g AS (
+            INSERT INTO user_groups (tenant_id, name, owner_id) VALUES ($3, $1, $2)
+            RETURNING *
+        )
+        SELECT {} FROM g
+        "#,
+        GROUP_COLUMNS
+    ))
+    .bind(name)
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .fetch_one(&state.db)
+    .await;
+
+    let group = match result {
+        Ok(group) => group,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return Err(AppError::Conflict(format!("Group {} already exists", name)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    tracing::info!("Group created: {} ({}) by {}", group.id, group.name, user);
+
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// List the groups the caller owns or belongs to; admins see every group of the tenant
+/// GET /groups
+pub async fn list_groups(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<GroupListResponse>> {
+    user.require(Scope::Admin)?;
+
+    let groups = sqlx::query_as::<_, Group>(&format!(
+        r#"
+        SELECT {} FROM user_groups g
+        WHERE g.tenant_id = $3
+          AND ($1 OR g.owner_id = $2
+           OR EXISTS (SELECT 1 FROM group_members m WHERE m.group_id = g.id AND m.user_id = $2))
+        ORDER BY g.name
+        "#,
+        GROUP_COLUMNS
+    ))
+    .bind(user.role >= Role::Admin)
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(GroupListResponse { groups }))
+}
+
+/// Delete a group, removing the shares made with it
+/// DELETE /groups/:id
+pub async fn delete_group(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::Admin)?;
+    managed_group(&state, id, &user).await?;
+
+    sqlx::query("DELETE FROM user_groups WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!("Group deleted: {} by {}", id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the members of a group
+/// GET /groups/:id/members
+pub async fn list_group_members(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<GroupMemberListResponse>> {
+    user.require(Scope::Admin)?;
+
+    let members = sqlx::query_as::<_, GroupMember>(
+        r#"
+        SELECT u.id AS user_id, u.username, m.created_at AS added_at
+        FROM group_members m
+        JOIN users u ON u.id = m.user_id
+        JOIN user_groups g ON g.id = m.group_id
+        WHERE m.group_id = $1 AND g.tenant_id = $2
+        ORDER BY u.username
+        "#,
+    )
+    .bind(id)
+    .bind(user.tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let visible = members.iter().any(|m| m.user_id == user.id)
+        || managed_group(&state, id, &user).await.is_ok();
+    if !visible {
+        return Err(AppError::NotFound(format!("Group {} not found", id)));
+    }
+
+    Ok(Json(GroupMemberListResponse { members }))
+}
+
+/// Add a user to a group
+/// POST /groups/:id/members
+pub async fn add_group_member(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<AddGroupMemberRequest>,
+) -> Result<(StatusCode, Json<GroupMember>)> {
+    user.require(Scope::Admin)?;
+    managed_group(&state, id, &user).await?;
+
+    let member = sqlx::query_as::<_, GroupMember>(
+        r#"
+        WITH added AS (
+            INSERT INTO group_members (group_id, user_id)
+            SELECT $1, id FROM users WHERE tenant_id = $3 AND LOWER(username) = LOWER($2)
+            ON CONFLICT (group_id, user_id) DO UPDATE SET group_id = EXCLUDED.group_id
+            RETURNING user_id, created_at
+        )
+        SELECT u.id AS user_id, u.username, a.created_at AS added_at
+        FROM added a
+        JOIN users u ON u.id = a.user_id
+        "#,
+    )
+    .bind(id)
+    .bind(payload.username.trim())
+    .bind(user.tenant.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("User {} not found", payload.username)))?;
+
+    tracing::info!("User {} added to group {} by {}", member.user_id, id, user);
+
+    Ok((StatusCode::CREATED, Json(member)))
+}
+
+/// Remove a user from a group
+/// DELETE /groups/:id/members/:user_id
+pub async fn remove_group_member(
+    State(state): State<AppState>,
+    Path((id, member_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::Admin)?;
+    managed_group(&state, id, &user).await?;
+
+    let result = sqlx::query("DELETE FROM group_members WHERE group_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(member_id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("User {} is not in group {}", member_id, id)));
+    }
+
+    tracing::info!("User {} removed from group {} by {}", member_id, id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check that a group of the caller's tenant exists and the caller owns it or is an admin
+async fn managed_group(state: &AppState, id: Uuid, user: &AuthUser) -> Result<()> {
+    let owner_id = sqlx::query_scalar::<_, Uuid>(
+        "SELECT owner_id FROM user_groups WHERE id = $1 AND tenant_id = $2",
+    )
+    .bind(id)
+    .bind(user.tenant.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Group {} not found", id)))?;
+
+    if owner_id != user.id && user.role < Role::Admin {
+        return Err(AppError::Forbidden(format!("Not allowed to manage group {}", id)));
+    }
+
+    Ok(())
+}
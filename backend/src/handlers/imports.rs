@@ -0,0 +1,170 @@
+use axum::{extract::State, http::StatusCode};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::{manageable_video, VIDEO_COLUMNS};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::history::audit_actor;
+use crate::handlers::metadata::{check_category, normalize_tags, set_video_tags, validate_metadata};
+use crate::handlers::search::text_search_config;
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    ImportOrigin, ImportResponse, ImportVideoRequest, Role, Scope, Tenant, Video, VideoImport,
+};
+use crate::services::ImportSource;
+
+/// Import a video from an http(s) URL, another bucket or a server-side path
+/// POST /videos/import
+///
+/// The file is copied in the background and then transcoded like an
+/// upload; `GET /videos/:id/import` reports the progress.
+pub async fn import_video(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<ImportVideoRequest>,
+) -> Result<(StatusCode, Json<ImportResponse>)> {
+    user.require(Scope::VideosWrite)?;
+    user.require_role(Role::Uploader)?;
+    // Buckets and paths are allowlisted for every tenant, so only admins may
+    // import from them, or even learn which files exist
+    let source = payload.source.trim();
+    if source.starts_with("s3://") || source.starts_with('/') {
+        user.require_role(Role::Admin)?;
+    }
+
+    let source = state
+        .imports
+        .check_source(&payload.source)
+        .await
+        .map_err(AppError::BadRequest)?;
+
+    // The size is checked again once the file is copied
+    let size = payload.size.unwrap_or(0).max(0);
+
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, &user).await?;
+    state.quotas.lock_upload(&mut tx, user.tenant.id, user.id, size).await?;
+    let (video, import) =
+        create_import(&state, &mut tx, &user.tenant, Some(user.id), &source, ImportOrigin::Request, payload)
+            .await?;
+    tx.commit().await?;
+
+    tracing::info!("Video import queued: {} from {} by {}", video.id, source, user);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ImportResponse {
+            video: video.to_response(&state.base_url),
+            import,
+        }),
+    ))
+}
+
+/// Get the state and progress of a video's import
+/// GET /videos/:id/import
+pub async fn get_video_import(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<VideoImport>> {
+    user.require(Scope::VideosRead)?;
+    manageable_video(&state, id, &user).await?;
+
+    let import = sqlx::query_as::<_, VideoImport>("SELECT * FROM video_imports WHERE video_id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Video {} was not imported", id)))?;
+
+    Ok(Json(import))
+}
+
+/// Create a pending video and the import that fills it in from `source`,
+/// within the caller's transaction
+pub(crate) async fn create_import(
+    state: &AppState,
+    conn: &mut PgConnection,
+    tenant: &Tenant,
+    owner_id: Option<Uuid>,
+    source: &ImportSource,
+    origin: ImportOrigin,
+    request: ImportVideoRequest,
+) -> Result<(Video, VideoImport)> {
+    if request.size.is_some_and(|size| size <= 0) {
+        return Err(AppError::invalid("/size", "size must be positive"));
+    }
+    let sha256 = request.sha256.map(|sha256| sha256.trim().to_lowercase());
+    if let Some(sha256) = &sha256 {
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::invalid("/sha256", "sha256 must be 64 hexadecimal characters"));
+        }
+    }
+
+    let metadata = request.metadata;
+    let filename = source.file_name().unwrap_or_else(|| "original".to_string());
+    let title = metadata
+        .title
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| filename.clone());
+    let language = match &metadata.language {
+        Some(language) => text_search_config(state, language).await?,
+        None => state.search_language.clone(),
+    };
+    let tags = normalize_tags(&metadata.tags)?;
+    if let Some(category_id) = metadata.category_id {
+        check_category(state, tenant, category_id).await?;
+    }
+    let custom_metadata = serde_json::Value::Object(metadata.custom_metadata.unwrap_or_default());
+    validate_metadata(tenant, &custom_metadata)?;
+
+    let video_id = Uuid::new_v4();
+    let original_path = format!("{}/{}", video_id, filename);
+    let mime_type = mime_guess::from_path(&filename).first().map(|mime| mime.to_string());
+
+    let mut video = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        INSERT INTO videos (id, tenant_id, title, description, original_filename, original_size, mime_type, original_path, visibility, owner_id, language, category_id, custom_metadata, status)
+        VALUES ($1, $10, $2, $3, $4, $5, $6, $7, $8, $9, $11, $12, $13, 'pending')
+        RETURNING {}
+        "#,
+        VIDEO_COLUMNS
+    ))
+    .bind(video_id)
+    .bind(&title)
+    .bind(&metadata.description)
+    .bind(&filename)
+    .bind(request.size.unwrap_or(0))
+    .bind(&mime_type)
+    .bind(&original_path)
+    .bind(metadata.visibility)
+    .bind(owner_id)
+    .bind(tenant.id)
+    .bind(&language)
+    .bind(metadata.category_id)
+    .bind(&custom_metadata)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if !tags.is_empty() {
+        video.tags = set_video_tags(conn, tenant.id, video_id, &tags).await?;
+    }
+
+    let import = sqlx::query_as::<_, VideoImport>(
+        r#"
+        INSERT INTO video_imports (video_id, source, origin, expected_size, expected_sha256)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(video_id)
+    .bind(source.to_string())
+    .bind(origin)
+    .bind(request.size)
+    .bind(&sha256)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok((video, import))
+}
@@ -0,0 +1,206 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+};
+use percent_encoding::percent_decode_str;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::Json;
+use crate::handlers::imports::create_import;
+use crate::handlers::AppState;
+use crate::models::{
+    BucketNotification, ImportMetadata, ImportOrigin, ImportVideoRequest, Video, WebhookEvent,
+};
+use crate::services::ingest::IngestFile;
+use crate::services::webhooks;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// File dropped for ingest
+enum Dropped<'a> {
+    /// File of `INGEST_WATCH_DIR`
+    File(&'a Path),
+    /// Object of `INGEST_BUCKET`, by key
+    Object(&'a str),
+}
+
+/// Ingest the objects announced by a MinIO bucket notification
+/// POST /ingest/notifications
+///
+/// Authenticated with `INGEST_NOTIFY_TOKEN` instead of a user token.
+/// Objects outside `INGEST_BUCKET` and `INGEST_PREFIX`, in the archive or
+/// without a video type are ignored, so the whole bucket can be subscribed.
+pub async fn ingest_notification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<BucketNotification>,
+) -> Result<StatusCode> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !state.ingest.check_token(authorization) {
+        return Err(AppError::Unauthorized("Invalid ingest token".to_string()));
+    }
+
+    for record in payload.records {
+        if !record.event_name.starts_with("s3:ObjectCreated:") {
+            continue;
+        }
+        // Keys are query-escaped, with spaces as `+`
+        let key = record.s3.object.key.replace('+', " ");
+        let key = percent_decode_str(&key).decode_utf8_lossy();
+        if !state.ingest.accepts_object(&record.s3.bucket.name, &key) {
+            continue;
+        }
+
+        match ingest(&state, Dropped::Object(&key)).await {
+            Ok(Some(video_id)) => tracing::info!("Ingested {} as video {}", key, video_id),
+            Ok(None) => {}
+            Err(e) => {
+                return Err(AppError::Internal(format!("Failed to ingest {}: {}", key, e)));
+            }
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ingest the files dropped into `INGEST_WATCH_DIR`, scanning it until the
+/// process exits
+pub async fn watch_ingest_dir(state: AppState) {
+    tracing::info!("Watching ingest directory...");
+
+    loop {
+        match state.ingest.settled_files().await {
+            Ok(files) => {
+                for path in files {
+                    match ingest(&state, Dropped::File(&path)).await {
+                        Ok(Some(video_id)) => {
+                            tracing::info!("Ingested {} as video {}", path.display(), video_id)
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::error!("Failed to ingest {}: {}", path.display(), e),
+                    }
+                }
+            }
+            Err(e) => tracing::error!("Error scanning ingest directory: {}", e),
+        }
+
+        tokio::time::sleep(state.ingest.scan_interval()).await;
+    }
+}
+
+/// Create the video and import of a dropped file and move the file to the
+/// archive, returning the video; `None` if the file was already ingested.
+///
+/// A file whose sidecar is unreadable or invalid still becomes a video,
+/// failed with the reason, so that it leaves the drop location.
+async fn ingest(state: &AppState, dropped: Dropped<'_>) -> std::result::Result<Option<Uuid>, BoxError> {
+    let (tenant, owner_id) = state.ingest.target().await?;
+    let location = match dropped {
+        Dropped::File(path) => path.to_string_lossy().into_owned(),
+        Dropped::Object(key) => key.to_string(),
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    // Scans and repeated notifications of the same file wait for each
+    // other here; the later one finds the file archived
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(format!("ingest:{}", location))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("SELECT set_config('videostream.actor', 'ingest', true)")
+        .execute(&mut *tx)
+        .await?;
+
+    let mut file = match dropped {
+        Dropped::File(path) => state.ingest.local_file(path).await?,
+        Dropped::Object(key) => state.ingest.bucket_file(key).await?,
+    };
+    let Some(file) = file.as_mut() else {
+        return Ok(None);
+    };
+
+    let request = |file: &IngestFile, metadata| ImportVideoRequest {
+        source: file.source.to_string(),
+        size: (file.size > 0).then_some(file.size),
+        sha256: None,
+        metadata,
+    };
+    let metadata = std::mem::replace(&mut file.metadata, Ok(ImportMetadata::default()));
+    let created = match metadata {
+        Ok(metadata) => {
+            create_import(
+                state,
+                &mut tx,
+                &tenant,
+                owner_id,
+                &file.source,
+                ImportOrigin::Ingest,
+                request(file, metadata),
+            )
+            .await
+        }
+        Err(e) => Err(AppError::BadRequest(e)),
+    };
+    let video = match created {
+        Ok((video, _)) => video,
+        Err(e @ (AppError::BadRequest(_) | AppError::NotFound(_) | AppError::Validation(_))) => {
+            let error = e.detail();
+            let (video, _) = create_import(
+                state,
+                &mut tx,
+                &tenant,
+                owner_id,
+                &file.source,
+                ImportOrigin::Ingest,
+                request(file, ImportMetadata::default()),
+            )
+            .await?;
+            fail(&mut tx, &video, &error).await?;
+            tracing::warn!("Ingest of {} failed: {}", location, error);
+            video
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let archived: std::result::Result<(), BoxError> = match dropped {
+        Dropped::File(_) => state.ingest.archive_local(file).await.map_err(Into::into),
+        Dropped::Object(_) => state.ingest.archive_object(file).await.map_err(Into::into),
+    };
+    let recorded = match archived {
+        Ok(()) => tx.commit().await.map_err(Into::into),
+        Err(e) => Err(e),
+    };
+    // Without its video the file goes back to where it was dropped, to be
+    // picked up again
+    if let Err(e) = recorded {
+        match dropped {
+            Dropped::File(_) => state.ingest.restore_local(file).await,
+            Dropped::Object(_) => state.ingest.restore_object(file).await,
+        }
+        return Err(e);
+    }
+
+    Ok(Some(video.id))
+}
+
+/// Fail a video whose metadata could not be ingested, before its import runs
+async fn fail(conn: &mut sqlx::PgConnection, video: &Video, error: &str) -> Result<()> {
+    sqlx::query("UPDATE video_imports SET status = 'failed', error_message = $2 WHERE video_id = $1")
+        .bind(video.id)
+        .bind(error)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE videos SET status = 'failed', error_message = $2 WHERE id = $1")
+        .bind(video.id)
+        .bind(format!("Ingest failed: {}", error))
+        .execute(&mut *conn)
+        .await?;
+    webhooks::emit(conn, video.id, WebhookEvent::Failed).await?;
+
+    Ok(())
+}
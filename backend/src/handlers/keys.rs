@@ -0,0 +1,149 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Path, Query};
+use crate::handlers::{Actor, AppState, AssetQuery, ClientIp};
+use crate::models::{ClearKeyLicenseRequest, ClearKeyLicenseResponse, JsonWebKey};
+
+/// Deliver an HLS content key to a player
+/// GET /keys/:video_id/:key_index
+///
+/// Keys are only released against a valid playback token for a video that
+/// has not been deleted; `tokenize_playlist` appends the token to every
+/// `EXT-X-KEY` URI.
+pub async fn get_content_key(
+    State(state): State<AppState>,
+    Path((video_id, key_index)): Path<(Uuid, i32)>,
+    Query(params): Query<AssetQuery>,
+    actor: Actor,
+    ClientIp(client_ip): ClientIp,
+) -> Result<Response> {
+    let token = params
+        .token
+        .ok_or_else(|| AppError::Forbidden(format!("Playback token required for video {}", video_id)))?;
+
+    state
+        .playback
+        .verify(&token, video_id, client_ip, actor.user_id().as_deref())
+        .map_err(|e| AppError::Forbidden(e.to_string()))?;
+    playable_video(&state, video_id).await?;
+
+    let key = state
+        .keys
+        .get(video_id, key_index)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load content key: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Key {} of video {} not found", key_index, video_id)))?;
+
+    tracing::info!("Delivered key {} of video {}", key_index, video_id);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream"),
+            (header::CACHE_CONTROL, "private, no-store"),
+        ],
+        key.key.to_vec(),
+    )
+        .into_response())
+}
+
+/// W3C Clear Key license server for common-encrypted (CENC) videos
+/// POST /videos/:id/license
+///
+/// The body is the license request generated by the browser's CDM, which
+/// is JSON but not sent as `application/json`. Only keys of the video the
+/// playback token was issued for are released, and only while it has not
+/// been deleted.
+pub async fn get_license(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<AssetQuery>,
+    actor: Actor,
+    ClientIp(client_ip): ClientIp,
+    body: Bytes,
+) -> Result<Response> {
+    let token = params
+        .token
+        .ok_or_else(|| AppError::Forbidden(format!("Playback token required for video {}", id)))?;
+
+    state
+        .playback
+        .verify(&token, id, client_ip, actor.user_id().as_deref())
+        .map_err(|e| AppError::Forbidden(e.to_string()))?;
+    playable_video(&state, id).await?;
+
+    let request: ClearKeyLicenseRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid license request: {}", e)))?;
+
+    if request.license_type != "temporary" {
+        return Err(AppError::BadRequest(format!(
+            "Unsupported license type: {}",
+            request.license_type
+        )));
+    }
+
+    let key_ids = request
+        .kids
+        .iter()
+        .map(|kid| {
+            URL_SAFE_NO_PAD
+                .decode(kid.trim_end_matches('='))
+                .ok()
+                .and_then(|bytes| Uuid::from_slice(&bytes).ok())
+                .ok_or_else(|| AppError::BadRequest(format!("Invalid key ID: {}", kid)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let keys = state
+        .keys
+        .find(id, &key_ids)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load content keys: {}", e)))?;
+
+    if keys.is_empty() {
+        return Err(AppError::NotFound(format!("No requested key belongs to video {}", id)));
+    }
+
+    tracing::info!("Issued Clear Key license with {} keys for video {}", keys.len(), id);
+
+    let license = ClearKeyLicenseResponse {
+        keys: keys
+            .into_iter()
+            .map(|stored| JsonWebKey {
+                kty: "oct",
+                kid: URL_SAFE_NO_PAD.encode(stored.key_id.as_bytes()),
+                k: URL_SAFE_NO_PAD.encode(stored.key),
+            })
+            .collect(),
+        license_type: request.license_type,
+    };
+
+    Ok((
+        [(header::CACHE_CONTROL, "private, no-store")],
+        axum::Json(license),
+    )
+        .into_response())
+}
+
+/// Ensure a video still exists and is not in the trash, as the manifests and
+/// segments it is played from are
+async fn playable_video(state: &AppState, video_id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM videos WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(video_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("Video {} not found", video_id)));
+    }
+    Ok(())
+}
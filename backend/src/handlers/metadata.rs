@@ -0,0 +1,357 @@
+use axum::{extract::State, http::StatusCode};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::error::{AppError, FieldError, Result};
+use crate::handlers::access::{listing_params, LISTED_TO_CALLER};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{Actor, AppState, AuthUser, CurrentTenant};
+use crate::models::{
+    Category, CategoryListResponse, CreateCategoryRequest, MetadataSchema, Role, Scope, Tag,
+    TagListResponse, Tenant,
+};
+
+/// Most tags a video can carry
+const MAX_TAGS: usize = 50;
+
+/// Longest tag name, matching `tags.name`
+const MAX_TAG_LEN: usize = 64;
+
+/// List the tags of the videos visible to the caller, most used first
+/// GET /tags
+pub async fn list_tags(
+    State(state): State<AppState>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<Json<TagListResponse>> {
+    let (user_id, sees_all) = listing_params(user.as_ref())?;
+
+    let tags = sqlx::query_as::<_, Tag>(&format!(
+        r#"
+        SELECT t.name, COUNT(*) AS video_count
+        FROM tags t
+        JOIN video_tags vt ON vt.tag_id = t.id
+        JOIN videos ON videos.id = vt.video_id
+        WHERE videos.deleted_at IS NULL AND {}
+        GROUP BY t.id, t.name
+        ORDER BY video_count DESC, LOWER(t.name)
+        "#,
+        LISTED_TO_CALLER
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(TagListResponse { tags }))
+}
+
+/// List the categories of the tenant
+/// GET /categories
+pub async fn list_categories(
+    State(state): State<AppState>,
+    CurrentTenant(tenant): CurrentTenant,
+) -> Result<Json<CategoryListResponse>> {
+    // Parents always predate their children, so creation order lists them first
+    let categories = sqlx::query_as::<_, Category>(
+        r#"
+        SELECT id, parent_id, name, created_at FROM categories
+        WHERE tenant_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(CategoryListResponse { categories }))
+}
+
+/// Create a category, optionally under a parent
+/// POST /categories
+pub async fn create_category(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateCategoryRequest>,
+) -> Result<(StatusCode, Json<Category>)> {
+    user.require(Scope::VideosWrite)?;
+    user.require_role(Role::Editor)?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(AppError::BadRequest(
+            "Category name must be 1 to 100 characters".to_string(),
+        ));
+    }
+    if let Some(parent_id) = payload.parent_id {
+        check_category(&state, &user.tenant, parent_id).await?;
+    }
+
+    let result = sqlx::query_as::<_, Category>(
+        r#"
+        INSERT INTO categories (tenant_id, parent_id, name)
+        VALUES ($1, $2, $3)
+        RETURNING id, parent_id, name, created_at
+        "#,
+    )
+    .bind(user.tenant.id)
+    .bind(payload.parent_id)
+    .bind(name)
+    .fetch_one(&state.db)
+    .await;
+
+    let category = match result {
+        Ok(category) => category,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return Err(AppError::Conflict(format!("Category {} already exists", name)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    tracing::info!("Category created: {} ({}) by {}", category.id, name, user);
+
+    Ok((StatusCode::CREATED, Json(category)))
+}
+
+/// Delete a category without subcategories; its videos become uncategorized
+/// DELETE /categories/:id
+pub async fn delete_category(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::VideosWrite)?;
+    user.require_role(Role::Editor)?;
+    check_category(&state, &user.tenant, id).await?;
+
+    let has_children = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM categories WHERE parent_id = $1)",
+    )
+    .bind(id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if has_children {
+        return Err(AppError::Conflict(format!("Category {} has subcategories", id)));
+    }
+
+    sqlx::query("DELETE FROM categories WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!("Category deleted: {} by {}", id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the JSON schema custom metadata must match
+/// GET /metadata-schema
+pub async fn get_metadata_schema(CurrentTenant(tenant): CurrentTenant) -> Json<MetadataSchema> {
+    Json(MetadataSchema {
+        schema: tenant.metadata_schema.clone(),
+    })
+}
+
+/// Set the JSON schema custom metadata must match
+/// PUT /metadata-schema
+///
+/// Metadata already stored is not checked again until the video is edited.
+pub async fn update_metadata_schema(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<MetadataSchema>,
+) -> Result<Json<MetadataSchema>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    if let Some(schema) = &payload.schema {
+        jsonschema::validator_for(schema)
+            .map_err(|e| AppError::BadRequest(format!("Invalid JSON schema: {}", e)))?;
+    }
+
+    sqlx::query("UPDATE tenants SET metadata_schema = $2 WHERE id = $1")
+        .bind(user.tenant.id)
+        .bind(&payload.schema)
+        .execute(&state.db)
+        .await?;
+    state.tenants.invalidate().await;
+
+    tracing::info!("Metadata schema of tenant {} updated by {}", user.tenant.slug, user);
+
+    Ok(Json(payload))
+}
+
+/// Ensure a category belongs to the tenant
+pub(crate) async fn check_category(state: &AppState, tenant: &Tenant, id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND tenant_id = $2)",
+    )
+    .bind(id)
+    .bind(tenant.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("Category {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Check custom metadata against the tenant's schema, listing every violation
+pub(crate) fn validate_metadata(tenant: &Tenant, metadata: &serde_json::Value) -> Result<()> {
+    let Some(schema) = &tenant.metadata_schema else {
+        return Ok(());
+    };
+
+    let validator = jsonschema::validator_for(schema).map_err(|e| {
+        AppError::Internal(format!("Invalid metadata schema of tenant {}: {}", tenant.slug, e))
+    })?;
+    let errors: Vec<FieldError> = validator
+        .iter_errors(metadata)
+        .map(|e| FieldError::new(format!("/custom_metadata{}", e.instance_path), e.to_string()))
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+    Ok(())
+}
+
+/// Apply a JSON Merge Patch (RFC 7386) to `target`: `null` removes a key
+/// and objects are merged recursively, anything else replaces the value
+pub(crate) fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let Some(target) = target.as_object_mut() else {
+        return;
+    };
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+        }
+    }
+}
+
+/// Trim and de-duplicate tag names, ignoring case
+pub(crate) fn normalize_tags(tags: &[String]) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN {
+            return Err(AppError::invalid(
+                "/tags",
+                format!("Tags must be 1 to {} characters", MAX_TAG_LEN),
+            ));
+        }
+        if !names.iter().any(|name| name.to_lowercase() == tag.to_lowercase()) {
+            names.push(tag.to_string());
+        }
+    }
+
+    if names.len() > MAX_TAGS {
+        return Err(AppError::invalid("/tags", format!("A video can have at most {} tags", MAX_TAGS)));
+    }
+    Ok(names)
+}
+
+/// Replace the tags of a video, creating tags the tenant does not have yet.
+///
+/// Returns the tags as stored, which keep the case of existing tags.
+pub(crate) async fn set_video_tags(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    video_id: Uuid,
+    tags: &[String],
+) -> Result<Vec<String>> {
+    sqlx::query(
+        r#"
+        INSERT INTO tags (tenant_id, name)
+        SELECT $1, tag FROM unnest($2::text[]) AS t(tag)
+        ON CONFLICT (tenant_id, LOWER(name)) DO NOTHING
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM video_tags WHERE video_id = $1")
+        .bind(video_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO video_tags (video_id, tag_id)
+        SELECT $1, id FROM tags
+        WHERE tenant_id = $2 AND LOWER(name) IN (SELECT LOWER(tag) FROM unnest($3::text[]) AS t(tag))
+        "#,
+    )
+    .bind(video_id)
+    .bind(tenant_id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    let stored = sqlx::query_scalar::<_, Vec<String>>(
+        r#"
+        SELECT ARRAY(
+            SELECT t.name::text FROM video_tags vt JOIN tags t ON t.id = vt.tag_id
+            WHERE vt.video_id = $1 ORDER BY LOWER(t.name)
+        )
+        "#,
+    )
+    .bind(video_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_patches_follow_rfc_7386() {
+        // The examples of RFC 7386, Appendix A
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "b"}), json!({"b": "c"}), json!({"a": "b", "b": "c"})),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (json!({"a": "b", "b": "c"}), json!({"a": null}), json!({"b": "c"})),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (json!({"a": [{"b": "c"}]}), json!({"a": [1]}), json!({"a": [1]})),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (json!({"e": null}), json!({"a": 1}), json!({"e": null, "a": 1})),
+            (json!([1, 2]), json!({"a": "b", "c": null}), json!({"a": "b"})),
+            (json!({}), json!({"a": {"bb": {"ccc": null}}}), json!({"a": {"bb": {}}})),
+        ];
+
+        for (target, patch, expected) in cases {
+            let mut merged = target.clone();
+            merge_patch(&mut merged, &patch);
+            assert_eq!(merged, expected, "{} patched with {}", target, patch);
+        }
+    }
+}
@@ -1,3 +1,41 @@
+pub mod access;
+pub mod actor;
+pub mod api_keys;
+pub mod auth;
+pub mod bulk;
+pub mod collections;
+pub mod extract;
+pub mod groups;
+pub mod history;
+pub mod imports;
+pub mod ingest;
+pub mod keys;
+pub mod metadata;
+pub mod quotas;
+pub mod search;
+pub mod sharing;
+pub mod stream;
+pub mod trash;
+pub mod users;
 pub mod video;
+pub mod webhooks;
 
+pub use actor::*;
+pub use api_keys::*;
+pub use auth::*;
+pub use bulk::*;
+pub use collections::*;
+pub use groups::*;
+pub use history::*;
+pub use imports::*;
+pub use ingest::*;
+pub use keys::*;
+pub use metadata::*;
+pub use quotas::*;
+pub use search::*;
+pub use sharing::*;
+pub use stream::*;
+pub use trash::*;
+pub use users::*;
 pub use video::*;
+pub use webhooks::*;
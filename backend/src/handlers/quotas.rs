@@ -0,0 +1,106 @@
+use axum::{extract::State, http::StatusCode};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{QuotaLimits, Role, Scope, UsageReport, UsageResponse};
+
+/// Storage used by the caller against their quota, plus the tenant's for admins
+/// GET /usage
+pub async fn get_usage(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<UsageResponse>> {
+    let tenant = if user.role >= Role::Admin {
+        Some(UsageReport {
+            usage: state.quotas.usage(user.tenant.id, None).await?,
+            limits: state.quotas.tenant_limits(user.tenant.id).await?,
+        })
+    } else {
+        None
+    };
+
+    Ok(Json(UsageResponse {
+        user: user_report(&state, &user, user.id).await?,
+        tenant,
+    }))
+}
+
+/// Storage used by a user of the tenant against their quota
+/// GET /users/:id/usage
+pub async fn get_user_usage(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<UsageReport>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+    tenant_user(&state, &user, id).await?;
+
+    Ok(Json(user_report(&state, &user, id).await?))
+}
+
+/// Set the quota of a user; omitted limits are unlimited
+/// PUT /users/:id/quota
+pub async fn update_user_quota(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(limits): Json<QuotaLimits>,
+) -> Result<Json<UsageReport>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+    tenant_user(&state, &user, id).await?;
+
+    if limits.any_negative() {
+        return Err(AppError::BadRequest("Limits cannot be negative".to_string()));
+    }
+
+    state.quotas.set_user_limits(user.tenant.id, id, &limits).await?;
+
+    tracing::info!("Quota of user {} updated by {}", id, user);
+
+    Ok(Json(user_report(&state, &user, id).await?))
+}
+
+/// Revert the quota of a user to the configured defaults
+/// DELETE /users/:id/quota
+pub async fn reset_user_quota(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+    tenant_user(&state, &user, id).await?;
+
+    if state.quotas.reset_user_limits(id).await? {
+        tracing::info!("Quota of user {} reset by {}", id, user);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn user_report(state: &AppState, user: &AuthUser, id: Uuid) -> Result<UsageReport> {
+    Ok(UsageReport {
+        usage: state.quotas.usage(user.tenant.id, Some(id)).await?,
+        limits: state.quotas.user_limits(id).await?,
+    })
+}
+
+/// Ensure a user belongs to the caller's tenant
+async fn tenant_user(state: &AppState, user: &AuthUser, id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND tenant_id = $2)",
+    )
+    .bind(id)
+    .bind(user.tenant.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("User {} not found", id)));
+    }
+    Ok(())
+}
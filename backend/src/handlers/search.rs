@@ -0,0 +1,208 @@
+use axum::extract::State;
+use serde::Deserialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::{listing_params, LISTED_TO_CALLER, VIDEO_COLUMNS};
+use crate::handlers::extract::{Json, Query};
+use crate::handlers::{Actor, AppState, CurrentTenant};
+use crate::models::{SearchHighlights, SearchHit, SearchResponse, Video, VideoStatus};
+
+/// Most terms of a search query that are matched
+const MAX_QUERY_TERMS: usize = 16;
+
+/// `ts_headline` options of the title, highlighted as a whole
+const TITLE_HEADLINE: &str = "StartSel=<mark>, StopSel=</mark>, HighlightAll=true";
+
+/// `ts_headline` options of descriptions and transcripts, cut to the
+/// fragments around the matches
+const TEXT_HEADLINE: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=30, MinWords=10, FragmentDelimiter=\" … \"";
+
+/// HTML-escape a text column in SQL, before `ts_headline` adds its
+/// `<mark>` tags, so those are the only markup of a highlight
+fn html_escaped(column: &str) -> String {
+    format!("replace(replace(replace({}, '&', '&amp;'), '<', '&lt;'), '>', '&gt;')", column)
+}
+
+/// Query parameters for searching videos
+#[derive(Debug, Deserialize)]
+pub struct SearchVideosQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub status: Option<String>,
+    pub owner_id: Option<Uuid>,
+    /// Text search configuration stemming the query; `SEARCH_LANGUAGE` by default
+    pub language: Option<String>,
+}
+
+#[derive(FromRow)]
+struct SearchRow {
+    #[sqlx(flatten)]
+    video: Video,
+    rank: f32,
+    title_highlight: String,
+    description_highlight: Option<String>,
+    transcript_highlight: Option<String>,
+}
+
+/// Search the videos visible to the caller by title, description and transcript
+/// GET /videos/search
+///
+/// Every term of the query must match, as a word prefix; results are
+/// ordered by relevance, titles weighing more than descriptions and
+/// descriptions more than transcripts.
+pub async fn search_videos(
+    State(state): State<AppState>,
+    Query(params): Query<SearchVideosQuery>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<Json<SearchResponse>> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let (user_id, sees_all) = listing_params(user.as_ref())?;
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<VideoStatus>)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    let query = prefix_query(&params.q)
+        .ok_or_else(|| AppError::BadRequest("Search query has no terms".to_string()))?;
+    let language = match &params.language {
+        Some(language) => text_search_config(&state, language).await?,
+        None => state.search_language.clone(),
+    };
+
+    let rows = sqlx::query_as::<_, SearchRow>(&format!(
+        r#"
+        SELECT {},
+               ts_rank_cd(search_vector, query) AS rank,
+               ts_headline(language::regconfig, {}, query, $8) AS title_highlight,
+               ts_headline(language::regconfig, {}, query, $9) AS description_highlight,
+               CASE WHEN to_tsvector(language::regconfig, COALESCE(transcript, '')) @@ query
+                    THEN ts_headline(language::regconfig, {}, query, $9)
+               END AS transcript_highlight
+        FROM videos, to_tsquery($6::regconfig, $7) AS query
+        WHERE deleted_at IS NULL AND {} AND search_vector @@ query
+          AND ($4::video_status IS NULL OR status = $4)
+          AND ($5::uuid IS NULL OR owner_id = $5)
+        ORDER BY rank DESC, created_at DESC
+        LIMIT $10 OFFSET $11
+        "#,
+        VIDEO_COLUMNS,
+        html_escaped("title"),
+        html_escaped("description"),
+        html_escaped("transcript"),
+        LISTED_TO_CALLER
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(&status)
+    .bind(params.owner_id)
+    .bind(&language)
+    .bind(&query)
+    .bind(TITLE_HEADLINE)
+    .bind(TEXT_HEADLINE)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        r#"
+        SELECT COUNT(*) FROM videos, to_tsquery($6::regconfig, $7) AS query
+        WHERE deleted_at IS NULL AND {} AND search_vector @@ query
+          AND ($4::video_status IS NULL OR status = $4)
+          AND ($5::uuid IS NULL OR owner_id = $5)
+        "#,
+        LISTED_TO_CALLER
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(&status)
+    .bind(params.owner_id)
+    .bind(&language)
+    .bind(&query)
+    .fetch_one(&state.db)
+    .await?;
+
+    let videos = rows
+        .into_iter()
+        .map(|row| SearchHit {
+            video: row.video.to_response(&state.base_url),
+            rank: row.rank,
+            highlights: SearchHighlights {
+                title: row.title_highlight,
+                description: row.description_highlight,
+                transcript: row.transcript_highlight,
+            },
+        })
+        .collect();
+
+    Ok(Json(SearchResponse { videos, total }))
+}
+
+/// Check that a text search configuration exists, returning its name
+pub(crate) async fn text_search_config(state: &AppState, name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM pg_ts_config WHERE cfgname = $1)",
+    )
+    .bind(&name)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::BadRequest(format!("Unknown search language: {}", name)));
+    }
+    Ok(name)
+}
+
+/// `to_tsquery` input matching every word of `q` as a prefix, or `None`
+/// when `q` has no words.
+///
+/// Anything but letters and digits separates words, so user input cannot
+/// inject tsquery operators.
+fn prefix_query(q: &str) -> Option<String> {
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .take(MAX_QUERY_TERMS)
+        .map(|term| format!("{}:*", term.to_lowercase()))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_become_prefix_terms() {
+        assert_eq!(prefix_query("Rust tutorial").as_deref(), Some("rust:* & tutorial:*"));
+        assert_eq!(prefix_query("  café  Ünïcode ").as_deref(), Some("café:* & ünïcode:*"));
+        assert_eq!(prefix_query("part2").as_deref(), Some("part2:*"));
+    }
+
+    #[test]
+    fn tsquery_operators_only_separate_words() {
+        assert_eq!(prefix_query("a & !b | (c:*)").as_deref(), Some("a:* & b:* & c:*"));
+        assert_eq!(prefix_query("it's <-> 'quoted'").as_deref(), Some("it:* & s:* & quoted:*"));
+        assert_eq!(prefix_query(""), None);
+        assert_eq!(prefix_query("&|!():*'\\"), None);
+    }
+
+    #[test]
+    fn queries_are_capped_at_the_term_limit() {
+        let q = (0..MAX_QUERY_TERMS + 5).map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ");
+        let query = prefix_query(&q).unwrap();
+        assert_eq!(query.split(" & ").count(), MAX_QUERY_TERMS);
+    }
+}
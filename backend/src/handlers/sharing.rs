@@ -0,0 +1,138 @@
+use axum::{extract::State, http::StatusCode};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::manageable_video;
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{CreateShareRequest, Scope, ShareListResponse, VideoShare};
+
+/// List who a video is shared with
+/// GET /videos/:id/shares
+pub async fn list_shares(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<ShareListResponse>> {
+    user.require(Scope::VideosRead)?;
+    manageable_video(&state, id, &user).await?;
+
+    let shares = sqlx::query_as::<_, VideoShare>(
+        r#"
+        SELECT s.id, s.video_id, s.user_id, u.username, s.group_id, g.name AS group_name,
+               s.created_by, s.created_at
+        FROM video_shares s
+        LEFT JOIN users u ON u.id = s.user_id
+        LEFT JOIN user_groups g ON g.id = s.group_id
+        WHERE s.video_id = $1
+        ORDER BY s.created_at
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(ShareListResponse { shares }))
+}
+
+/// Share a video with a user or a group
+/// POST /videos/:id/shares
+pub async fn share_video(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<CreateShareRequest>,
+) -> Result<(StatusCode, Json<VideoShare>)> {
+    user.require(Scope::VideosWrite)?;
+    manageable_video(&state, id, &user).await?;
+
+    let (grantee_user, grantee_group) = match (payload.username.as_deref(), payload.group_id) {
+        (Some(username), None) => {
+            let user_id = sqlx::query_scalar::<_, Uuid>(
+                "SELECT id FROM users WHERE tenant_id = $2 AND LOWER(username) = LOWER($1)",
+            )
+            .bind(username.trim())
+            .bind(user.tenant.id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", username)))?;
+            (Some(user_id), None)
+        }
+        (None, Some(group_id)) => {
+            let exists = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM user_groups WHERE id = $1 AND tenant_id = $2)",
+            )
+            .bind(group_id)
+            .bind(user.tenant.id)
+            .fetch_one(&state.db)
+            .await?;
+            if !exists {
+                return Err(AppError::NotFound(format!("Group {} not found", group_id)));
+            }
+            (None, Some(group_id))
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Specify either a username or a group_id".to_string(),
+            ))
+        }
+    };
+
+    let result = sqlx::query_as::<_, VideoShare>(
+        r#"
+        WITH share AS (
+            INSERT INTO video_shares (video_id, user_id, group_id, created_by)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+        )
+        SELECT s.id, s.video_id, s.user_id, u.username, s.group_id, g.name AS group_name,
+               s.created_by, s.created_at
+        FROM share s
+        LEFT JOIN users u ON u.id = s.user_id
+        LEFT JOIN user_groups g ON g.id = s.group_id
+        "#,
+    )
+    .bind(id)
+    .bind(grantee_user)
+    .bind(grantee_group)
+    .bind(user.id)
+    .fetch_one(&state.db)
+    .await;
+
+    let share = match result {
+        Ok(share) => share,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return Err(AppError::Conflict(format!("Video {} is already shared with them", id)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    tracing::info!("Video shared: {} with {:?}/{:?} by {}", id, grantee_user, grantee_group, user);
+
+    Ok((StatusCode::CREATED, Json(share)))
+}
+
+/// Stop sharing a video
+/// DELETE /videos/:id/shares/:share_id
+pub async fn unshare_video(
+    State(state): State<AppState>,
+    Path((id, share_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::VideosWrite)?;
+    manageable_video(&state, id, &user).await?;
+
+    let result = sqlx::query("DELETE FROM video_shares WHERE id = $1 AND video_id = $2")
+        .bind(share_id)
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Share {} not found", share_id)));
+    }
+
+    tracing::info!("Video unshared: {} (share {}) by {}", id, share_id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
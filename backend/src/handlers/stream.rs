@@ -0,0 +1,484 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::viewable_video;
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::{Actor, AppState, ClientIp, CurrentTenant};
+use crate::models::{PlaybackResponse, VideoStatus};
+use crate::services::playback::{tokenize_mpd, tokenize_playlist};
+use crate::services::storage::{ByteRange, StorageError};
+use crate::services::StorageService;
+
+/// Cache policy for playlists; they are revalidated cheaply through their ETag
+const CACHE_MANIFEST: &str = "no-cache";
+
+/// Cache policy for media segments of videos anyone may play. Segments never
+/// change, but a video can be made private or deleted, and shared caches would
+/// keep serving it without a token for as long as they hold its segments.
+const CACHE_SEGMENT: &str = "public, max-age=60";
+
+/// Cache policy for thumbnails and other images, short for the same reason
+const CACHE_IMAGE: &str = "public, max-age=60";
+
+/// Cache policy for anything else served from storage
+const CACHE_DEFAULT: &str = "public, max-age=60";
+
+/// Cache policy for anything served after a playback token check and for
+/// playlists rewritten with a token; caching them would outlive the token
+/// and its binding
+const CACHE_TOKENIZED: &str = "private, no-store";
+
+/// Query parameters for requesting a playback token
+#[derive(Debug, Deserialize)]
+pub struct PlaybackQuery {
+    /// Bind the token to the caller's IP address
+    #[serde(default)]
+    pub bind_ip: bool,
+    /// Bind the token to the calling user
+    #[serde(default)]
+    pub bind_user: bool,
+}
+
+/// Query parameters accepted by the streaming routes
+#[derive(Debug, Deserialize)]
+pub struct AssetQuery {
+    pub token: Option<String>,
+}
+
+/// Issue a signed, expiring playback URL to a caller who may watch the video
+/// GET /videos/:id/playback
+pub async fn get_playback(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PlaybackQuery>,
+    CurrentTenant(tenant): CurrentTenant,
+    actor: Actor,
+    ClientIp(client_ip): ClientIp,
+) -> Result<Json<PlaybackResponse>> {
+    let video = viewable_video(&state, &tenant, id, actor.0.as_ref()).await?;
+
+    let manifest_path = match (video.status, video.manifest_path) {
+        (VideoStatus::Ready, Some(path)) => path,
+        _ => return Err(AppError::BadRequest(format!("Video {} is not ready for playback", id))),
+    };
+
+    let ip = if params.bind_ip {
+        Some(client_ip.ok_or_else(|| {
+            AppError::BadRequest("Client address unavailable for IP binding".to_string())
+        })?)
+    } else {
+        None
+    };
+
+    let user = if params.bind_user {
+        Some(actor.user_id().ok_or_else(|| {
+            AppError::BadRequest("No user to bind the playback token to".to_string())
+        })?)
+    } else {
+        None
+    };
+
+    let (token, expires_at) = state.playback.issue(id, ip, user);
+
+    // CMAF common encryption also yields a DASH manifest and a Clear Key license
+    let common_encryption = matches!(video.encryption.as_deref(), Some("cenc") | Some("cbcs"));
+    let (dash_url, license_url) = if common_encryption {
+        (
+            Some(format!("{}/manifests/{}/manifest.mpd?token={}", state.base_url, id, token)),
+            Some(format!("{}/api/videos/{}/license?token={}", state.base_url, id, token)),
+        )
+    } else {
+        (None, None)
+    };
+
+    Ok(Json(PlaybackResponse {
+        video_id: id,
+        streaming_url: format!("{}/manifests/{}?token={}", state.base_url, manifest_path, token),
+        thumbnail_url: video
+            .thumbnail_path
+            .map(|path| format!("{}/segments/{}?token={}", state.base_url, path, token)),
+        dash_url,
+        license_url,
+        token,
+        expires_at,
+    }))
+}
+
+/// Stream an HLS/DASH manifest
+/// GET /manifests/*key
+///
+/// When a playback token is supplied, HLS playlists and DASH manifests are
+/// rewritten so every child playlist and segment URI carries the same token.
+pub async fn serve_manifest(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(params): Query<AssetQuery>,
+    actor: Actor,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let token = params.token.as_deref();
+    let (storage, restricted) =
+        authorize_asset(&state, &key, token, client_ip, actor.user_id().as_deref()).await?;
+
+    let Some(token) = token.filter(|_| key.ends_with(".m3u8") || key.ends_with(".mpd")) else {
+        let cache_control = restricted.then_some(CACHE_TOKENIZED);
+        return object_response(&storage, storage.manifests_bucket(), &key, &headers, cache_control)
+            .await;
+    };
+
+    let playlist = storage
+        .download_manifest(&key)
+        .await
+        .map_err(|e| storage_error(&key, e))?;
+    let playlist = String::from_utf8(playlist.to_vec())
+        .map_err(|_| AppError::Internal(format!("Manifest {} is not valid UTF-8", key)))?;
+
+    let (content_type, body) = if key.ends_with(".mpd") {
+        ("application/dash+xml", tokenize_mpd(&playlist, token))
+    } else {
+        ("application/vnd.apple.mpegurl", tokenize_playlist(&playlist, token))
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, CACHE_TOKENIZED),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Stream a media segment or thumbnail
+/// GET /segments/*key
+pub async fn serve_segment(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(params): Query<AssetQuery>,
+    actor: Actor,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let (storage, restricted) =
+        authorize_asset(&state, &key, params.token.as_deref(), client_ip, actor.user_id().as_deref())
+            .await?;
+    let cache_control = restricted.then_some(CACHE_TOKENIZED);
+    object_response(&storage, storage.segments_bucket(), &key, &headers, cache_control).await
+}
+
+/// Check that a streaming asset belongs to a video that may be played, and
+/// return the storage of the video's tenant and whether a playback token was
+/// checked, in which case the response must not be cached.
+///
+/// Asset keys always start with the video ID, which ties every manifest and
+/// segment request back to its video and tenant. Private videos require a
+/// valid playback token for every asset, encrypted ones for all but their
+/// thumbnail; a token that is supplied is always validated.
+pub(crate) async fn authorize_asset(
+    state: &AppState,
+    key: &str,
+    token: Option<&str>,
+    client_ip: Option<std::net::IpAddr>,
+    user: Option<&str>,
+) -> Result<(StorageService, bool)> {
+    let not_found = || AppError::NotFound(format!("Asset {} not found", key));
+
+    let video_id = key
+        .split('/')
+        .next()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(not_found)?;
+
+    let (private, encrypted, tenant_id) = sqlx::query_as::<_, (bool, bool, Uuid)>(
+        r#"
+        SELECT visibility = 'private', encryption IS NOT NULL, tenant_id
+        FROM videos WHERE id = $1 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(video_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(not_found)?;
+
+    match token {
+        Some(token) => {
+            state
+                .playback
+                .verify(token, video_id, client_ip, user)
+                .map_err(|e| AppError::Forbidden(e.to_string()))?;
+        }
+        None if private || (encrypted && !is_image(key)) => {
+            return Err(AppError::Forbidden(format!(
+                "Playback token required for video {}",
+                video_id
+            )));
+        }
+        None => {}
+    }
+
+    let tenant = state
+        .tenants
+        .get(tenant_id)
+        .await?
+        .ok_or_else(|| AppError::Internal(format!("Tenant of video {} not found", video_id)))?;
+
+    tracing::debug!("Serving asset {} of video {}", key, video_id);
+    Ok((state.storage.for_tenant(&tenant), token.is_some()))
+}
+
+/// Build the response for a stored object.
+///
+/// Honors `If-None-Match` (304), single-part `Range`/`If-Range` requests
+/// (206/416) and sets content type and cache policy from the file extension,
+/// unless a cache policy is given.
+pub async fn object_response(
+    storage: &StorageService,
+    bucket: &str,
+    key: &str,
+    headers: &HeaderMap,
+    cache_control: Option<&'static str>,
+) -> Result<Response> {
+    let meta = storage
+        .head_object(bucket, key)
+        .await
+        .map_err(|e| storage_error(key, e))?;
+
+    let etag = meta.etag.as_deref();
+    let content_type = content_type_for(key)
+        .map(str::to_string)
+        .or(meta.content_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let mut response_headers = HeaderMap::new();
+    let cache_control = cache_control.unwrap_or_else(|| cache_control_for(key));
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Some(value) = etag.and_then(|e| HeaderValue::from_str(e).ok()) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Some(value) = meta.last_modified.and_then(|t| HeaderValue::from_str(&http_date(t)).ok()) {
+        response_headers.insert(header::LAST_MODIFIED, value);
+    }
+
+    if let (Some(etag), Some(candidates)) = (etag, header_str(headers, header::IF_NONE_MATCH)) {
+        if etag_matches(candidates, etag) {
+            return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+        }
+    }
+
+    // A stale If-Range validator means the client must receive the full object
+    let range_applies = match header_str(headers, header::IF_RANGE) {
+        Some(validator) => etag == Some(validator.trim()),
+        None => true,
+    };
+
+    let range = match header_str(headers, header::RANGE).filter(|_| range_applies) {
+        Some(value) => match parse_range(value, meta.size) {
+            Ok(range) => range,
+            Err(()) => {
+                response_headers.insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes */{}", meta.size))
+                        .expect("content range is ASCII"),
+                );
+                return Ok((StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response());
+            }
+        },
+        None => None,
+    };
+
+    let object = storage
+        .stream_object(bucket, key, range)
+        .await
+        .map_err(|e| storage_error(key, e))?;
+
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+
+    let status = match range {
+        Some(range) => {
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {}-{}/{}", range.start, range.end, meta.size))
+                    .expect("content range is ASCII"),
+            );
+            response_headers.insert(header::CONTENT_LENGTH, range.len().into());
+            StatusCode::PARTIAL_CONTENT
+        }
+        None => {
+            let length = object.content_length.unwrap_or(meta.size);
+            response_headers.insert(header::CONTENT_LENGTH, length.into());
+            StatusCode::OK
+        }
+    };
+
+    Ok((status, response_headers, Body::from_stream(object.body)).into_response())
+}
+
+fn storage_error(key: &str, e: StorageError) -> AppError {
+    match e {
+        StorageError::NotFound(_) | StorageError::InvalidKey(_) => {
+            AppError::NotFound(format!("Asset {} not found", key))
+        }
+        e => AppError::Storage(e.to_string()),
+    }
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Whether an `If-None-Match` header value matches the object's ETag
+fn etag_matches(candidates: &str, etag: &str) -> bool {
+    let strip_weak = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = strip_weak(etag);
+
+    candidates
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || strip_weak(candidate) == etag)
+}
+
+/// Parse a `Range` header against an object of `size` bytes.
+///
+/// Returns `Ok(None)` for headers that should be ignored (other units or
+/// multiple ranges, which are answered with the full object) and `Err(())`
+/// when the range cannot be satisfied.
+fn parse_range(value: &str, size: u64) -> std::result::Result<Option<ByteRange>, ()> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+
+    let (start, end) = spec.split_once('-').ok_or(())?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+        // Suffix range: the last N bytes
+        let suffix: u64 = end.parse().map_err(|_| ())?;
+        if suffix == 0 || size == 0 {
+            return Err(());
+        }
+        ByteRange {
+            start: size.saturating_sub(suffix),
+            end: size - 1,
+        }
+    } else {
+        let start: u64 = start.parse().map_err(|_| ())?;
+        let end: u64 = if end.is_empty() {
+            size.saturating_sub(1)
+        } else {
+            end.parse::<u64>().map_err(|_| ())?.min(size.saturating_sub(1))
+        };
+        if start >= size || start > end {
+            return Err(());
+        }
+        ByteRange { start, end }
+    };
+
+    Ok(Some(range))
+}
+
+/// Content type of a streaming asset, by file extension
+fn content_type_for(key: &str) -> Option<&'static str> {
+    let extension = key.rsplit_once('.')?.1.to_ascii_lowercase();
+
+    let content_type = match extension.as_str() {
+        "m3u8" => "application/vnd.apple.mpegurl",
+        "mpd" => "application/dash+xml",
+        "m4s" => "video/iso.segment",
+        "mp4" => "video/mp4",
+        "m4a" => "audio/mp4",
+        "webm" => "video/webm",
+        "ts" => "video/mp2t",
+        "vtt" => "text/vtt",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        _ => return None,
+    };
+
+    Some(content_type)
+}
+
+fn is_image(key: &str) -> bool {
+    content_type_for(key).is_some_and(|t| t.starts_with("image/"))
+}
+
+/// Cache-Control policy of a streaming asset, by file extension
+fn cache_control_for(key: &str) -> &'static str {
+    match content_type_for(key) {
+        Some("application/vnd.apple.mpegurl") | Some("application/dash+xml") => CACHE_MANIFEST,
+        Some(t) if t.starts_with("video/") || t.starts_with("audio/") => CACHE_SEGMENT,
+        Some(t) if t.starts_with("image/") => CACHE_IMAGE,
+        _ => CACHE_DEFAULT,
+    }
+}
+
+/// Format a timestamp as an HTTP date (RFC 7231 IMF-fixdate)
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> Option<ByteRange> {
+        Some(ByteRange { start, end })
+    }
+
+    #[test]
+    fn ranges_are_clamped_to_the_object() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok(range(0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Ok(range(500, 999)));
+        assert_eq!(parse_range("bytes=900-2000", 1000), Ok(range(900, 999)));
+        assert_eq!(parse_range(" bytes= 10 - 19 ", 1000), Ok(range(10, 19)));
+    }
+
+    #[test]
+    fn suffix_ranges_take_the_last_bytes() {
+        assert_eq!(parse_range("bytes=-100", 1000), Ok(range(900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Ok(range(0, 999)));
+        assert_eq!(parse_range("bytes=-0", 1000), Err(()));
+        assert_eq!(parse_range("bytes=-10", 0), Err(()));
+    }
+
+    #[test]
+    fn unsatisfiable_ranges_are_errors() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=50-10", 1000), Err(()));
+        assert_eq!(parse_range("bytes=0-", 0), Err(()));
+        assert_eq!(parse_range("bytes=abc-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=10", 1000), Err(()));
+    }
+
+    #[test]
+    fn other_units_and_multiple_ranges_are_ignored() {
+        assert_eq!(parse_range("items=0-9", 1000), Ok(None));
+        assert_eq!(parse_range("bytes=0-9,20-29", 1000), Ok(None));
+    }
+
+    #[test]
+    fn etags_match_weakly_in_lists_and_wildcards() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("\"xyz\", \"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"abcd\"", "\"abc\""));
+        assert!(!etag_matches("\"xyz\", W/\"uvw\"", "\"abc\""));
+    }
+}
@@ -0,0 +1,140 @@
+use axum::extract::State;
+use chrono::Duration;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::VIDEO_COLUMNS;
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::history::audit_actor;
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    Scope, TrashListResponse, TrashedVideoResponse, Video, VideoResponse, WebhookEvent,
+};
+use crate::services::webhooks;
+
+/// Query parameters for listing the trash bin
+#[derive(Debug, Deserialize)]
+pub struct ListTrashQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List deleted videos awaiting purge; editors see every user's
+/// GET /trash
+pub async fn list_trash(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(params): Query<ListTrashQuery>,
+) -> Result<Json<TrashListResponse>> {
+    user.require(Scope::VideosRead)?;
+
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let videos = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        SELECT {} FROM videos
+        WHERE deleted_at IS NOT NULL AND tenant_id = $5 AND ($1 OR owner_id = $2)
+        ORDER BY deleted_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        VIDEO_COLUMNS
+    ))
+    .bind(user.sees_all_videos())
+    .bind(user.id)
+    .bind(limit)
+    .bind(offset)
+    .bind(user.tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM videos WHERE deleted_at IS NOT NULL AND tenant_id = $3 AND ($1 OR owner_id = $2)",
+    )
+    .bind(user.sees_all_videos())
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    let retention = Duration::days(state.trash_retention_days);
+    let trashed = videos
+        .into_iter()
+        .filter_map(|v| {
+            let deleted_at = v.deleted_at?;
+            Some(TrashedVideoResponse {
+                deleted_by: v.deleted_by.clone(),
+                purge_at: deleted_at + retention,
+                deleted_at,
+                video: v.to_response(&state.base_url),
+            })
+        })
+        .collect();
+
+    Ok(Json(TrashListResponse {
+        videos: trashed,
+        total,
+    }))
+}
+
+/// Restore a deleted video from the trash bin
+/// POST /videos/:id/restore
+pub async fn restore_video(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<Json<VideoResponse>> {
+    user.require(Scope::VideosWrite)?;
+
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, &user).await?;
+
+    // Videos the garbage collector has started purging can no longer be restored
+    let restored = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        UPDATE videos
+        SET deleted_at = NULL, deleted_by = NULL
+        WHERE id = $1 AND tenant_id = $4 AND deleted_at IS NOT NULL AND purge_after IS NULL
+          AND ($2 OR owner_id = $3)
+        RETURNING {}
+        "#,
+        VIDEO_COLUMNS
+    ))
+    .bind(id)
+    .bind(user.sees_all_videos())
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if restored.is_some() {
+        webhooks::emit(&mut tx, id, WebhookEvent::Restored).await?;
+    }
+    tx.commit().await?;
+
+    if let Some(video) = restored {
+        tracing::info!("Video restored: {} by {}", id, user);
+        return Ok(Json(video.to_response(&state.base_url)));
+    }
+
+    let in_trash = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM videos
+            WHERE id = $1 AND tenant_id = $4 AND deleted_at IS NOT NULL AND ($2 OR owner_id = $3)
+        )
+        "#,
+    )
+    .bind(id)
+    .bind(user.sees_all_videos())
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if in_trash {
+        Err(AppError::Conflict(format!("Video {} is already being purged", id)))
+    } else {
+        Err(AppError::NotFound(format!("Video {} not found in trash", id)))
+    }
+}
@@ -0,0 +1,52 @@
+use axum::extract::State;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{Role, Scope, UpdateRoleRequest, UserListResponse, UserResponse};
+
+/// List every account of the tenant
+/// GET /users
+pub async fn list_users(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<UserListResponse>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let users = state.auth.list_users(user.tenant.id).await?;
+
+    Ok(Json(UserListResponse {
+        users: users.iter().map(|u| u.to_response()).collect(),
+    }))
+}
+
+/// Change the role of a user
+/// PUT /users/:id/role
+///
+/// The new role applies to API keys at once and to sessions on their next
+/// token refresh.
+pub async fn update_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> Result<Json<UserResponse>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    if id == user.id && payload.role < Role::Admin {
+        return Err(AppError::BadRequest("Admins cannot demote themselves".to_string()));
+    }
+
+    let updated = state
+        .auth
+        .set_role(user.tenant.id, id, payload.role)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", id)))?;
+
+    tracing::info!("Role of user {} set to {} by {}", id, payload.role.as_str(), user);
+
+    Ok(Json(updated.to_response()))
+}
@@ -1,16 +1,40 @@
 use axum::{
-    extract::{Multipart, Path, Query, State},
-    http::StatusCode,
-    Json,
+    extract::{
+        multipart::{Field, MultipartError},
+        Multipart, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
 };
-use serde::Deserialize;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::path::PathBuf;
 use std::sync::Arc;
+use ipnet::IpNet;
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 use crate::error::{AppError, Result};
-use crate::models::{TranscodeJobMessage, UpdateVideoRequest, Video, VideoListResponse, VideoResponse};
-use crate::services::{StorageService, TranscoderService};
+use crate::handlers::access::{
+    listing_params, manageable_video, viewable_video, LISTED_TO_CALLER, VIDEO_COLUMNS,
+};
+use crate::handlers::collections::remove_from_collections;
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::history::{audit_actor, audit_details};
+use crate::handlers::metadata::{
+    check_category, merge_patch, normalize_tags, set_video_tags, validate_metadata,
+};
+use crate::handlers::search::text_search_config;
+use crate::handlers::{Actor, AuthUser, CurrentTenant};
+use crate::models::{
+    AuditAction, Role, Scope, TranscodeJobMessage, UpdateVideoRequest, Video, VideoFilter,
+    VideoListResponse, VideoResponse, VideoStatus, Visibility, WebhookEvent,
+};
+use crate::request_id;
+use crate::services::webhooks;
+use crate::services::{ApiKeyService, AuthService, ImportService, IngestService, KeyService, TenantService, PlaybackTokens, QuotaService, StorageService, TranscoderService};
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -18,34 +42,156 @@ pub struct AppState {
     pub db: PgPool,
     pub storage: Arc<StorageService>,
     pub transcoder: Arc<TranscoderService>,
+    pub imports: Arc<ImportService>,
+    pub ingest: Arc<IngestService>,
+    pub playback: Arc<PlaybackTokens>,
+    pub keys: Arc<KeyService>,
+    pub auth: Arc<AuthService>,
+    pub api_keys: Arc<ApiKeyService>,
+    pub tenants: Arc<TenantService>,
+    pub quotas: Arc<QuotaService>,
     pub base_url: String,
+    pub trash_retention_days: i64,
+    /// Text search configuration of videos uploaded without a language
+    pub search_language: String,
+    /// Proxies trusted to report the client address, see `ClientIp`
+    pub trusted_proxies: Arc<Vec<IpNet>>,
+    /// Directory receiving uploads before they are stored
+    pub temp_dir: PathBuf,
 }
 
 /// Query parameters for listing videos
 #[derive(Debug, Deserialize)]
 pub struct ListVideosQuery {
     pub limit: Option<i64>,
+    /// Ignored when a cursor is given
     pub offset: Option<i64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub sort: VideoSort,
+    #[serde(default)]
+    pub order: SortOrder,
     pub status: Option<String>,
+    pub owner_id: Option<Uuid>,
+    /// Duration range, in seconds
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+    /// Original size range, in bytes
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    /// Upload date range; `created_before` is exclusive
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Name of a rendition the video must have, e.g. `720p`
+    pub resolution: Option<String>,
+    /// Tag the video must carry, ignoring case
+    pub tag: Option<String>,
+    /// Category of the video or any of its subcategories
+    pub category_id: Option<Uuid>,
+    /// JSON object the custom metadata must contain, e.g. `{"genre":"jazz"}`
+    pub metadata: Option<String>,
+}
+
+/// Sort key of video listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    Duration,
+    Size,
+}
+
+impl VideoSort {
+    /// Expression sorted by and its SQL type; videos not yet probed sort
+    /// as zero seconds long
+    fn column(self) -> (&'static str, &'static str) {
+        match self {
+            Self::CreatedAt => ("created_at", "timestamptz"),
+            Self::UpdatedAt => ("updated_at", "timestamptz"),
+            Self::Title => ("title", "text"),
+            Self::Duration => ("COALESCE(duration_seconds, 0)", "float8"),
+            Self::Size => ("original_size", "bigint"),
+        }
+    }
+
+    /// Value of the sort expression for a video, as text
+    fn key(self, video: &Video) -> String {
+        match self {
+            Self::CreatedAt => video.created_at.to_rfc3339(),
+            Self::UpdatedAt => video.updated_at.to_rfc3339(),
+            Self::Title => video.title.clone(),
+            Self::Duration => video.duration_seconds.unwrap_or(0.0).to_string(),
+            Self::Size => video.original_size.to_string(),
+        }
+    }
+}
+
+/// Direction of video listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Position after the last video of a page.
+///
+/// Pages continue from the sort key and ID of that video rather than an
+/// offset, so uploads and deletions between requests neither skip nor
+/// repeat videos.
+#[derive(Debug, Serialize, Deserialize)]
+struct ListCursor {
+    sort: VideoSort,
+    order: SortOrder,
+    key: String,
+    id: Uuid,
+}
+
+impl ListCursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))
+    }
 }
 
 /// Upload a new video
 /// POST /videos
 pub async fn upload_video(
     State(state): State<AppState>,
+    user: AuthUser,
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<VideoResponse>)> {
+    user.require(Scope::VideosWrite)?;
+    user.require_role(Role::Uploader)?;
+
     let mut title: Option<String> = None;
     let mut description: Option<String> = None;
-    let mut file_data: Option<Vec<u8>> = None;
+    let mut visibility = Visibility::default();
+    let mut language: Option<String> = None;
+    let mut file: Option<(NamedTempFile, i64)> = None;
     let mut filename: Option<String> = None;
     let mut content_type: Option<String> = None;
 
+    // Files over the upload limit are cut off while they stream in
+    let upload_limit = state.quotas.upload_limit(user.tenant.id, Some(user.id)).await?;
+
     // Parse multipart form
     while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| AppError::BadRequest(format!("Failed to parse multipart: {}", e)))?
+        .map_err(|e| multipart_error("Failed to parse multipart", e))?
     {
         let name = field.name().unwrap_or("").to_string();
 
@@ -55,7 +201,7 @@ pub async fn upload_video(
                     field
                         .text()
                         .await
-                        .map_err(|e| AppError::BadRequest(format!("Failed to read title: {}", e)))?,
+                        .map_err(|e| multipart_error("Failed to read title", e))?,
                 );
             }
             "description" => {
@@ -63,54 +209,84 @@ pub async fn upload_video(
                     field
                         .text()
                         .await
-                        .map_err(|e| AppError::BadRequest(format!("Failed to read description: {}", e)))?,
+                        .map_err(|e| multipart_error("Failed to read description", e))?,
                 );
             }
-            "file" => {
-                filename = field.file_name().map(|s| s.to_string());
-                content_type = field.content_type().map(|s| s.to_string());
-                file_data = Some(
+            "visibility" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| multipart_error("Failed to read visibility", e))?;
+                visibility = value.trim().parse().map_err(AppError::BadRequest)?;
+            }
+            "language" => {
+                language = Some(
                     field
-                        .bytes()
+                        .text()
                         .await
-                        .map_err(|e| AppError::BadRequest(format!("Failed to read file: {}", e)))?
-                        .to_vec(),
+                        .map_err(|e| multipart_error("Failed to read language", e))?,
                 );
             }
+            "file" => {
+                filename = field.file_name().map(|s| s.to_string());
+                content_type = field.content_type().map(|s| s.to_string());
+                file = Some(receive_file(&state, field, upload_limit).await?);
+            }
             _ => {}
         }
     }
 
     // Validate required fields
-    let file_data = file_data.ok_or_else(|| AppError::BadRequest("No file uploaded".to_string()))?;
+    let (file, file_size) = file.ok_or_else(|| AppError::BadRequest("No file uploaded".to_string()))?;
     let filename = filename.ok_or_else(|| AppError::BadRequest("No filename provided".to_string()))?;
     let title = title.unwrap_or_else(|| filename.clone());
+    let language = match language {
+        Some(language) => text_search_config(&state, &language).await?,
+        None => state.search_language.clone(),
+    };
 
     // Validate file type
     let content_type = content_type.unwrap_or_else(|| "application/octet-stream".to_string());
     if !content_type.starts_with("video/") {
-        return Err(AppError::BadRequest("File must be a video".to_string()));
+        return Err(AppError::UnsupportedCodec(format!(
+            "File must be a video, not {}",
+            content_type
+        )));
     }
 
-    let file_size = file_data.len() as i64;
+    state.quotas.check_upload(user.tenant.id, user.id, file_size).await?;
+
     let video_id = Uuid::new_v4();
     let original_path = format!("{}/{}", video_id, filename);
 
-    // Upload original file to MinIO
-    state
-        .storage
-        .upload_original(&original_path, file_data.clone(), &content_type)
+    // Upload original file to the tenant's storage
+    let storage = state.storage.for_tenant(&user.tenant);
+    storage
+        .upload_original_from_file(&original_path, file.path(), &content_type)
         .await
         .map_err(|e| AppError::Storage(e.to_string()))?;
 
     // Insert video record into database
-    let video = sqlx::query_as::<_, Video>(
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, &user).await?;
+
+    // Uploads checked at the same time may together exceed the quota
+    if let Err(e) = state.quotas.lock_upload(&mut tx, user.tenant.id, user.id, file_size).await {
+        tx.rollback().await?;
+        if let Err(e) = storage.purge_video(video_id).await {
+            tracing::warn!("Failed to remove original of rejected upload {}: {}", video_id, e);
+        }
+        return Err(e.into());
+    }
+
+    let video = sqlx::query_as::<_, Video>(&format!(
         r#"
-        INSERT INTO videos (id, title, description, original_filename, original_size, mime_type, original_path, status)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, 'pending')
-        RETURNING *
+        INSERT INTO videos (id, tenant_id, title, description, original_filename, original_size, mime_type, original_path, visibility, owner_id, language, status)
+        VALUES ($1, $10, $2, $3, $4, $5, $6, $7, $8, $9, $11, 'pending')
+        RETURNING {}
         "#,
-    )
+        VIDEO_COLUMNS
+    ))
     .bind(video_id)
     .bind(&title)
     .bind(&description)
@@ -118,14 +294,23 @@ pub async fn upload_video(
     .bind(file_size)
     .bind(&content_type)
     .bind(&original_path)
-    .fetch_one(&state.db)
+    .bind(visibility)
+    .bind(user.id)
+    .bind(user.tenant.id)
+    .bind(&language)
+    .fetch_one(&mut *tx)
     .await?;
 
+    webhooks::emit(&mut tx, video_id, WebhookEvent::Uploaded).await?;
+    tx.commit().await?;
+
     // Queue transcoding job
     let job_message = TranscodeJobMessage {
         video_id,
+        tenant_id: user.tenant.id,
         original_path: original_path.clone(),
         resolutions: vec!["360p".to_string(), "720p".to_string()],
+        request_id: request_id::current(),
     };
 
     state
@@ -134,52 +319,154 @@ pub async fn upload_video(
         .await
         .map_err(|e| AppError::Internal(format!("Failed to queue transcoding job: {}", e)))?;
 
-    tracing::info!("Video uploaded: {} ({})", video_id, filename);
+    tracing::info!("Video uploaded: {} ({}) by {}", video_id, filename, user);
 
     Ok((StatusCode::CREATED, Json(video.to_response(&state.base_url))))
 }
 
-/// List all videos
+/// Write an uploaded file to a temporary file, refusing it once it exceeds
+/// `limit` bytes
+async fn receive_file(
+    state: &AppState,
+    mut field: Field<'_>,
+    limit: Option<i64>,
+) -> Result<(NamedTempFile, i64)> {
+    let temp = NamedTempFile::new_in(&state.temp_dir)
+        .map_err(|e| AppError::Internal(format!("Failed to create temporary file: {}", e)))?;
+    let mut file = tokio::fs::File::create(temp.path())
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to open temporary file: {}", e)))?;
+
+    let mut size: i64 = 0;
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| multipart_error("Failed to read file", e))?
+    {
+        size += chunk.len() as i64;
+        if let Some(limit) = limit.filter(|limit| size > *limit) {
+            return Err(AppError::PayloadTooLarge(format!(
+                "File exceeds the upload limit of {} bytes",
+                limit
+            )));
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write temporary file: {}", e)))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to write temporary file: {}", e)))?;
+
+    Ok((temp, size))
+}
+
+/// Error of a multipart upload; a body over the length limit is too large
+/// rather than malformed
+fn multipart_error(context: &str, e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        return AppError::PayloadTooLarge("Upload exceeds the request size limit".to_string());
+    }
+    AppError::BadRequest(format!("{}: {}", context, e))
+}
+
+/// List the videos visible to the caller
 /// GET /videos
 pub async fn list_videos(
     State(state): State<AppState>,
     Query(params): Query<ListVideosQuery>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
 ) -> Result<Json<VideoListResponse>> {
-    let limit = params.limit.unwrap_or(20).min(100);
-    let offset = params.offset.unwrap_or(0);
-
-    // Support optional status filter
-    let videos = if let Some(status_filter) = params.status {
-        sqlx::query_as::<_, Video>(
-            r#"
-            SELECT * FROM videos WHERE status = $1
-            ORDER BY created_at DESC
-            LIMIT $2 OFFSET $3
-            "#,
-        )
-        .bind(status_filter)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await?
-    } else {
-        sqlx::query_as::<_, Video>(
-            r#"
-            SELECT * FROM videos
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await?
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let (user_id, sees_all) = listing_params(user.as_ref())?;
+    let filter = params.filter()?;
+    let status = filter_status(&filter)?;
+
+    let cursor = params.cursor.as_deref().map(ListCursor::decode).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort != params.sort || cursor.order != params.order {
+            return Err(AppError::BadRequest("Cursor belongs to another sort order".to_string()));
+        }
+    }
+    let offset = match cursor {
+        Some(_) => 0,
+        None => params.offset.unwrap_or(0).max(0),
+    };
+    let metadata = filter.metadata.clone().map(serde_json::Value::Object);
+    let filters = filter_conditions();
+
+    // One extra row tells whether there is a next page
+    let (key, key_type) = params.sort.column();
+    let (direction, comparison) = match params.order {
+        SortOrder::Asc => ("ASC", ">"),
+        SortOrder::Desc => ("DESC", "<"),
     };
+    let mut videos = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        SELECT {VIDEO_COLUMNS} FROM videos
+        WHERE {filters}
+          AND ($16::text IS NULL OR ({key}, id) {comparison} ($16::{key_type}, $17::uuid))
+        ORDER BY {key} {direction}, id {direction}
+        LIMIT $18 OFFSET $19
+        "#
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant.id)
+    .bind(&status)
+    .bind(filter.owner_id)
+    .bind(filter.min_duration)
+    .bind(filter.max_duration)
+    .bind(filter.min_size)
+    .bind(filter.max_size)
+    .bind(filter.created_after)
+    .bind(filter.created_before)
+    .bind(&filter.resolution)
+    .bind(&filter.tag)
+    .bind(filter.category_id)
+    .bind(&metadata)
+    .bind(cursor.as_ref().map(|c| &c.key))
+    .bind(cursor.as_ref().map(|c| c.id))
+    .bind(limit + 1)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
 
-    let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM videos")
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM videos WHERE {filters}"))
+        .bind(user_id)
+        .bind(sees_all)
+        .bind(tenant.id)
+        .bind(&status)
+        .bind(filter.owner_id)
+        .bind(filter.min_duration)
+        .bind(filter.max_duration)
+        .bind(filter.min_size)
+        .bind(filter.max_size)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(&filter.resolution)
+        .bind(&filter.tag)
+        .bind(filter.category_id)
+        .bind(&metadata)
         .fetch_one(&state.db)
         .await?;
 
+    let next_cursor = if videos.len() as i64 > limit {
+        videos.truncate(limit as usize);
+        videos.last().map(|last| {
+            ListCursor {
+                sort: params.sort,
+                order: params.order,
+                key: params.sort.key(last),
+                id: last.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
     let video_responses: Vec<VideoResponse> = videos
         .into_iter()
         .map(|v| v.to_response(&state.base_url))
@@ -188,114 +475,533 @@ pub async fn list_videos(
     Ok(Json(VideoListResponse {
         videos: video_responses,
         total,
+        next_cursor,
     }))
 }
 
+impl ListVideosQuery {
+    /// Filters of the listing
+    fn filter(&self) -> Result<VideoFilter> {
+        let metadata = self
+            .metadata
+            .as_deref()
+            .map(serde_json::from_str::<serde_json::Map<String, serde_json::Value>>)
+            .transpose()
+            .map_err(|e| AppError::BadRequest(format!("Invalid metadata filter: {}", e)))?;
+
+        Ok(VideoFilter {
+            status: self.status.clone(),
+            owner_id: self.owner_id,
+            min_duration: self.min_duration,
+            max_duration: self.max_duration,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            resolution: self.resolution.clone(),
+            tag: self.tag.clone(),
+            category_id: self.category_id,
+            metadata,
+        })
+    }
+}
+
+/// Conditions selecting the videos of a [`VideoFilter`] listed to the
+/// caller. The caller is bound as in [`LISTED_TO_CALLER`] and the filter as
+/// `$4` to `$15`, in the order of its fields.
+fn filter_conditions() -> String {
+    format!(
+        r#"
+        deleted_at IS NULL AND {}
+          AND ($4::video_status IS NULL OR status = $4)
+          AND ($5::uuid IS NULL OR owner_id = $5)
+          AND ($6::float8 IS NULL OR duration_seconds >= $6)
+          AND ($7::float8 IS NULL OR duration_seconds <= $7)
+          AND ($8::bigint IS NULL OR original_size >= $8)
+          AND ($9::bigint IS NULL OR original_size <= $9)
+          AND ($10::timestamptz IS NULL OR created_at >= $10)
+          AND ($11::timestamptz IS NULL OR created_at < $11)
+          AND ($12::text IS NULL OR resolutions @> jsonb_build_array(jsonb_build_object('name', $12::text)))
+          AND ($13::text IS NULL OR EXISTS (
+              SELECT 1 FROM video_tags vt JOIN tags t ON t.id = vt.tag_id
+              WHERE vt.video_id = videos.id AND LOWER(t.name) = LOWER($13)
+          ))
+          AND ($14::uuid IS NULL OR category_id IN (
+              WITH RECURSIVE subtree AS (
+                  SELECT id FROM categories WHERE id = $14
+                  UNION ALL
+                  SELECT c.id FROM categories c JOIN subtree ON c.parent_id = subtree.id
+              )
+              SELECT id FROM subtree
+          ))
+          AND ($15::jsonb IS NULL OR custom_metadata @> $15)
+        "#,
+        LISTED_TO_CALLER
+    )
+}
+
+fn filter_status(filter: &VideoFilter) -> Result<Option<VideoStatus>> {
+    filter
+        .status
+        .as_deref()
+        .map(str::parse::<VideoStatus>)
+        .transpose()
+        .map_err(AppError::BadRequest)
+}
+
+/// IDs of the videos of a filter listed to the caller, oldest first; with
+/// `managed`, only those the caller may modify
+pub(crate) async fn filtered_video_ids(
+    state: &AppState,
+    user: &AuthUser,
+    filter: &VideoFilter,
+    managed: bool,
+    limit: i64,
+) -> Result<Vec<Uuid>> {
+    let (user_id, sees_all) = listing_params(Some(user))?;
+    select_video_ids(&state.db, user.tenant.id, user_id, sees_all, filter, managed, limit).await
+}
+
+/// IDs of the videos of a filter in a tenant, oldest first, as listed to
+/// the caller given by the bind parameters of [`LISTED_TO_CALLER`]
+pub(crate) async fn select_video_ids(
+    db: &PgPool,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    sees_all: bool,
+    filter: &VideoFilter,
+    managed: bool,
+    limit: i64,
+) -> Result<Vec<Uuid>> {
+    let status = filter_status(filter)?;
+
+    let ids = sqlx::query_scalar::<_, Uuid>(&format!(
+        r#"
+        SELECT id FROM videos
+        WHERE {}
+          AND (NOT $16 OR $2 OR owner_id = $1)
+        ORDER BY created_at, id
+        LIMIT $17
+        "#,
+        filter_conditions()
+    ))
+    .bind(user_id)
+    .bind(sees_all)
+    .bind(tenant_id)
+    .bind(&status)
+    .bind(filter.owner_id)
+    .bind(filter.min_duration)
+    .bind(filter.max_duration)
+    .bind(filter.min_size)
+    .bind(filter.max_size)
+    .bind(filter.created_after)
+    .bind(filter.created_before)
+    .bind(&filter.resolution)
+    .bind(&filter.tag)
+    .bind(filter.category_id)
+    .bind(filter.metadata.clone().map(serde_json::Value::Object))
+    .bind(managed)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(ids)
+}
+
 /// Get a single video by ID
 /// GET /videos/:id
 pub async fn get_video(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<VideoResponse>> {
-    let video = sqlx::query_as::<_, Video>(
-        r#"
-        SELECT * FROM videos WHERE id = $1
-        "#,
-    )
-    .bind(id)
-    .fetch_optional(&state.db)
-    .await?
-    .ok_or_else(|| AppError::NotFound(format!("Video {} not found", id)))?;
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
+) -> Result<(HeaderMap, Json<VideoResponse>)> {
+    let video = viewable_video(&state, &tenant, id, user.as_ref()).await?;
 
-    Ok(Json(video.to_response(&state.base_url)))
+    Ok((etag_header(&video), Json(video.to_response(&state.base_url))))
 }
 
 /// Update video metadata
 /// PUT /videos/:id
+///
+/// Omitted fields keep their value. With `If-Match`, the update is only
+/// applied while the video still has one of the given ETags.
 pub async fn update_video(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    user: AuthUser,
     Json(payload): Json<UpdateVideoRequest>,
-) -> Result<Json<VideoResponse>> {
-    // Check if video exists
-    let existing = sqlx::query_as::<_, Video>("SELECT * FROM videos WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Video {} not found", id)))?;
-
-    // Update fields (last write wins)
-    let new_title = payload.title.unwrap_or(existing.title);
-    let new_description = payload.description.or(existing.description);
-
-    let video = sqlx::query_as::<_, Video>(
+) -> Result<(HeaderMap, Json<VideoResponse>)> {
+    user.require(Scope::VideosWrite)?;
+
+    let existing = manageable_video(&state, id, &user).await?;
+    let conditional = check_if_match(&headers, &existing)?;
+    let version = existing.version;
+    let mut edit = VideoEdit::new(existing);
+
+    if let Some(title) = payload.title {
+        edit.title = title;
+    }
+    if let Some(description) = payload.description {
+        edit.description = Some(description);
+    }
+    if let Some(visibility) = payload.visibility {
+        edit.visibility = visibility;
+    }
+    if let Some(language) = &payload.language {
+        edit.language = text_search_config(&state, language).await?;
+    }
+    if let Some(transcript) = payload.transcript {
+        edit.transcript = Some(Some(transcript));
+    }
+    match payload.category_id {
+        Some(Some(category_id)) => {
+            check_category(&state, &user.tenant, category_id).await?;
+            edit.category_id = Some(category_id);
+        }
+        Some(None) => edit.category_id = None,
+        None => {}
+    }
+    if let Some(metadata) = payload.custom_metadata {
+        edit.custom_metadata = serde_json::Value::Object(metadata);
+    }
+    edit.tags = payload.tags;
+
+    let action = AuditAction::MetadataUpdated;
+    let video = save_video(&state, &user, id, version, conditional, action, edit).await?;
+
+    Ok((etag_header(&video), Json(video.to_response(&state.base_url))))
+}
+
+/// Update video metadata with a JSON Merge Patch (RFC 7386)
+/// PATCH /videos/:id
+///
+/// Unlike `PUT`, `null` clears a field: the description, transcript, tags
+/// or category, or a key of the custom metadata, whose objects are merged
+/// key by key; a `null` language reverts to the default. `If-Match` is
+/// honored as for `PUT`.
+pub async fn patch_video(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    user: AuthUser,
+    Json(patch): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Result<(HeaderMap, Json<VideoResponse>)> {
+    user.require(Scope::VideosWrite)?;
+
+    let existing = manageable_video(&state, id, &user).await?;
+    let conditional = check_if_match(&headers, &existing)?;
+    let version = existing.version;
+    let mut edit = VideoEdit::new(existing);
+
+    for (field, value) in patch {
+        match field.as_str() {
+            "title" => edit.title = patch_field(&field, value)?,
+            "description" => edit.description = patch_field(&field, value)?,
+            "visibility" => edit.visibility = patch_field(&field, value)?,
+            "language" => {
+                edit.language = match patch_field::<Option<String>>(&field, value)? {
+                    Some(language) => text_search_config(&state, &language).await?,
+                    None => state.search_language.clone(),
+                };
+            }
+            "transcript" => edit.transcript = Some(patch_field(&field, value)?),
+            "tags" => {
+                edit.tags = Some(patch_field::<Option<Vec<String>>>(&field, value)?.unwrap_or_default());
+            }
+            "category_id" => {
+                let category_id = patch_field::<Option<Uuid>>(&field, value)?;
+                if let Some(category_id) = category_id {
+                    check_category(&state, &user.tenant, category_id).await?;
+                }
+                edit.category_id = category_id;
+            }
+            "custom_metadata" => match value {
+                serde_json::Value::Null => edit.custom_metadata = serde_json::json!({}),
+                value @ serde_json::Value::Object(_) => merge_patch(&mut edit.custom_metadata, &value),
+                _ => {
+                    return Err(AppError::invalid(
+                        "/custom_metadata",
+                        "custom_metadata must be an object",
+                    ))
+                }
+            },
+            _ => {
+                return Err(AppError::BadRequest(format!(
+                    "Field {} cannot be patched",
+                    field
+                )))
+            }
+        }
+    }
+
+    let action = AuditAction::MetadataUpdated;
+    let video = save_video(&state, &user, id, version, conditional, action, edit).await?;
+
+    Ok((etag_header(&video), Json(video.to_response(&state.base_url))))
+}
+
+/// Metadata of a video as written back by an update
+pub(crate) struct VideoEdit {
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+    pub language: String,
+    /// `None` keeps the transcript, which is not loaded with the video
+    pub transcript: Option<Option<String>>,
+    pub category_id: Option<Uuid>,
+    pub custom_metadata: serde_json::Value,
+    /// `None` keeps the tags
+    pub tags: Option<Vec<String>>,
+    /// Tags before the update, recorded in the history when they change
+    previous_tags: Vec<String>,
+}
+
+impl VideoEdit {
+    pub(crate) fn new(video: Video) -> Self {
+        Self {
+            title: video.title,
+            description: video.description,
+            visibility: video.visibility,
+            language: video.language,
+            transcript: None,
+            category_id: video.category_id,
+            custom_metadata: video.custom_metadata,
+            tags: None,
+            previous_tags: video.tags,
+        }
+    }
+}
+
+/// Write the metadata of a video, provided it is still at `version`.
+///
+/// A video changed since it was read fails the update rather than being
+/// overwritten: with 412 when the caller sent `If-Match`, otherwise with a
+/// conflict the caller can retry. The change is recorded in the video's
+/// history as `action`.
+pub(crate) async fn save_video(
+    state: &AppState,
+    user: &AuthUser,
+    id: Uuid,
+    version: i64,
+    conditional: bool,
+    action: AuditAction,
+    edit: VideoEdit,
+) -> Result<Video> {
+    if edit.title.trim().is_empty() {
+        return Err(AppError::invalid("/title", "Title cannot be empty"));
+    }
+    if !edit.custom_metadata.is_object() {
+        return Err(AppError::invalid("/custom_metadata", "custom_metadata must be an object"));
+    }
+    validate_metadata(&user.tenant, &edit.custom_metadata)?;
+    let tags = edit.tags.as_deref().map(normalize_tags).transpose()?;
+
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, user).await?;
+
+    // Tags live outside the row, so the history trigger is told how they changed
+    let mut changes = serde_json::Map::new();
+    if let Some(tags) = &tags {
+        let stored = set_video_tags(&mut tx, user.tenant.id, id, tags).await?;
+        if stored != edit.previous_tags {
+            changes.insert(
+                "tags".to_string(),
+                serde_json::json!({ "before": edit.previous_tags, "after": stored }),
+            );
+        }
+    }
+    audit_details(&mut tx, action, &changes).await?;
+
+    let video = sqlx::query_as::<_, Video>(&format!(
         r#"
-        UPDATE videos 
-        SET title = $2, description = $3, updated_at = CURRENT_TIMESTAMP
-        WHERE id = $1
-        RETURNING *
+        UPDATE videos
+        SET title = $2, description = $3, visibility = $4, language = $5,
+            transcript = CASE WHEN $6 THEN $7 ELSE transcript END,
+            category_id = $8, custom_metadata = $9
+        WHERE id = $1 AND deleted_at IS NULL AND version = $10
+        RETURNING {}
         "#,
-    )
+        VIDEO_COLUMNS
+    ))
     .bind(id)
-    .bind(&new_title)
-    .bind(&new_description)
-    .fetch_one(&state.db)
+    .bind(&edit.title)
+    .bind(&edit.description)
+    .bind(edit.visibility)
+    .bind(&edit.language)
+    .bind(edit.transcript.is_some())
+    .bind(edit.transcript.flatten())
+    .bind(edit.category_id)
+    .bind(&edit.custom_metadata)
+    .bind(version)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    tracing::info!("Video updated: {}", id);
+    let Some(video) = video else {
+        return Err(if conditional {
+            AppError::PreconditionFailed(format!("Video {} has changed", id))
+        } else {
+            AppError::Conflict(format!("Video {} was changed concurrently, retry", id))
+        });
+    };
+
+    webhooks::emit(&mut tx, id, WebhookEvent::Updated).await?;
+    tx.commit().await?;
+
+    tracing::info!("Video updated: {} by {}", id, user);
+
+    Ok(video)
+}
+
+/// Check an `If-Match` header against the video's ETag, returning whether
+/// the request is conditional.
+///
+/// `If-Match` compares strongly, so weak tags never match.
+pub(crate) fn check_if_match(headers: &HeaderMap, video: &Video) -> Result<bool> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(false);
+    };
+    let candidates = value
+        .to_str()
+        .map_err(|_| AppError::BadRequest("Invalid If-Match header".to_string()))?;
+
+    let etag = video.etag();
+    if !candidates
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate == etag)
+    {
+        return Err(AppError::PreconditionFailed(format!(
+            "Video {} is at version {}",
+            video.id, etag
+        )));
+    }
+
+    Ok(true)
+}
+
+/// `ETag` header of a video's current version
+pub(crate) fn etag_header(video: &Video) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&video.etag()) {
+        headers.insert(header::ETAG, value);
+    }
+    headers
+}
 
-    Ok(Json(video.to_response(&state.base_url)))
+/// Deserialize the value of a patched field
+fn patch_field<T: serde::de::DeserializeOwned>(field: &str, value: serde_json::Value) -> Result<T> {
+    serde_json::from_value(value)
+        .map_err(|e| AppError::BadRequest(format!("Invalid {}: {}", field, e)))
 }
 
 /// Delete a video
 /// DELETE /videos/:id
+///
+/// Moves the video to the trash bin and out of every collection; stored
+/// objects are removed by the garbage collector once the retention period
+/// has passed.
 pub async fn delete_video(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    user: AuthUser,
 ) -> Result<StatusCode> {
-    // Check if video exists and get paths
-    let video = sqlx::query_as::<_, Video>("SELECT * FROM videos WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Video {} not found", id)))?;
-
-    // Delete from storage
-    if let Some(path) = &video.original_path {
-        state
-            .storage
-            .delete_original(path)
-            .await
-            .map_err(|e| AppError::Storage(format!("Failed to delete original: {}", e)))?;
-    }
+    user.require(Scope::VideosDelete)?;
+    trash_video(&state, &user, id).await?;
 
-    // Delete segments
-    for resolution in video.resolutions.0.iter() {
-        state
-            .storage
-            .delete_segments(&resolution.segment_path)
-            .await
-            .map_err(|e| AppError::Storage(format!("Failed to delete segments: {}", e)))?;
-    }
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Delete manifest
-    if let Some(path) = &video.manifest_path {
-        state
-            .storage
-            .delete_manifest(path)
-            .await
-            .map_err(|e| AppError::Storage(format!("Failed to delete manifest: {}", e)))?;
+/// Move a video to the trash bin and out of every collection
+pub(crate) async fn trash_video(state: &AppState, user: &AuthUser, id: Uuid) -> Result<()> {
+    manageable_video(state, id, user).await?;
+
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, user).await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE videos
+        SET deleted_at = CURRENT_TIMESTAMP, deleted_by = $2
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .bind(user.id.to_string())
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::VideoNotFound(id));
     }
 
-    // Delete from database
-    sqlx::query("DELETE FROM videos WHERE id = $1")
-        .bind(id)
-        .execute(&state.db)
-        .await?;
+    remove_from_collections(&mut tx, id).await?;
+    webhooks::emit(&mut tx, id, WebhookEvent::Deleted).await?;
+    tx.commit().await?;
 
-    tracing::info!("Video deleted: {}", id);
+    tracing::info!("Video deleted: {} by {}", id, user);
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
+}
+
+/// Transcode a video again from its original file
+/// POST /videos/:id/retranscode
+///
+/// Only videos that are ready or failed can be transcoded again; the video
+/// is not streamed until the new renditions are ready.
+pub async fn retranscode_video(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<(StatusCode, Json<VideoResponse>)> {
+    user.require(Scope::VideosWrite)?;
+    let video = retranscode(&state, &user, id).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(video.to_response(&state.base_url))))
+}
+
+/// Reset a ready or failed video to pending and queue its transcoding
+pub(crate) async fn retranscode(state: &AppState, user: &AuthUser, id: Uuid) -> Result<Video> {
+    let existing = manageable_video(state, id, user).await?;
+    let original_path = existing
+        .original_path
+        .ok_or_else(|| AppError::Conflict(format!("Video {} has no original file", id)))?;
+
+    let mut tx = state.db.begin().await?;
+    audit_actor(&mut tx, user).await?;
+
+    let video = sqlx::query_as::<_, Video>(&format!(
+        r#"
+        UPDATE videos SET status = 'pending', error_message = NULL
+        WHERE id = $1 AND deleted_at IS NULL AND status IN ('ready', 'failed')
+        RETURNING {}
+        "#,
+        VIDEO_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("Video {} is still being processed", id)))?;
+
+    // Queued before committing so that a video whose job could not be
+    // queued keeps its previous status
+    state
+        .transcoder
+        .queue_job(TranscodeJobMessage {
+            video_id: id,
+            tenant_id: user.tenant.id,
+            original_path,
+            resolutions: vec!["360p".to_string(), "720p".to_string()],
+            request_id: request_id::current(),
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to queue transcoding job: {}", e)))?;
+    tx.commit().await?;
+
+    tracing::info!("Video queued for transcoding again: {} by {}", id, user);
+
+    Ok(video)
 }
 
 /// Get video processing status
@@ -303,12 +1009,10 @@ pub async fn delete_video(
 pub async fn get_video_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    CurrentTenant(tenant): CurrentTenant,
+    Actor(user): Actor,
 ) -> Result<Json<serde_json::Value>> {
-    let video = sqlx::query_as::<_, Video>("SELECT * FROM videos WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Video {} not found", id)))?;
+    let video = viewable_video(&state, &tenant, id, user.as_ref()).await?;
 
     Ok(Json(serde_json::json!({
         "id": video.id,
@@ -317,3 +1021,44 @@ pub async fn get_video_status(
         "processed_at": video.processed_at,
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip() {
+        let cursor = ListCursor {
+            sort: VideoSort::Title,
+            order: SortOrder::Asc,
+            key: "Holiday, part 2 / \"final\"".to_string(),
+            id: Uuid::new_v4(),
+        };
+
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let decoded = ListCursor::decode(&encoded).unwrap();
+        assert_eq!(decoded.sort, cursor.sort);
+        assert_eq!(decoded.order, cursor.order);
+        assert_eq!(decoded.key, cursor.key);
+        assert_eq!(decoded.id, cursor.id);
+    }
+
+    #[test]
+    fn invalid_cursors_are_bad_requests() {
+        let encode = |json: &str| URL_SAFE_NO_PAD.encode(json);
+        for cursor in [
+            "not a cursor".to_string(),
+            encode("not json"),
+            encode(r#"{"sort":"title","order":"asc","key":"a"}"#),
+            encode(r#"{"sort":"views","order":"asc","key":"a","id":"00000000-0000-0000-0000-000000000000"}"#),
+        ] {
+            assert!(
+                matches!(ListCursor::decode(&cursor), Err(AppError::BadRequest(_))),
+                "{:?} is rejected",
+                cursor
+            );
+        }
+    }
+}
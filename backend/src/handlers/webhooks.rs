@@ -0,0 +1,329 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{extract::State, http::StatusCode};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::extract::{Json, Path, Query};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    CreateWebhookRequest, CreatedWebhookResponse, Role, Scope, UpdateWebhookRequest, Webhook,
+    WebhookDelivery, WebhookDeliveryListResponse, WebhookDeliveryStatus, WebhookEvent,
+    WebhookListResponse,
+};
+
+/// Most webhooks a tenant can have
+const MAX_WEBHOOKS: i64 = 20;
+
+/// Longest webhook URL
+const MAX_URL_LEN: usize = 2048;
+
+/// Shortest and longest secret a caller can choose
+const SECRET_LEN: std::ops::RangeInclusive<usize> = 16..=255;
+
+/// Prefix of generated webhook secrets
+const SECRET_PREFIX: &str = "whsec_";
+
+const WEBHOOK_COLUMNS: &str = "id, url, events, active, created_by, created_at, updated_at";
+
+/// Query parameters for the delivery log
+#[derive(Debug, Deserialize)]
+pub struct ListDeliveriesQuery {
+    pub status: Option<String>,
+    pub event: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Create a webhook; the secret is only returned by this call
+/// POST /webhooks
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreatedWebhookResponse>)> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let url = check_url(&payload.url)?;
+    let secret = match payload.secret {
+        Some(secret) if !SECRET_LEN.contains(&secret.len()) => {
+            return Err(AppError::BadRequest(format!(
+                "Secret must be {} to {} characters",
+                SECRET_LEN.start(),
+                SECRET_LEN.end()
+            )));
+        }
+        Some(secret) => secret,
+        None => {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            format!("{}{}", SECRET_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+        }
+    };
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM webhooks WHERE tenant_id = $1")
+        .bind(user.tenant.id)
+        .fetch_one(&state.db)
+        .await?;
+    if count >= MAX_WEBHOOKS {
+        return Err(AppError::Conflict(format!(
+            "A tenant can have at most {} webhooks",
+            MAX_WEBHOOKS
+        )));
+    }
+
+    let webhook = sqlx::query_as::<_, Webhook>(&format!(
+        r#"
+        INSERT INTO webhooks (tenant_id, url, secret, events, active, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        WEBHOOK_COLUMNS
+    ))
+    .bind(user.tenant.id)
+    .bind(&url)
+    .bind(&secret)
+    .bind(event_names(&payload.events))
+    .bind(payload.active)
+    .bind(user.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!("Webhook created: {} ({}) by {}", webhook.id, webhook.url, user);
+
+    Ok((StatusCode::CREATED, Json(CreatedWebhookResponse { webhook, secret })))
+}
+
+/// List the webhooks of the tenant
+/// GET /webhooks
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<WebhookListResponse>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let webhooks = sqlx::query_as::<_, Webhook>(&format!(
+        "SELECT {} FROM webhooks WHERE tenant_id = $1 ORDER BY created_at, id",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(user.tenant.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(WebhookListResponse { webhooks }))
+}
+
+/// Change the URL, events or state of a webhook
+/// PUT /webhooks/:id
+///
+/// Deliveries already queued keep their event and go to the new URL.
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Result<Json<Webhook>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let url = payload.url.as_deref().map(check_url).transpose()?;
+    let events = payload.events.as_deref().map(event_names);
+
+    let webhook = sqlx::query_as::<_, Webhook>(&format!(
+        r#"
+        UPDATE webhooks
+        SET url = COALESCE($3, url),
+            events = COALESCE($4, events),
+            active = COALESCE($5, active)
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING {}
+        "#,
+        WEBHOOK_COLUMNS
+    ))
+    .bind(id)
+    .bind(user.tenant.id)
+    .bind(url)
+    .bind(events)
+    .bind(payload.active)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Webhook {} not found", id)))?;
+
+    tracing::info!("Webhook updated: {} by {}", id, user);
+
+    Ok(Json(webhook))
+}
+
+/// Delete a webhook together with its delivery log
+/// DELETE /webhooks/:id
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> Result<StatusCode> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(user.tenant.id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    }
+
+    tracing::info!("Webhook deleted: {} by {}", id, user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the deliveries of a webhook, newest first
+/// GET /webhooks/:id/deliveries
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListDeliveriesQuery>,
+    user: AuthUser,
+) -> Result<Json<WebhookDeliveryListResponse>> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+    tenant_webhook(&state, &user, id).await?;
+
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<WebhookDeliveryStatus>)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let event = params
+        .event
+        .as_deref()
+        .map(str::parse::<WebhookEvent>)
+        .transpose()
+        .map_err(AppError::BadRequest)?
+        .map(|event| event.as_str());
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1
+          AND ($2::webhook_delivery_status IS NULL OR status = $2)
+          AND ($3::text IS NULL OR event = $3)
+        ORDER BY created_at DESC, id
+        LIMIT $4 OFFSET $5
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(event)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM webhook_deliveries
+        WHERE webhook_id = $1
+          AND ($2::webhook_delivery_status IS NULL OR status = $2)
+          AND ($3::text IS NULL OR event = $3)
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(event)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(WebhookDeliveryListResponse { deliveries, total }))
+}
+
+/// Send a delivery again, as a new delivery with the same event and payload
+/// POST /webhooks/:id/deliveries/:delivery_id/redeliver
+pub async fn redeliver_webhook_delivery(
+    State(state): State<AppState>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<(StatusCode, Json<WebhookDelivery>)> {
+    user.require(Scope::Admin)?;
+    user.require_role(Role::Admin)?;
+
+    let webhook = tenant_webhook(&state, &user, id).await?;
+    if !webhook.active {
+        return Err(AppError::Conflict(format!("Webhook {} is disabled", id)));
+    }
+
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, event_id, payload, redelivery_of)
+        SELECT webhook_id, event, event_id, payload, id FROM webhook_deliveries
+        WHERE id = $1 AND webhook_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Delivery {} not found", delivery_id)))?;
+
+    tracing::info!(
+        "Webhook delivery {} queued again as {} by {}",
+        delivery_id,
+        delivery.id,
+        user
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+/// Load a webhook of the caller's tenant
+async fn tenant_webhook(state: &AppState, user: &AuthUser, id: Uuid) -> Result<Webhook> {
+    sqlx::query_as::<_, Webhook>(&format!(
+        "SELECT {} FROM webhooks WHERE id = $1 AND tenant_id = $2",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(id)
+    .bind(user.tenant.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Webhook {} not found", id)))
+}
+
+/// Check that a webhook URL is an absolute http(s) URL
+fn check_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| AppError::BadRequest(format!("Invalid webhook URL: {}", e)))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(AppError::BadRequest(
+            "Webhook URL must be an http or https URL".to_string(),
+        ));
+    }
+    if url.len() > MAX_URL_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Webhook URL must be at most {} characters",
+            MAX_URL_LEN
+        )));
+    }
+    Ok(url.to_string())
+}
+
+/// Names of the subscribed events, without duplicates
+fn event_names(events: &[WebhookEvent]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for event in events {
+        if !names.iter().any(|name| name == event.as_str()) {
+            names.push(event.as_str().to_string());
+        }
+    }
+    names
+}
@@ -1,28 +1,56 @@
+mod cli;
 mod config;
 mod db;
 mod error;
 mod handlers;
 mod models;
+mod request_id;
 mod services;
 
 use axum::{
-    routing::{delete, get, post, put},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use axum::http::{header, HeaderValue};
+use clap::Parser;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::cli::Cli;
 use crate::config::Config;
 use crate::handlers::{
-    delete_video, get_video, get_video_status, list_videos, update_video, upload_video, AppState,
+    add_collection_video, add_group_member, cancel_bulk_operation, create_api_key,
+    create_bulk_operation, create_category, create_collection, create_group, create_webhook,
+    delete_category, delete_collection, delete_group, delete_video, delete_webhook,
+    get_bulk_operation, get_collection, get_content_key, get_current_user, get_license,
+    get_metadata_schema, get_playback, get_usage, get_user_usage, get_video, get_video_history,
+    get_video_import, get_video_status, import_video, ingest_notification, list_api_keys,
+    list_bulk_operation_items, list_bulk_operations, list_categories, list_collection_videos,
+    list_collections, list_group_members, list_groups, list_shares, list_tags, list_trash,
+    list_users, list_videos, list_webhook_deliveries, list_webhooks, login, logout, patch_video,
+    redeliver_webhook_delivery, refresh, register, remove_collection_video, remove_group_member,
+    reorder_collection_videos, reset_user_quota, restore_video, restore_video_revision,
+    retranscode_video, revoke_api_key, search_videos, serve_manifest,
+    serve_segment, share_video, unshare_video, update_collection,
+    update_metadata_schema, update_user_quota, update_user_role, update_video, update_webhook,
+    upload_video, watch_ingest_dir, AppState,
+};
+use crate::services::{
+    run_bulk_operations, ApiKeyService, AuthService, GarbageCollector, ImportService, IngestService, KeyService,
+    PlaybackTokens, QuotaService, StorageService, TenantService, TranscoderService,
+    WebhookDispatcher,
 };
-use crate::services::{StorageService, TranscoderService};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let cli = Cli::parse();
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::try_from_default_env()
@@ -34,58 +62,254 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     dotenvy::dotenv().ok();
     let config = Config::from_env()?;
 
+    // Run a maintenance command instead of the server
+    if let Some(command) = cli.command {
+        return cli::run(command, &config).await;
+    }
+
     tracing::info!("Starting Video Streaming Backend...");
     tracing::info!("Server: {}:{}", config.server_host, config.server_port);
 
-    // Create database connection pool
+    // Create database connection pool and apply pending migrations
     let db_pool = db::create_pool(&config.database_url).await?;
-    db::run_migrations(&db_pool).await?;
+    db::run_migrations(&db_pool).await.map_err(|e| {
+        tracing::error!("Database migrations failed: {}", e);
+        e
+    })?;
     tracing::info!("Database connected");
 
     // Create storage service
     let storage = Arc::new(StorageService::new(&config).await?);
-    tracing::info!("Storage service initialized");
+    tracing::info!("Storage service initialized ({:?} backend)", config.storage_backend);
+
+    // Create tenant service
+    let tenants = Arc::new(TenantService::new(&config, db_pool.clone()));
+    tracing::info!("Tenant service initialized (default tenant: {})", config.default_tenant);
+
+    // Create content key service
+    let keys = Arc::new(KeyService::new(&config, db_pool.clone())?);
+    tracing::info!("Key service initialized (HLS encryption: {:?})", config.hls_encryption);
+
+    // Create authentication service
+    let auth = Arc::new(AuthService::new(&config, db_pool.clone())?);
+    tracing::info!("Auth service initialized");
+
+    // Create API key service
+    let api_keys = Arc::new(ApiKeyService::new(&config, db_pool.clone())?);
+    tracing::info!("API key service initialized");
+
+    // Create quota service
+    let quotas = Arc::new(QuotaService::new(&config, db_pool.clone()));
+    tracing::info!("Quota service initialized");
 
     // Create transcoder service
     let transcoder = Arc::new(
-        TranscoderService::new(&config, storage.clone(), db_pool.clone()).await?,
+        TranscoderService::new(
+            &config,
+            storage.clone(),
+            tenants.clone(),
+            quotas.clone(),
+            keys.clone(),
+            db_pool.clone(),
+        )
+        .await?,
     );
     tracing::info!("Transcoder service initialized");
 
+    // Create ingest service; it creates the archive imports read from
+    let ingest = Arc::new(
+        IngestService::new(&config, storage.clone(), tenants.clone(), db_pool.clone()).await?,
+    );
+
+    // Create import service
+    let imports = Arc::new(
+        ImportService::new(
+            &config,
+            storage.clone(),
+            tenants.clone(),
+            quotas.clone(),
+            transcoder.clone(),
+            db_pool.clone(),
+        )
+        .await?,
+    );
+    tracing::info!("Import service initialized");
+
     // Start background transcoding worker
     let worker_transcoder = transcoder.clone();
     tokio::spawn(async move {
         worker_transcoder.start_worker().await;
     });
 
+    // Start background import workers
+    for _ in 0..config.import_workers {
+        let worker_imports = imports.clone();
+        tokio::spawn(async move {
+            worker_imports.start_worker().await;
+        });
+    }
+
+    // Start background garbage collector for deleted videos
+    let gc = Arc::new(GarbageCollector::new(
+        &config,
+        storage.clone(),
+        tenants.clone(),
+        db_pool.clone(),
+    ));
+    tokio::spawn(async move {
+        gc.start_worker().await;
+    });
+
+    // Start background delivery of webhook events
+    let webhooks = Arc::new(WebhookDispatcher::new(&config, db_pool.clone())?);
+    tokio::spawn(async move {
+        webhooks.start_worker().await;
+    });
+
     // Create application state
     let state = AppState {
         db: db_pool,
         storage,
         transcoder,
+        imports,
+        ingest,
+        playback: Arc::new(PlaybackTokens::new(&config)),
+        keys,
+        auth,
+        api_keys,
+        tenants,
+        quotas,
         base_url: String::new(), // Will be set by reverse proxy
+        trash_retention_days: config.trash_retention_days,
+        search_language: config.search_language.clone(),
+        trusted_proxies: Arc::new(config.trusted_proxies.clone()),
+        temp_dir: PathBuf::from(&config.temp_dir),
     };
 
-    // Configure CORS
+    // Start background ingest of the watched directory
+    if state.ingest.watches_dir() {
+        tokio::spawn(watch_ingest_dir(state.clone()));
+    }
+
+    // Start background bulk operation worker
+    tokio::spawn(run_bulk_operations(state.clone()));
+
+    // Configure CORS; any origin unless CORS_ALLOWED_ORIGINS is set
+    let allowed_origins = if config.cors_allowed_origins.is_empty() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .cors_allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
     let cors = CorsLayer::new()
-        .allow_origin(Any)
+        .allow_origin(allowed_origins)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        // Clients send the ETag back in If-Match
+        .expose_headers([header::ETAG, request_id::X_REQUEST_ID]);
 
     // Build router
     let app = Router::new()
+        // Authentication
+        .route("/auth/register", post(register))
+        .route("/auth/login", post(login))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(get_current_user))
+        // User administration
+        .route("/users", get(list_users))
+        .route("/users/:id/role", put(update_user_role))
+        // Quotas
+        .route("/usage", get(get_usage))
+        .route("/users/:id/usage", get(get_user_usage))
+        .route("/users/:id/quota", put(update_user_quota))
+        .route("/users/:id/quota", delete(reset_user_quota))
+        // Groups
+        .route("/groups", post(create_group))
+        .route("/groups", get(list_groups))
+        .route("/groups/:id", delete(delete_group))
+        .route("/groups/:id/members", get(list_group_members))
+        .route("/groups/:id/members", post(add_group_member))
+        .route("/groups/:id/members/:user_id", delete(remove_group_member))
+        // Tags, categories and custom metadata
+        .route("/tags", get(list_tags))
+        .route("/categories", get(list_categories))
+        .route("/categories", post(create_category))
+        .route("/categories/:id", delete(delete_category))
+        .route("/metadata-schema", get(get_metadata_schema))
+        .route("/metadata-schema", put(update_metadata_schema))
+        // Collections
+        .route("/collections", post(create_collection))
+        .route("/collections", get(list_collections))
+        .route("/collections/:id", get(get_collection))
+        .route("/collections/:id", put(update_collection))
+        .route("/collections/:id", delete(delete_collection))
+        .route("/collections/:id/videos", get(list_collection_videos))
+        .route("/collections/:id/videos", post(add_collection_video))
+        .route("/collections/:id/videos", put(reorder_collection_videos))
+        .route("/collections/:id/videos/:video_id", delete(remove_collection_video))
+        // API keys
+        .route("/api-keys", post(create_api_key))
+        .route("/api-keys", get(list_api_keys))
+        .route("/api-keys/:id", delete(revoke_api_key))
+        // Webhooks
+        .route("/webhooks", post(create_webhook))
+        .route("/webhooks", get(list_webhooks))
+        .route("/webhooks/:id", put(update_webhook))
+        .route("/webhooks/:id", delete(delete_webhook))
+        .route("/webhooks/:id/deliveries", get(list_webhook_deliveries))
+        .route(
+            "/webhooks/:id/deliveries/:delivery_id/redeliver",
+            post(redeliver_webhook_delivery),
+        )
+        // Ingest
+        .route("/ingest/notifications", post(ingest_notification))
+        // Bulk operations
+        .route("/bulk-operations", post(create_bulk_operation))
+        .route("/bulk-operations", get(list_bulk_operations))
+        .route("/bulk-operations/:id", get(get_bulk_operation))
+        .route("/bulk-operations/:id/items", get(list_bulk_operation_items))
+        .route("/bulk-operations/:id/cancel", post(cancel_bulk_operation))
         // Video routes
-        .route("/videos", post(upload_video))
+        // Uploads are held to the caller's quota while they stream in,
+        // instead of the default 2 MB body limit
+        .route("/videos", post(upload_video).layer(DefaultBodyLimit::disable()))
         .route("/videos", get(list_videos))
-        .route("/videos/{id}", get(get_video))
-        .route("/videos/{id}", put(update_video))
-        .route("/videos/{id}", delete(delete_video))
-        .route("/videos/{id}/status", get(get_video_status))
+        .route("/videos/search", get(search_videos))
+        .route("/videos/import", post(import_video))
+        .route("/videos/:id", get(get_video))
+        .route("/videos/:id", put(update_video))
+        .route("/videos/:id", patch(patch_video))
+        .route("/videos/:id", delete(delete_video))
+        .route("/videos/:id/status", get(get_video_status))
+        .route("/videos/:id/restore", post(restore_video))
+        .route("/videos/:id/retranscode", post(retranscode_video))
+        .route("/videos/:id/playback", get(get_playback))
+        .route("/videos/:id/license", post(get_license))
+        .route("/videos/:id/import", get(get_video_import))
+        .route("/videos/:id/history", get(get_video_history))
+        .route("/videos/:id/history/:entry_id/restore", post(restore_video_revision))
+        .route("/videos/:id/shares", get(list_shares))
+        .route("/videos/:id/shares", post(share_video))
+        .route("/videos/:id/shares/:share_id", delete(unshare_video))
+        // Trash bin
+        .route("/trash", get(list_trash))
+        // Streaming assets
+        .route("/manifests/*key", get(serve_manifest))
+        .route("/segments/*key", get(serve_segment))
+        .route("/keys/:video_id/:key_index", get(get_content_key))
+        // Presigned objects of backends without their own HTTP endpoint
         // Health check
         .route("/health", get(|| async { "OK" }))
         // Middleware
         .layer(cors)
         .layer(TraceLayer::new_for_http())
+        // Outermost, so the request ID is in the spans of the layers above
+        .layer(middleware::from_fn(request_id::propagate))
         .with_state(state);
 
     // Start server
@@ -95,7 +319,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     tracing::info!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Permission granted to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "videos:read")]
+    VideosRead,
+    #[serde(rename = "videos:write")]
+    VideosWrite,
+    #[serde(rename = "videos:delete")]
+    VideosDelete,
+    /// Implies every other scope and allows managing API keys
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::VideosRead => "videos:read",
+            Self::VideosWrite => "videos:write",
+            Self::VideosDelete => "videos:delete",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "videos:read" => Ok(Self::VideosRead),
+            "videos:write" => Ok(Self::VideosWrite),
+            "videos:delete" => Ok(Self::VideosDelete),
+            "admin" => Ok(Self::Admin),
+            _ => Err(format!("Unknown scope: {}", s)),
+        }
+    }
+}
+
+/// API key model from database
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: i32,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to create an API key
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Requests per minute; defaults to `API_KEY_DEFAULT_RATE_LIMIT`
+    pub rate_limit_per_minute: Option<i32>,
+    /// Days until the key expires; keys without expiry stay valid until revoked
+    pub expires_in_days: Option<i64>,
+}
+
+/// API key response for API; never includes the key itself
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the key, to recognise it
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: i32,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Newly created API key, the only response that carries the key
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    pub key: String,
+}
+
+/// API key list response
+#[derive(Debug, Serialize)]
+pub struct ApiKeyListResponse {
+    pub api_keys: Vec<ApiKeyResponse>,
+}
+
+impl ApiKey {
+    /// Convert to API response
+    pub fn to_response(&self) -> ApiKeyResponse {
+        ApiKeyResponse {
+            id: self.id,
+            name: self.name.clone(),
+            prefix: self.prefix.clone(),
+            scopes: self.scopes.clone(),
+            rate_limit_per_minute: self.rate_limit_per_minute,
+            last_used_at: self.last_used_at,
+            expires_at: self.expires_at,
+            revoked_at: self.revoked_at,
+            created_at: self.created_at,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::Visibility;
+
+/// Kind of change recorded in the audit log, matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "video_audit_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Created,
+    MetadataUpdated,
+    /// Metadata restored from an earlier entry
+    MetadataRestored,
+    StatusChanged,
+    /// Transcoding started again after it had finished
+    Retranscoded,
+    ThumbnailChanged,
+    /// Duration, renditions, encryption and other transcoding results
+    ProcessingUpdated,
+    Deleted,
+    Restored,
+    Purged,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::MetadataUpdated => "metadata_updated",
+            Self::MetadataRestored => "metadata_restored",
+            Self::StatusChanged => "status_changed",
+            Self::Retranscoded => "retranscoded",
+            Self::ThumbnailChanged => "thumbnail_changed",
+            Self::ProcessingUpdated => "processing_updated",
+            Self::Deleted => "deleted",
+            Self::Restored => "restored",
+            Self::Purged => "purged",
+        }
+    }
+}
+
+/// Entry of a video's history
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub video_id: Uuid,
+    pub action: AuditAction,
+    /// `None` for changes made by background workers
+    pub actor_id: Option<Uuid>,
+    pub actor: Option<String>,
+    /// Changed fields with their `before` and `after` values
+    pub changes: serde_json::Value,
+    /// Metadata after the change; only entries with it can be restored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Metadata of a video as recorded in [`AuditEntry::snapshot`]
+#[derive(Debug, Deserialize)]
+pub struct MetadataSnapshot {
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+    pub language: String,
+    pub transcript: Option<String>,
+    pub category_id: Option<Uuid>,
+    pub custom_metadata: serde_json::Value,
+    pub tags: Vec<String>,
+}
+
+/// Video history response, newest entries first
+#[derive(Debug, Serialize)]
+pub struct HistoryResponse {
+    pub entries: Vec<AuditEntry>,
+    pub total: i64,
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::models::{VideoFilter, Visibility};
+
+/// Bulk operation action matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "bulk_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAction {
+    /// Move to the trash bin
+    Delete,
+    /// Transcode again from the original
+    Retranscode,
+    SetVisibility,
+    AddTags,
+    RemoveTags,
+    /// Metadata of each video in its item's result
+    Export,
+}
+
+/// Bulk operation status matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "bulk_operation_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BulkOperationStatus {
+    /// Waiting for a worker
+    Pending,
+    Running,
+    /// Every item processed, successfully or not
+    Completed,
+    /// Stopped on request; remaining items stay pending
+    Cancelled,
+    /// Could not run at all
+    Failed,
+}
+
+/// Bulk operation item status matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "bulk_item_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BulkItemStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl FromStr for BulkItemStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "succeeded" => Ok(Self::Succeeded),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Unknown item status: {}", s)),
+        }
+    }
+}
+
+/// Bulk operation from database
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BulkOperation {
+    pub id: Uuid,
+    pub action: BulkAction,
+    /// Arguments of the action
+    pub params: serde_json::Value,
+    pub status: BulkOperationStatus,
+    pub total: i32,
+    pub succeeded: i32,
+    pub failed: i32,
+    pub error_message: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Video of a bulk operation, with the outcome of the action on it
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BulkOperationItem {
+    pub position: i32,
+    pub video_id: Uuid,
+    pub status: BulkItemStatus,
+    pub error: Option<String>,
+    /// Output of the action, e.g. the exported metadata
+    pub result: Option<serde_json::Value>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+/// Request to start a bulk operation
+#[derive(Debug, Deserialize)]
+pub struct CreateBulkOperationRequest {
+    pub action: BulkAction,
+    /// Videos to act on, in order; either this or `filter`
+    pub video_ids: Option<Vec<Uuid>>,
+    /// Videos to act on, selected as by the video listing
+    pub filter: Option<VideoFilter>,
+    /// Visibility set by `set_visibility`
+    pub visibility: Option<Visibility>,
+    /// Tags added by `add_tags` or removed by `remove_tags`
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Bulk operation list response
+#[derive(Debug, Serialize)]
+pub struct BulkOperationListResponse {
+    pub operations: Vec<BulkOperation>,
+    pub total: i64,
+}
+
+/// Items of a bulk operation
+#[derive(Debug, Serialize)]
+pub struct BulkOperationItemListResponse {
+    pub items: Vec<BulkOperationItem>,
+    pub total: i64,
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::video::double_option;
+use crate::models::Visibility;
+
+/// Collection kind matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "collection_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionKind {
+    /// Videos in any order
+    #[default]
+    Playlist,
+    /// Episodes in order
+    Series,
+}
+
+/// Collection from database, with its video count and cover thumbnail
+#[derive(Debug, Clone, FromRow)]
+pub struct Collection {
+    pub id: Uuid,
+    pub owner_id: Option<Uuid>,
+    pub kind: CollectionKind,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+    pub cover_video_id: Option<Uuid>,
+    /// Thumbnail of the cover video, or of the first public video
+    pub cover_path: Option<String>,
+    pub video_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Collection response for API
+#[derive(Debug, Serialize)]
+pub struct CollectionResponse {
+    pub id: Uuid,
+    pub owner_id: Option<Uuid>,
+    pub kind: CollectionKind,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+    pub cover_video_id: Option<Uuid>,
+    pub cover_url: Option<String>,
+    pub video_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Collection {
+    /// Convert to API response with the cover URL
+    pub fn to_response(&self, base_url: &str) -> CollectionResponse {
+        CollectionResponse {
+            id: self.id,
+            owner_id: self.owner_id,
+            kind: self.kind,
+            title: self.title.clone(),
+            description: self.description.clone(),
+            visibility: self.visibility,
+            cover_video_id: self.cover_video_id,
+            cover_url: self
+                .cover_path
+                .as_ref()
+                .map(|p| format!("{}/segments/{}", base_url, p)),
+            video_count: self.video_count,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Request to create a collection
+#[derive(Debug, Deserialize)]
+pub struct CreateCollectionRequest {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub kind: CollectionKind,
+    #[serde(default)]
+    pub visibility: Visibility,
+    pub cover_video_id: Option<Uuid>,
+}
+
+/// Request to update a collection
+#[derive(Debug, Deserialize)]
+pub struct UpdateCollectionRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub kind: Option<CollectionKind>,
+    pub visibility: Option<Visibility>,
+    /// `null` falls back to the thumbnail of the first video
+    #[serde(default, deserialize_with = "double_option")]
+    pub cover_video_id: Option<Option<Uuid>>,
+}
+
+/// Collection list response
+#[derive(Debug, Serialize)]
+pub struct CollectionListResponse {
+    pub collections: Vec<CollectionResponse>,
+    pub total: i64,
+}
+
+/// Request to add a video to a collection
+#[derive(Debug, Deserialize)]
+pub struct AddCollectionVideoRequest {
+    pub video_id: Uuid,
+    /// Zero-based position to insert at; appended when omitted
+    pub position: Option<i32>,
+}
+
+/// Request to reorder the videos of a collection
+#[derive(Debug, Deserialize)]
+pub struct ReorderCollectionRequest {
+    /// Every video of the collection, in the new order
+    pub video_ids: Vec<Uuid>,
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{VideoResponse, Visibility};
+
+/// Import job status matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "import_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    /// Waiting for a worker
+    Pending,
+    /// Being copied into storage
+    Downloading,
+    /// Stored and queued for transcoding
+    Completed,
+    Failed,
+}
+
+/// What created an import, matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "import_origin", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ImportOrigin {
+    /// `POST /videos/import`, with its source checked against the allowlists
+    Request,
+    /// The ingest, importing from its own archive
+    Ingest,
+}
+
+/// Import of a video from database
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct VideoImport {
+    pub id: Uuid,
+    pub video_id: Uuid,
+    pub source: String,
+    pub origin: ImportOrigin,
+    pub status: ImportStatus,
+    pub expected_size: Option<i64>,
+    pub expected_sha256: Option<String>,
+    /// Size announced by the source, if known
+    pub total_bytes: Option<i64>,
+    pub bytes_received: i64,
+    /// SHA-256 of the imported file, once complete
+    pub sha256: Option<String>,
+    pub error_message: Option<String>,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Request to import a video
+#[derive(Debug, Deserialize)]
+pub struct ImportVideoRequest {
+    /// http(s) URL, `s3://bucket/key` or absolute server-side path
+    pub source: String,
+    /// Size in bytes the file must have
+    pub size: Option<i64>,
+    /// Hex SHA-256 the file must have
+    pub sha256: Option<String>,
+    #[serde(flatten)]
+    pub metadata: ImportMetadata,
+}
+
+/// Metadata of an imported video, from the request or an ingest sidecar
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportMetadata {
+    /// Defaults to the file name of the source
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Text search configuration; `SEARCH_LANGUAGE` by default
+    pub language: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub category_id: Option<Uuid>,
+    /// Checked against the tenant's metadata schema
+    pub custom_metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Video created by an import, with the import's state
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub video: VideoResponse,
+    pub import: VideoImport,
+}
+
+/// S3 event notification sent by MinIO; only the fields the ingest reads
+#[derive(Debug, Deserialize)]
+pub struct BucketNotification {
+    #[serde(rename = "Records", default)]
+    pub records: Vec<BucketNotificationRecord>,
+}
+
+/// One event of a bucket notification
+#[derive(Debug, Deserialize)]
+pub struct BucketNotificationRecord {
+    /// e.g. `s3:ObjectCreated:Put`
+    #[serde(rename = "eventName", default)]
+    pub event_name: String,
+    pub s3: BucketNotificationEntity,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BucketNotificationEntity {
+    pub bucket: BucketNotificationBucket,
+    pub object: BucketNotificationObject,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BucketNotificationBucket {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BucketNotificationObject {
+    /// URL-encoded object key
+    pub key: String,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// W3C Clear Key license request, as generated by the browser's CDM
+#[derive(Debug, Deserialize)]
+pub struct ClearKeyLicenseRequest {
+    /// Requested key IDs, base64url-encoded without padding
+    pub kids: Vec<String>,
+    #[serde(rename = "type", default = "default_license_type")]
+    pub license_type: String,
+}
+
+fn default_license_type() -> String {
+    "temporary".to_string()
+}
+
+/// W3C Clear Key license: the requested keys as a JSON Web Key Set
+#[derive(Debug, Serialize)]
+pub struct ClearKeyLicenseResponse {
+    pub keys: Vec<JsonWebKey>,
+    #[serde(rename = "type")]
+    pub license_type: String,
+}
+
+/// Symmetric JSON Web Key carrying one content key
+#[derive(Debug, Serialize)]
+pub struct JsonWebKey {
+    pub kty: &'static str,
+    /// Key ID, base64url-encoded without padding
+    pub kid: String,
+    /// Content key, base64url-encoded without padding
+    pub k: String,
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Tag with the number of videos carrying it
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Tag {
+    pub name: String,
+    pub video_count: i64,
+}
+
+/// List of tags response
+#[derive(Debug, Serialize)]
+pub struct TagListResponse {
+    pub tags: Vec<Tag>,
+}
+
+/// Category from database; top-level categories have no parent
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Category {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to create a category
+#[derive(Debug, Deserialize)]
+pub struct CreateCategoryRequest {
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+}
+
+/// List of categories response, parents before their children
+#[derive(Debug, Serialize)]
+pub struct CategoryListResponse {
+    pub categories: Vec<Category>,
+}
+
+/// JSON schema of a tenant's custom metadata
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataSchema {
+    /// `None` allows any object
+    pub schema: Option<serde_json::Value>,
+}
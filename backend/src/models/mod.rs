@@ -1,3 +1,27 @@
+pub mod api_key;
+pub mod audit;
+pub mod bulk;
+pub mod collection;
+pub mod import;
+pub mod license;
+pub mod metadata;
+pub mod quota;
+pub mod sharing;
+pub mod tenant;
+pub mod user;
 pub mod video;
+pub mod webhook;
 
+pub use api_key::*;
+pub use audit::*;
+pub use bulk::*;
+pub use collection::*;
+pub use import::*;
+pub use license::*;
+pub use metadata::*;
+pub use quota::*;
+pub use sharing::*;
+pub use tenant::*;
+pub use user::*;
 pub use video::*;
+pub use webhook::*;
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Usage limits of a user or a tenant; `None` is unlimited
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+pub struct QuotaLimits {
+    /// Total size of uploaded originals
+    pub max_original_bytes: Option<i64>,
+    /// Total size of transcoded renditions
+    pub max_rendition_bytes: Option<i64>,
+    /// Number of videos, including those in the trash bin
+    pub max_videos: Option<i64>,
+    /// Size of a single upload
+    pub max_upload_bytes: Option<i64>,
+    /// Duration of a single video
+    pub max_duration_seconds: Option<f64>,
+}
+
+impl QuotaLimits {
+    /// Whether any limit is below zero, which no usage could satisfy
+    pub fn any_negative(&self) -> bool {
+        [
+            self.max_original_bytes,
+            self.max_rendition_bytes,
+            self.max_videos,
+            self.max_upload_bytes,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|limit| limit < 0)
+            || self.max_duration_seconds.is_some_and(|limit| limit < 0.0)
+    }
+}
+
+/// Storage consumed by a user or a tenant
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct Usage {
+    pub videos: i64,
+    pub original_bytes: i64,
+    pub rendition_bytes: i64,
+}
+
+/// Consumption against the limits that apply to it
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    pub usage: Usage,
+    pub limits: QuotaLimits,
+}
+
+/// Usage of a user and, for admins, of their whole tenant
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub user: UsageReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<UsageReport>,
+}
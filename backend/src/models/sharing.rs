@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Group of users from database, with its member count
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Group {
+    pub id: Uuid,
+    pub name: String,
+    pub owner_id: Uuid,
+    pub member_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to create a group
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+}
+
+/// Group list response
+#[derive(Debug, Serialize)]
+pub struct GroupListResponse {
+    pub groups: Vec<Group>,
+}
+
+/// Request to add a user to a group
+#[derive(Debug, Deserialize)]
+pub struct AddGroupMemberRequest {
+    pub username: String,
+}
+
+/// Member of a group
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GroupMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Group member list response
+#[derive(Debug, Serialize)]
+pub struct GroupMemberListResponse {
+    pub members: Vec<GroupMember>,
+}
+
+/// Request to share a video with either a user or a group
+#[derive(Debug, Deserialize)]
+pub struct CreateShareRequest {
+    pub username: Option<String>,
+    pub group_id: Option<Uuid>,
+}
+
+/// Grant of access to a video, with the name of its grantee
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct VideoShare {
+    pub id: Uuid,
+    pub video_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Video share list response
+#[derive(Debug, Serialize)]
+pub struct ShareListResponse {
+    pub shares: Vec<VideoShare>,
+}
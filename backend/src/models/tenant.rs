@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Tenant from database: an isolated catalog with its own accounts and storage
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    /// Host names resolving to the tenant for requests without a token
+    pub hostnames: Vec<String>,
+    pub storage_prefix: Option<String>,
+    pub videos_bucket: Option<String>,
+    pub segments_bucket: Option<String>,
+    pub manifests_bucket: Option<String>,
+    /// JSON schema custom metadata of the tenant's videos must match
+    pub metadata_schema: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tenant {
+    /// Prefix of the tenant's object keys; the slug unless configured,
+    /// and empty for objects at the bucket root
+    pub fn key_prefix(&self) -> &str {
+        self.storage_prefix.as_deref().unwrap_or(&self.slug)
+    }
+}
@@ -4,18 +4,54 @@ use sqlx::FromRow;
 use uuid::Uuid;
 
 /// Video status enum matching PostgreSQL enum
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "video_status", rename_all = "lowercase")]
 pub enum VideoStatus {
+    #[default]
     Pending,
     Processing,
     Ready,
     Failed,
 }
 
-impl Default for VideoStatus {
-    fn default() -> Self {
-        Self::Pending
+impl std::str::FromStr for VideoStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "processing" => Ok(Self::Processing),
+            "ready" => Ok(Self::Ready),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Unknown status: {}", s)),
+        }
+    }
+}
+
+/// Video visibility matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "video_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Listed and playable by anyone
+    #[default]
+    Public,
+    /// Playable by anyone with the link, but not listed
+    Unlisted,
+    /// Only the owner, editors and users it is shared with
+    Private,
+}
+
+impl std::str::FromStr for Visibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Self::Public),
+            "unlisted" => Ok(Self::Unlisted),
+            "private" => Ok(Self::Private),
+            _ => Err(format!("Unknown visibility: {}", s)),
+        }
     }
 }
 
@@ -39,15 +75,30 @@ pub struct Video {
     pub original_size: i64,
     pub mime_type: Option<String>,
     pub duration_seconds: Option<f64>,
+    /// Total size of the stored renditions, thumbnail and manifests
+    pub rendition_size: i64,
     pub original_path: Option<String>,
     pub manifest_path: Option<String>,
     pub thumbnail_path: Option<String>,
     pub status: VideoStatus,
     pub error_message: Option<String>,
     pub resolutions: sqlx::types::Json<Vec<Resolution>>,
+    pub visibility: Visibility,
+    pub encryption: Option<String>,
+    pub owner_id: Option<Uuid>,
+    /// Text search configuration of the title, description and transcript
+    pub language: String,
+    pub category_id: Option<Uuid>,
+    pub custom_metadata: serde_json::Value,
+    /// Names of the video's tags, selected along with the row
+    pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Bumped by the database on every change
+    pub version: i64,
     pub processed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<String>,
 }
 
 
@@ -56,6 +107,50 @@ pub struct Video {
 pub struct UpdateVideoRequest {
     pub title: Option<String>,
     pub description: Option<String>,
+    pub visibility: Option<Visibility>,
+    /// Searchable transcript of the video's speech
+    pub transcript: Option<String>,
+    pub language: Option<String>,
+    /// Replaces every tag of the video
+    pub tags: Option<Vec<String>>,
+    /// `null` removes the video from its category
+    #[serde(default, deserialize_with = "double_option")]
+    pub category_id: Option<Option<Uuid>>,
+    /// Replaces the custom metadata; must match the tenant's metadata schema
+    pub custom_metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Selection of videos, with the filters of the video listing
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VideoFilter {
+    pub status: Option<String>,
+    pub owner_id: Option<Uuid>,
+    /// Duration range, in seconds
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+    /// Original size range, in bytes
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    /// Upload date range; `created_before` is exclusive
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Name of a rendition the video must have, e.g. `720p`
+    pub resolution: Option<String>,
+    /// Tag the video must carry, ignoring case
+    pub tag: Option<String>,
+    /// Category of the video or any of its subcategories
+    pub category_id: Option<Uuid>,
+    /// Object the custom metadata must contain
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Tell a `null` field (`Some(None)`) apart from an absent one (`None`)
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 /// Video response for API
@@ -67,24 +162,48 @@ pub struct VideoResponse {
     pub original_filename: String,
     pub original_size: i64,
     pub duration_seconds: Option<f64>,
+    pub rendition_size: i64,
     pub status: VideoStatus,
     pub resolutions: Vec<Resolution>,
+    pub visibility: Visibility,
+    pub encryption: Option<String>,
+    pub owner_id: Option<Uuid>,
+    pub language: String,
+    pub tags: Vec<String>,
+    pub category_id: Option<Uuid>,
+    pub custom_metadata: serde_json::Value,
     pub streaming_url: Option<String>,
     pub thumbnail_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub version: i64,
 }
 
 impl Video {
+    /// Whether streaming this video requires a playback token
+    pub fn requires_token(&self) -> bool {
+        self.visibility == Visibility::Private || self.encryption.is_some()
+    }
+
+    /// Entity tag of the video's current version
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.version)
+    }
+
     /// Convert to API response with streaming URL
+    ///
+    /// Private and encrypted videos get no streaming URL, and private ones
+    /// no thumbnail URL; clients request tokenized ones from
+    /// `GET /videos/:id/playback`.
     pub fn to_response(&self, base_url: &str) -> VideoResponse {
-        let streaming_url = if self.status == VideoStatus::Ready {
+        let streaming_url = if self.status == VideoStatus::Ready && !self.requires_token() {
             self.manifest_path.as_ref().map(|p| format!("{}/manifests/{}", base_url, p))
         } else {
             None
         };
 
         let thumbnail_url = self.thumbnail_path.as_ref()
+            .filter(|_| self.visibility != Visibility::Private)
             .map(|p| format!("{}/segments/{}", base_url, p));
 
         VideoResponse {
@@ -94,28 +213,104 @@ impl Video {
             original_filename: self.original_filename.clone(),
             original_size: self.original_size,
             duration_seconds: self.duration_seconds,
+            rendition_size: self.rendition_size,
             status: self.status.clone(),
             resolutions: self.resolutions.0.clone(),
+            visibility: self.visibility,
+            encryption: self.encryption.clone(),
+            owner_id: self.owner_id,
+            language: self.language.clone(),
+            tags: self.tags.clone(),
+            category_id: self.category_id,
+            custom_metadata: self.custom_metadata.clone(),
             streaming_url,
             thumbnail_url,
             created_at: self.created_at,
             updated_at: self.updated_at,
+            version: self.version,
         }
     }
 }
 
+/// Tokenized playback URL for a video
+#[derive(Debug, Serialize)]
+pub struct PlaybackResponse {
+    pub video_id: Uuid,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub streaming_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    /// DASH manifest, only produced for CMAF common encryption
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dash_url: Option<String>,
+    /// Clear Key license server for common-encrypted videos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_url: Option<String>,
+}
+
 /// Video list response
 #[derive(Debug, Serialize)]
 pub struct VideoListResponse {
     pub videos: Vec<VideoResponse>,
+    /// Number of videos matching the filters, across all pages
+    pub total: i64,
+    /// Cursor of the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Search result with its rank and highlighted snippets
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub video: VideoResponse,
+    pub rank: f32,
+    pub highlights: SearchHighlights,
+}
+
+/// Matched terms of a search result wrapped in `<mark>` tags. The text is
+/// HTML-escaped, so the snippets are safe to insert as HTML.
+#[derive(Debug, Serialize)]
+pub struct SearchHighlights {
+    pub title: String,
+    pub description: Option<String>,
+    /// Only present when the transcript matched
+    pub transcript: Option<String>,
+}
+
+/// Paginated search results
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub videos: Vec<SearchHit>,
     pub total: i64,
 }
 
+/// Deleted video as shown in the trash bin
+#[derive(Debug, Serialize)]
+pub struct TrashedVideoResponse {
+    #[serde(flatten)]
+    pub video: VideoResponse,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<String>,
+    pub purge_at: DateTime<Utc>,
+}
+
+/// Trash bin list response
+#[derive(Debug, Serialize)]
+pub struct TrashListResponse {
+    pub videos: Vec<TrashedVideoResponse>,
+    pub total: i64,
+}
 
 /// Message for Redis job queue
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscodeJobMessage {
     pub video_id: Uuid,
+    pub tenant_id: Uuid,
     pub original_path: String,
     pub resolutions: Vec<String>,  // ["360p", "720p", "1080p"]
+    /// ID of the request that queued the job, to correlate its logs
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Video lifecycle event delivered to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "video.uploaded")]
+    Uploaded,
+    /// Metadata edited or restored from the history
+    #[serde(rename = "video.updated")]
+    Updated,
+    /// Transcoding started
+    #[serde(rename = "video.processing")]
+    Processing,
+    #[serde(rename = "video.ready")]
+    Ready,
+    #[serde(rename = "video.failed")]
+    Failed,
+    /// Moved to the trash bin
+    #[serde(rename = "video.deleted")]
+    Deleted,
+    /// Restored from the trash bin
+    #[serde(rename = "video.restored")]
+    Restored,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uploaded => "video.uploaded",
+            Self::Updated => "video.updated",
+            Self::Processing => "video.processing",
+            Self::Ready => "video.ready",
+            Self::Failed => "video.failed",
+            Self::Deleted => "video.deleted",
+            Self::Restored => "video.restored",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "video.uploaded" => Ok(Self::Uploaded),
+            "video.updated" => Ok(Self::Updated),
+            "video.processing" => Ok(Self::Processing),
+            "video.ready" => Ok(Self::Ready),
+            "video.failed" => Ok(Self::Failed),
+            "video.deleted" => Ok(Self::Deleted),
+            "video.restored" => Ok(Self::Restored),
+            _ => Err(format!("Unknown webhook event: {}", s)),
+        }
+    }
+}
+
+/// Webhook delivery status matching PostgreSQL enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    /// Acknowledged with a 2xx response
+    Delivered,
+    /// Out of attempts; can still be redelivered by hand
+    Failed,
+}
+
+impl FromStr for WebhookDeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "delivered" => Ok(Self::Delivered),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Unknown delivery status: {}", s)),
+        }
+    }
+}
+
+/// Webhook subscription from database; never includes the secret
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    /// Events delivered to the webhook; every event when empty
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create a webhook
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Signing secret; a random one is generated when omitted
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+/// Request to update a webhook; omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+}
+
+/// Newly created webhook, the only response that carries the secret
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Webhook list response
+#[derive(Debug, Serialize)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+/// Delivery of an event to a webhook, from the outbox
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub event_id: Uuid,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    /// When a pending delivery is attempted next
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    /// HTTP status of the last response, if one was received
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// Delivery this one was manually resent from
+    pub redelivery_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Delivery log response
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryListResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+    pub total: i64,
+}
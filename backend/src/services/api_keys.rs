@@ -0,0 +1,191 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use redis::Client as RedisClient;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{ApiKey, Role, Scope};
+use crate::services::auth::{digest, AuthError};
+
+/// Prefix telling API keys apart from JWT access tokens in the `Authorization` header
+pub const API_KEY_PREFIX: &str = "vsk_";
+
+/// Number of leading characters of a key kept in clear to recognise it
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// Caller authenticated with an API key
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    pub key_id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub username: String,
+    /// Current role of the key's owner
+    pub role: Role,
+    pub scopes: Vec<Scope>,
+}
+
+/// API keys for service-to-service access.
+///
+/// Keys are random 256-bit secrets shown once on creation and stored as
+/// SHA-256 digests. Each key has its own per-minute rate limit, counted in
+/// Redis so every backend instance shares the same budget.
+pub struct ApiKeyService {
+    db: PgPool,
+    redis_client: RedisClient,
+    default_rate_limit: i32,
+}
+
+impl ApiKeyService {
+    /// Create the API key service
+    pub fn new(config: &Config, db: PgPool) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            db,
+            redis_client: RedisClient::open(config.redis_url.as_str())?,
+            default_rate_limit: config.api_key_default_rate_limit,
+        })
+    }
+
+    /// Rate limit applied to keys created without an explicit one
+    pub fn default_rate_limit(&self) -> i32 {
+        self.default_rate_limit
+    }
+
+    /// Create a key for a user, returning it together with the key itself
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        scopes: &[Scope],
+        rate_limit_per_minute: i32,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(ApiKey, String), AuthError> {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        let key = format!("{}{}", API_KEY_PREFIX, URL_SAFE_NO_PAD.encode(secret));
+
+        let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, rate_limit_per_minute, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(&key[..DISPLAY_PREFIX_LEN])
+        .bind(digest(&key))
+        .bind(&scopes)
+        .bind(rate_limit_per_minute)
+        .bind(expires_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok((api_key, key))
+    }
+
+    /// List the keys of a user, including revoked ones
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<ApiKey>, AuthError> {
+        Ok(sqlx::query_as::<_, ApiKey>(
+            "SELECT * FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Revoke a key of a user, returning whether an active key was revoked
+    pub async fn revoke(&self, user_id: Uuid, id: Uuid) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Validate a key, enforce its rate limit and record its use
+    pub async fn authenticate(&self, key: &str) -> Result<ApiKeyPrincipal, AuthError> {
+        let row = sqlx::query_as::<_, (Uuid, Uuid, Uuid, String, Role, Vec<String>, i32)>(
+            r#"
+            SELECT k.id, k.user_id, u.tenant_id, u.username, u.role, k.scopes, k.rate_limit_per_minute
+            FROM api_keys k
+            JOIN users u ON u.id = k.user_id
+            WHERE k.key_hash = $1
+              AND k.revoked_at IS NULL
+              AND (k.expires_at IS NULL OR k.expires_at > CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(digest(key))
+        .fetch_optional(&self.db)
+        .await?;
+
+        let (key_id, user_id, tenant_id, username, role, scopes, rate_limit) = row.ok_or(AuthError::InvalidToken)?;
+
+        self.check_rate_limit(key_id, rate_limit).await?;
+
+        // Record use at most once a minute to keep writes off the hot path
+        sqlx::query(
+            r#"
+            UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+              AND (last_used_at IS NULL OR last_used_at < CURRENT_TIMESTAMP - INTERVAL '1 minute')
+            "#,
+        )
+        .bind(key_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(ApiKeyPrincipal {
+            key_id,
+            user_id,
+            tenant_id,
+            username,
+            role,
+            scopes: scopes.iter().filter_map(|scope| scope.parse().ok()).collect(),
+        })
+    }
+
+    /// Count a request against the key's fixed one-minute window.
+    ///
+    /// Requests are let through if Redis is unavailable, so an outage of the
+    /// queue does not lock out every ingestion pipeline.
+    async fn check_rate_limit(&self, key_id: Uuid, limit: i32) -> Result<(), AuthError> {
+        let now = Utc::now().timestamp();
+        let window_key = format!("ratelimit:api_key:{}:{}", key_id, now / 60);
+
+        let count = async {
+            let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+            let (count,): (i64,) = redis::pipe()
+                .atomic()
+                .incr(&window_key, 1)
+                .expire(&window_key, 60)
+                .ignore()
+                .query_async(&mut conn)
+                .await?;
+            Ok::<_, redis::RedisError>(count)
+        }
+        .await;
+
+        match count {
+            Ok(count) if count > i64::from(limit) => {
+                Err(AuthError::RateLimited((60 - now % 60) as u64))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Rate limiting unavailable for API key {}: {}", key_id, e);
+                Ok(())
+            }
+        }
+    }
+}
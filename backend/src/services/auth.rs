@@ -0,0 +1,352 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{Role, User};
+
+/// Errors returned by the authentication service
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("invalid username or password")]
+    InvalidCredentials,
+
+    #[error("username or email already registered")]
+    AlreadyRegistered,
+
+    #[error("invalid or expired token")]
+    InvalidToken,
+
+    #[error("rate limit exceeded; retry in {0} seconds")]
+    RateLimited(u64),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("password hashing failed: {0}")]
+    Hashing(String),
+
+    #[error("token signing failed: {0}")]
+    Signing(String),
+}
+
+/// Claims carried by a JWT access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessClaims {
+    /// User ID
+    pub sub: Uuid,
+    /// Tenant of the user
+    pub tid: Uuid,
+    /// Username at the time the token was issued
+    pub name: String,
+    /// Role at the time the token was issued; changes apply on the next refresh
+    pub role: Role,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Access and refresh token pair issued to a user
+#[derive(Debug, Clone)]
+pub struct IssuedTokens {
+    pub access_token: String,
+    pub access_ttl_secs: i64,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+}
+
+/// User accounts, password verification and token issuance.
+///
+/// Access tokens are short-lived HS256 JWTs checked without a database
+/// round trip. Refresh tokens are opaque random strings stored as SHA-256
+/// digests; each refresh rotates the token, and presenting a rotated token
+/// again revokes every session of the user.
+pub struct AuthService {
+    db: PgPool,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    access_ttl_secs: i64,
+    refresh_ttl_days: i64,
+    default_role: Role,
+    /// Hash verified for unknown users so login timing does not reveal them
+    dummy_hash: String,
+}
+
+impl AuthService {
+    /// Create the authentication service from the configured secret and lifetimes
+    pub fn new(config: &Config, db: PgPool) -> Result<Self, AuthError> {
+        Ok(Self {
+            db,
+            encoding_key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            access_ttl_secs: config.access_token_ttl_secs,
+            refresh_ttl_days: config.refresh_token_ttl_days,
+            default_role: config.default_user_role,
+            dummy_hash: hash_password("not a real password")?,
+        })
+    }
+
+    /// Create an account in a tenant with the default role
+    pub async fn register(
+        &self,
+        tenant_id: Uuid,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<User, AuthError> {
+        self.create_user(tenant_id, username, email, password, self.default_role)
+            .await
+    }
+
+    /// Create an account in a tenant with the given role
+    pub async fn create_user(
+        &self,
+        tenant_id: Uuid,
+        username: &str,
+        email: &str,
+        password: &str,
+        role: Role,
+    ) -> Result<User, AuthError> {
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|e| AuthError::Hashing(e.to_string()))??;
+
+        let result = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (tenant_id, username, email, password_hash, role)
+            VALUES ($5, $1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(username)
+        .bind(email)
+        .bind(&password_hash)
+        .bind(role)
+        .bind(tenant_id)
+        .fetch_one(&self.db)
+        .await;
+
+        match result {
+            Ok(user) => Ok(user),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                Err(AuthError::AlreadyRegistered)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check a username or email address and password within a tenant, and open a session
+    pub async fn login(
+        &self,
+        tenant_id: Uuid,
+        login: &str,
+        password: &str,
+    ) -> Result<(User, IssuedTokens), AuthError> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT * FROM users
+            WHERE tenant_id = $2 AND (LOWER(username) = LOWER($1) OR LOWER(email) = LOWER($1))
+            "#,
+        )
+        .bind(login)
+        .bind(tenant_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let hash = user
+            .as_ref()
+            .map(|user| user.password_hash.clone())
+            .unwrap_or_else(|| self.dummy_hash.clone());
+        let password = password.to_string();
+        let valid = tokio::task::spawn_blocking(move || verify_password(&password, &hash))
+            .await
+            .map_err(|e| AuthError::Hashing(e.to_string()))?;
+
+        let user = match user {
+            Some(user) if valid => user,
+            _ => return Err(AuthError::InvalidCredentials),
+        };
+
+        let tokens = self.issue(&user, None).await?;
+        Ok((user, tokens))
+    }
+
+    /// Exchange a refresh token for a new token pair, rotating the refresh token
+    pub async fn refresh(&self, refresh_token: &str) -> Result<(User, IssuedTokens), AuthError> {
+        let token_hash = digest(refresh_token);
+
+        let current = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+            RETURNING id, user_id
+            "#,
+        )
+        .bind(&token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some((token_id, user_id)) = current else {
+            self.detect_reuse(&token_hash).await?;
+            return Err(AuthError::InvalidToken);
+        };
+
+        let user = self
+            .get_user(user_id)
+            .await?
+            .ok_or(AuthError::InvalidToken)?;
+        let tokens = self.issue(&user, Some(token_id)).await?;
+        Ok((user, tokens))
+    }
+
+    /// Revoke a refresh token; unknown and already revoked tokens are ignored
+    pub async fn logout(&self, refresh_token: &str) -> Result<(), AuthError> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE token_hash = $1 AND revoked_at IS NULL",
+        )
+        .bind(digest(refresh_token))
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Validate a JWT access token
+    pub fn verify_access(&self, token: &str) -> Result<AccessClaims, AuthError> {
+        jsonwebtoken::decode::<AccessClaims>(token, &self.decoding_key, &Validation::new(Algorithm::HS256))
+            .map(|data| data.claims)
+            .map_err(|_| AuthError::InvalidToken)
+    }
+
+    /// Fetch a user by ID
+    pub async fn get_user(&self, id: Uuid) -> Result<Option<User>, AuthError> {
+        Ok(sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?)
+    }
+
+    /// List every account of a tenant
+    pub async fn list_users(&self, tenant_id: Uuid) -> Result<Vec<User>, AuthError> {
+        Ok(sqlx::query_as::<_, User>("SELECT * FROM users WHERE tenant_id = $1 ORDER BY created_at")
+            .bind(tenant_id)
+            .fetch_all(&self.db)
+            .await?)
+    }
+
+    /// Change the role of a user of a tenant
+    pub async fn set_role(&self, tenant_id: Uuid, id: Uuid, role: Role) -> Result<Option<User>, AuthError> {
+        Ok(sqlx::query_as::<_, User>(
+            "UPDATE users SET role = $3 WHERE id = $1 AND tenant_id = $2 RETURNING *",
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .bind(role)
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    /// Issue an access token and a new refresh token, recording which token it replaces
+    async fn issue(&self, user: &User, replaces: Option<Uuid>) -> Result<IssuedTokens, AuthError> {
+        let now = Utc::now();
+        let claims = AccessClaims {
+            sub: user.id,
+            tid: user.tenant_id,
+            name: user.username.clone(),
+            role: user.role,
+            iat: now.timestamp(),
+            exp: now.timestamp() + self.access_ttl_secs,
+        };
+        let access_token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::Signing(e.to_string()))?;
+
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        let refresh_token = URL_SAFE_NO_PAD.encode(secret);
+        let refresh_expires_at = now + chrono::Duration::days(self.refresh_ttl_days);
+
+        let mut tx = self.db.begin().await?;
+
+        let token_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO refresh_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(user.id)
+        .bind(digest(&refresh_token))
+        .bind(refresh_expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(previous) = replaces {
+            sqlx::query("UPDATE refresh_tokens SET replaced_by = $2 WHERE id = $1")
+                .bind(previous)
+                .bind(token_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(IssuedTokens {
+            access_token,
+            access_ttl_secs: self.access_ttl_secs,
+            refresh_token,
+            refresh_expires_at,
+        })
+    }
+
+    /// Revoke every session of a user whose rotated refresh token is presented again
+    async fn detect_reuse(&self, token_hash: &[u8]) -> Result<(), AuthError> {
+        let reused = sqlx::query_scalar::<_, Uuid>(
+            "SELECT user_id FROM refresh_tokens WHERE token_hash = $1 AND replaced_by IS NOT NULL",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        if let Some(user_id) = reused {
+            tracing::warn!("Rotated refresh token reused; revoking all sessions of user {}", user_id);
+
+            sqlx::query(
+                "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE user_id = $1 AND revoked_at IS NULL",
+            )
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn hash_password(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AuthError::Hashing(e.to_string()))
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// SHA-256 of a random secret, the form in which refresh tokens and API keys
+/// are stored; the secrets are long enough not to need a slow hash
+pub(crate) fn digest(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
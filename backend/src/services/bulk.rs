@@ -0,0 +1,358 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::access::{manageable_video, viewable_video};
+use crate::handlers::video::{retranscode, save_video, trash_video, VideoEdit};
+use crate::handlers::{AppState, AuthUser};
+use crate::models::{
+    AuditAction, BulkAction, BulkItemStatus, BulkOperationStatus, Role, Visibility,
+};
+use crate::services::jobs::{run_worker, POLL_INTERVAL};
+
+/// How long a claimed operation is hidden from other workers unless
+/// renewed; every processed video renews it
+const CLAIM_LEASE_SECS: f64 = 300.0;
+
+/// Pending items loaded at a time
+const BATCH_SIZE: i64 = 100;
+
+/// Arguments of an action, stored with the operation
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Operation claimed by a worker, with what it needs to act as its creator
+#[derive(FromRow)]
+struct ClaimedOperation {
+    id: Uuid,
+    tenant_id: Uuid,
+    created_by: Option<Uuid>,
+    api_key_id: Option<Uuid>,
+    scopes: Option<Vec<String>>,
+    action: BulkAction,
+    params: serde_json::Value,
+    attempts: i32,
+}
+
+/// Run bulk operations in the background, one at a time, until the
+/// process exits
+pub async fn run_bulk_operations(state: AppState) {
+    let state = &state;
+    run_worker("bulk operation", POLL_INTERVAL, move || async move {
+        let finished = process_next_operation(state).await?;
+        if let Some(id) = finished {
+            tracing::info!("Bulk operation finished: {}", id);
+        }
+        Ok::<_, AppError>(finished.is_some())
+    })
+    .await
+}
+
+/// Claim the oldest waiting operation and process its remaining videos.
+///
+/// An operation whose worker died is claimed again once its lease runs
+/// out and continues with the videos not processed yet.
+async fn process_next_operation(state: &AppState) -> Result<Option<Uuid>> {
+    let Some(operation) = claim(state).await? else {
+        return Ok(None);
+    };
+
+    let user = match operation_user(state, &operation).await {
+        Ok(user) => user,
+        Err(e) => {
+            finish(
+                state,
+                &operation,
+                BulkOperationStatus::Failed,
+                Some(&e.to_string()),
+            )
+            .await?;
+            return Ok(Some(operation.id));
+        }
+    };
+    let params: BulkParams = serde_json::from_value(operation.params.clone())
+        .map_err(|e| AppError::Internal(format!("Invalid bulk operation parameters: {}", e)))?;
+
+    loop {
+        let items = sqlx::query_as::<_, (i32, Uuid)>(
+            r#"
+            SELECT position, video_id FROM bulk_operation_items
+            WHERE operation_id = $1 AND status = 'pending'
+            ORDER BY position
+            LIMIT $2
+            "#,
+        )
+        .bind(operation.id)
+        .bind(BATCH_SIZE)
+        .fetch_all(&state.db)
+        .await?;
+
+        if items.is_empty() {
+            finish(state, &operation, BulkOperationStatus::Completed, None).await?;
+            return Ok(Some(operation.id));
+        }
+
+        for (position, video_id) in items {
+            let outcome = run_item(state, &user, operation.action, &params, video_id).await;
+            if !record(state, &operation, position, outcome).await? {
+                tracing::info!("Bulk operation stopped: {}", operation.id);
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Apply the action of an operation to one video, returning its output
+async fn run_item(
+    state: &AppState,
+    user: &AuthUser,
+    action: BulkAction,
+    params: &BulkParams,
+    id: Uuid,
+) -> Result<Option<serde_json::Value>> {
+    match action {
+        BulkAction::Delete => trash_video(state, user, id).await?,
+        BulkAction::Retranscode => {
+            retranscode(state, user, id).await?;
+        }
+        BulkAction::SetVisibility => {
+            edit_video(state, user, id, |edit, _| {
+                if let Some(visibility) = params.visibility {
+                    edit.visibility = visibility;
+                }
+            })
+            .await?;
+        }
+        BulkAction::AddTags => {
+            edit_video(state, user, id, |edit, tags| {
+                let mut tags = tags.to_vec();
+                for tag in &params.tags {
+                    if !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                        tags.push(tag.clone());
+                    }
+                }
+                edit.tags = Some(tags);
+            })
+            .await?;
+        }
+        BulkAction::RemoveTags => {
+            edit_video(state, user, id, |edit, tags| {
+                let removed = |tag: &String| {
+                    params
+                        .tags
+                        .iter()
+                        .any(|r| r.to_lowercase() == tag.to_lowercase())
+                };
+                edit.tags = Some(tags.iter().filter(|tag| !removed(tag)).cloned().collect());
+            })
+            .await?;
+        }
+        BulkAction::Export => {
+            let video = viewable_video(state, &user.tenant, id, Some(user)).await?;
+            let exported = serde_json::to_value(video.to_response(&state.base_url))
+                .map_err(|e| AppError::Internal(format!("Failed to export video {}: {}", id, e)))?;
+            return Ok(Some(exported));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Change the metadata of a video through the regular update, skipping
+/// videos the change would leave as they are
+async fn edit_video(
+    state: &AppState,
+    user: &AuthUser,
+    id: Uuid,
+    change: impl FnOnce(&mut VideoEdit, &[String]),
+) -> Result<()> {
+    let video = manageable_video(state, id, user).await?;
+    let version = video.version;
+    let (visibility, tags) = (video.visibility, video.tags.clone());
+
+    let mut edit = VideoEdit::new(video);
+    change(&mut edit, &tags);
+    if edit.visibility == visibility && edit.tags.as_ref().is_none_or(|new| *new == tags) {
+        return Ok(());
+    }
+
+    save_video(
+        state,
+        user,
+        id,
+        version,
+        false,
+        AuditAction::MetadataUpdated,
+        edit,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn claim(state: &AppState) -> Result<Option<ClaimedOperation>> {
+    let operation = sqlx::query_as::<_, ClaimedOperation>(
+        r#"
+        UPDATE bulk_operations o
+        SET status = 'running',
+            attempts = o.attempts + 1,
+            lease_until = CURRENT_TIMESTAMP + make_interval(secs => $1),
+            started_at = COALESCE(o.started_at, CURRENT_TIMESTAMP)
+        WHERE o.id = (
+            SELECT id FROM bulk_operations
+            WHERE status = 'pending'
+               OR (status = 'running' AND lease_until <= CURRENT_TIMESTAMP)
+            ORDER BY created_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING o.id, o.tenant_id, o.created_by, o.api_key_id, o.scopes, o.action, o.params, o.attempts
+        "#,
+    )
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_optional(&state.db)
+    .await?;
+
+    Ok(operation)
+}
+
+/// The creator of an operation as they are now, with the scopes of the
+/// API key the operation was created with
+async fn operation_user(state: &AppState, operation: &ClaimedOperation) -> Result<AuthUser> {
+    let gone = || AppError::Forbidden("The creator of the operation no longer exists".to_string());
+    let user_id = operation.created_by.ok_or_else(gone)?;
+    let tenant = state
+        .tenants
+        .get(operation.tenant_id)
+        .await?
+        .ok_or_else(gone)?;
+    let (username, role) = sqlx::query_as::<_, (String, Role)>(
+        "SELECT username, role FROM users WHERE id = $1 AND tenant_id = $2",
+    )
+    .bind(user_id)
+    .bind(operation.tenant_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(gone)?;
+
+    if let Some(key_id) = operation.api_key_id {
+        let active = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM api_keys
+                WHERE id = $1 AND revoked_at IS NULL
+                  AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+            )
+            "#,
+        )
+        .bind(key_id)
+        .fetch_one(&state.db)
+        .await?;
+        if !active {
+            return Err(AppError::Forbidden(format!(
+                "API key {} was revoked or has expired",
+                key_id
+            )));
+        }
+    }
+
+    Ok(AuthUser {
+        id: user_id,
+        tenant,
+        username,
+        role,
+        api_key_id: operation.api_key_id,
+        scopes: operation.scopes.as_ref().map(|scopes| {
+            scopes
+                .iter()
+                .filter_map(|scope| scope.parse().ok())
+                .collect()
+        }),
+    })
+}
+
+/// Record the outcome for a video and renew the lease. Returns false once
+/// the operation was cancelled or claimed by another worker.
+async fn record(
+    state: &AppState,
+    operation: &ClaimedOperation,
+    position: i32,
+    outcome: Result<Option<serde_json::Value>>,
+) -> Result<bool> {
+    let (status, error, result) = match outcome {
+        Ok(result) => (BulkItemStatus::Succeeded, None, result),
+        Err(e) => (BulkItemStatus::Failed, Some(e.to_string()), None),
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    let current = sqlx::query_scalar::<_, BulkOperationStatus>(
+        r#"
+        UPDATE bulk_operations
+        SET succeeded = succeeded + $3,
+            failed = failed + $4,
+            lease_until = CURRENT_TIMESTAMP + make_interval(secs => $5)
+        WHERE id = $1 AND attempts = $2
+        RETURNING status
+        "#,
+    )
+    .bind(operation.id)
+    .bind(operation.attempts)
+    .bind((status == BulkItemStatus::Succeeded) as i32)
+    .bind((status == BulkItemStatus::Failed) as i32)
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    // Another worker took over; it processes the video again
+    let Some(current) = current else {
+        return Ok(false);
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE bulk_operation_items
+        SET status = $3, error = $4, result = $5, processed_at = CURRENT_TIMESTAMP
+        WHERE operation_id = $1 AND position = $2
+        "#,
+    )
+    .bind(operation.id)
+    .bind(position)
+    .bind(status)
+    .bind(&error)
+    .bind(&result)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(current == BulkOperationStatus::Running)
+}
+
+async fn finish(
+    state: &AppState,
+    operation: &ClaimedOperation,
+    status: BulkOperationStatus,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE bulk_operations
+        SET status = $3, error_message = $4, lease_until = NULL, completed_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND attempts = $2 AND status = 'running'
+        "#,
+    )
+    .bind(operation.id)
+    .bind(operation.attempts)
+    .bind(status)
+    .bind(error)
+    .execute(&state.db)
+    .await?;
+
+    Ok(())
+}
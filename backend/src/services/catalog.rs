@@ -0,0 +1,716 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+use tokio::task::block_in_place;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{Resolution, Tenant, VideoStatus, Visibility};
+use crate::services::{KeyService, StorageService};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Name of the manifest inside an archive
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// `format` of the manifest of a catalog archive
+const FORMAT: &str = "videostream-catalog";
+
+/// Layout version of the archives written; later versions are rejected
+const FORMAT_VERSION: u32 = 1;
+
+/// Manifest of a catalog archive.
+///
+/// The archive is a tar file holding the stored objects of every video as
+/// `objects/{video_id}/{bucket}/{key}`, followed by this manifest as
+/// `manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogManifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Slug of the tenant the videos were exported from
+    pub tenant: String,
+    pub videos: Vec<CatalogVideo>,
+}
+
+/// Video of a catalog archive; what it refers to in its deployment is given
+/// by name rather than ID
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CatalogVideo {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub transcript: Option<String>,
+    pub language: String,
+    pub visibility: Visibility,
+    /// Names of the category and its parents, outermost first
+    pub category: Vec<String>,
+    pub tags: Vec<String>,
+    pub custom_metadata: serde_json::Value,
+    /// Username of the owner
+    pub owner: Option<String>,
+    pub original_filename: String,
+    pub original_size: i64,
+    pub mime_type: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub rendition_size: i64,
+    pub original_path: Option<String>,
+    pub manifest_path: Option<String>,
+    pub thumbnail_path: Option<String>,
+    pub status: VideoStatus,
+    pub error_message: Option<String>,
+    pub resolutions: sqlx::types::Json<Vec<Resolution>>,
+    pub encryption: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    #[sqlx(skip)]
+    pub content_keys: Vec<CatalogKey>,
+    #[sqlx(skip)]
+    pub objects: Vec<CatalogObject>,
+}
+
+/// Content key of an encrypted video, still sealed with the master key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogKey {
+    pub key_index: i32,
+    pub key_id: Uuid,
+    /// Sealed key, in base64
+    pub encrypted_key: String,
+    /// Nonce of the sealed key, in base64
+    pub nonce: String,
+}
+
+/// Stored object of a video
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogObject {
+    pub bucket: CatalogBucket,
+    /// Key relative to the video, e.g. `720p/playlist.m3u8`
+    pub key: String,
+    pub size: u64,
+    /// Hex SHA-256 of the object
+    pub sha256: String,
+}
+
+/// Bucket of an object, by role rather than by name, which differs
+/// between deployments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogBucket {
+    Videos,
+    Segments,
+    Manifests,
+}
+
+impl CatalogBucket {
+    const ALL: [Self; 3] = [Self::Videos, Self::Segments, Self::Manifests];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Videos => "videos",
+            Self::Segments => "segments",
+            Self::Manifests => "manifests",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|bucket| bucket.as_str() == name)
+    }
+}
+
+/// Outcome of importing an archive
+#[derive(Debug, Default)]
+pub struct CatalogImport {
+    /// Archived and new ID of every imported video; they differ when the
+    /// archived ID was taken
+    pub imported: Vec<(Uuid, Uuid)>,
+    /// Archived ID of every video that could not be imported, with the reason
+    pub failed: Vec<(Uuid, String)>,
+}
+
+/// Video of an archive being imported
+struct PendingVideo {
+    /// ID the video is imported as
+    id: Uuid,
+    /// Size and SHA-256 of the objects not received yet
+    objects: HashMap<(CatalogBucket, String), (u64, String)>,
+    error: Option<String>,
+}
+
+/// Export of videos, with their metadata and stored objects, to a portable
+/// archive, and import of such archives into another deployment
+pub struct CatalogService {
+    db: PgPool,
+    storage: StorageService,
+    keys: KeyService,
+    temp_dir: PathBuf,
+}
+
+impl CatalogService {
+    pub fn new(config: &Config, db: PgPool, storage: StorageService, keys: KeyService) -> Self {
+        Self {
+            db,
+            storage,
+            keys,
+            temp_dir: PathBuf::from(&config.temp_dir),
+        }
+    }
+
+    /// Write the ready and failed videos of a tenant to an archive, all of
+    /// them or those of `video_ids`, returning its manifest.
+    ///
+    /// The archive is written next to `path` and only moved there once
+    /// complete.
+    pub async fn export(
+        &self,
+        tenant: &Tenant,
+        video_ids: Option<&[Uuid]>,
+        path: &Path,
+    ) -> Result<CatalogManifest, BoxError> {
+        tokio::fs::create_dir_all(&self.temp_dir).await?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        match self.write_archive(tenant, video_ids, &partial).await {
+            Ok(manifest) => {
+                tokio::fs::rename(&partial, path).await?;
+                Ok(manifest)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_archive(
+        &self,
+        tenant: &Tenant,
+        video_ids: Option<&[Uuid]>,
+        path: &Path,
+    ) -> Result<CatalogManifest, BoxError> {
+        let storage = self.storage.for_tenant(tenant);
+        let mut videos = sqlx::query_as::<_, CatalogVideo>(
+            r#"
+            SELECT videos.id, title, description, transcript, language, visibility,
+                ARRAY(
+                    WITH RECURSIVE path AS (
+                        SELECT c.parent_id, c.name, 0 AS depth FROM categories c WHERE c.id = videos.category_id
+                        UNION ALL
+                        SELECT c.parent_id, c.name, path.depth + 1
+                        FROM categories c JOIN path ON c.id = path.parent_id
+                    )
+                    SELECT name::text FROM path ORDER BY depth DESC
+                ) AS category,
+                ARRAY(
+                    SELECT t.name::text FROM video_tags vt JOIN tags t ON t.id = vt.tag_id
+                    WHERE vt.video_id = videos.id ORDER BY LOWER(t.name)
+                ) AS tags,
+                custom_metadata, users.username AS owner, original_filename, original_size,
+                mime_type, duration_seconds, rendition_size, original_path, manifest_path,
+                thumbnail_path, status, error_message, resolutions, encryption,
+                videos.created_at, processed_at
+            FROM videos
+            LEFT JOIN users ON users.id = videos.owner_id
+            WHERE videos.tenant_id = $1 AND videos.deleted_at IS NULL
+              AND videos.status IN ('ready', 'failed')
+              AND ($2::uuid[] IS NULL OR videos.id = ANY($2))
+            ORDER BY videos.created_at, videos.id
+            "#,
+        )
+        .bind(tenant.id)
+        .bind(video_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        if let Some(video_ids) = video_ids {
+            for id in video_ids {
+                if !videos.iter().any(|video| video.id == *id) {
+                    tracing::warn!("Skipping video {}: not found, deleted or still processing", id);
+                }
+            }
+        }
+
+        let file = block_in_place(|| File::create(path))?;
+        let mut archive = tar::Builder::new(file);
+        archive.mode(tar::HeaderMode::Deterministic);
+
+        for video in &mut videos {
+            video.content_keys = sqlx::query_as::<_, (i32, Uuid, Vec<u8>, Vec<u8>)>(
+                "SELECT key_index, key_id, encrypted_key, nonce FROM content_keys WHERE video_id = $1 ORDER BY key_index",
+            )
+            .bind(video.id)
+            .fetch_all(&self.db)
+            .await?
+            .into_iter()
+            .map(|(key_index, key_id, encrypted_key, nonce)| CatalogKey {
+                key_index,
+                key_id,
+                encrypted_key: BASE64.encode(encrypted_key),
+                nonce: BASE64.encode(nonce),
+            })
+            .collect();
+
+            let prefix = format!("{}/", video.id);
+            for bucket in CatalogBucket::ALL {
+                let bucket_name = self.bucket_name(&storage, bucket);
+                for object in storage.list_objects(bucket_name, &prefix).await? {
+                    let Some(key) = object.key.strip_prefix(&prefix) else {
+                        continue;
+                    };
+
+                    let temp = NamedTempFile::new_in(&self.temp_dir)?;
+                    let mut file = tokio::fs::File::from_std(temp.reopen()?);
+                    let mut stream = storage.stream_object(bucket_name, &object.key, None).await?;
+                    let mut hasher = Sha256::new();
+                    let mut size = 0u64;
+                    while let Some(chunk) = stream.body.next().await {
+                        let chunk = chunk?;
+                        hasher.update(&chunk);
+                        size += chunk.len() as u64;
+                        file.write_all(&chunk).await?;
+                    }
+                    file.flush().await?;
+
+                    let name = format!("objects/{}/{}/{}", video.id, bucket.as_str(), key);
+                    block_in_place(|| archive.append_path_with_name(temp.path(), &name))?;
+                    video.objects.push(CatalogObject {
+                        bucket,
+                        key: key.to_string(),
+                        size,
+                        sha256: hex::encode(hasher.finalize()),
+                    });
+                }
+            }
+
+            tracing::info!("Exported video {} ({} objects)", video.id, video.objects.len());
+        }
+
+        let manifest = CatalogManifest {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            exported_at: Utc::now(),
+            tenant: tenant.slug.clone(),
+            videos,
+        };
+        let data = serde_json::to_vec_pretty(&manifest)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.exported_at.timestamp() as u64);
+        block_in_place(|| -> std::io::Result<()> {
+            archive.append_data(&mut header, MANIFEST_NAME, data.as_slice())?;
+            archive.into_inner()?.sync_all()
+        })?;
+
+        Ok(manifest)
+    }
+
+    /// Import the videos of an archive into a tenant.
+    ///
+    /// Videos keep their ID unless it is taken, in which case they get a new
+    /// one and their playlists are rewritten to it. Owners are matched by
+    /// username, falling back to `default_owner`; categories and tags are
+    /// created as needed. A video is only created once every one of its
+    /// objects was stored with the size and checksum of the manifest; the
+    /// objects of videos that fail are removed again.
+    pub async fn import(
+        &self,
+        tenant: &Tenant,
+        default_owner: Option<Uuid>,
+        path: &Path,
+    ) -> Result<CatalogImport, BoxError> {
+        tokio::fs::create_dir_all(&self.temp_dir).await?;
+        let storage = self.storage.for_tenant(tenant);
+        let manifest = block_in_place(|| read_manifest(path))?;
+
+        let mut pending: HashMap<Uuid, PendingVideo> = HashMap::new();
+        for video in &manifest.videos {
+            let taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM videos WHERE id = $1)")
+                .bind(video.id)
+                .fetch_one(&self.db)
+                .await?;
+            let objects = video
+                .objects
+                .iter()
+                .map(|object| ((object.bucket, object.key.clone()), (object.size, object.sha256.clone())))
+                .collect();
+            pending.insert(
+                video.id,
+                PendingVideo {
+                    id: if taken { Uuid::new_v4() } else { video.id },
+                    objects,
+                    error: None,
+                },
+            );
+        }
+
+        let mut archive = tar::Archive::new(block_in_place(|| File::open(path))?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            if name == MANIFEST_NAME {
+                continue;
+            }
+            let Some((archived_id, bucket, key)) = parse_object_name(&name) else {
+                tracing::warn!("Skipping unexpected archive entry {}", name);
+                continue;
+            };
+            let Some(video) = pending.get_mut(&archived_id) else {
+                tracing::warn!("Skipping archive entry {} of no video in the manifest", name);
+                continue;
+            };
+            if video.error.is_some() {
+                continue;
+            }
+            let Some((size, sha256)) = video.objects.remove(&(bucket, key.to_string())) else {
+                tracing::warn!("Skipping archive entry {} missing from the manifest", name);
+                continue;
+            };
+
+            let temp = NamedTempFile::new_in(&self.temp_dir)?;
+            let (received_size, received_sha256) = block_in_place(|| copy_hashed(&mut entry, temp.as_file()))?;
+            if received_size != size || received_sha256 != sha256 {
+                video.error = Some(format!("Checksum mismatch for {}/{}", bucket.as_str(), key));
+                continue;
+            }
+            if video.id != archived_id && bucket == CatalogBucket::Manifests {
+                block_in_place(|| rewrite_video_id(temp.path(), archived_id, video.id))?;
+            }
+
+            let content_type = mime_guess::from_path(key).first_or_octet_stream();
+            if let Err(e) = storage
+                .upload_object_from_file(
+                    self.bucket_name(&storage, bucket),
+                    &format!("{}/{}", video.id, key),
+                    temp.path(),
+                    content_type.as_ref(),
+                )
+                .await
+            {
+                video.error = Some(format!("Failed to store {}/{}: {}", bucket.as_str(), key, e));
+            }
+        }
+
+        let mut owners: HashMap<String, Option<Uuid>> = HashMap::new();
+        let mut outcome = CatalogImport::default();
+        for archived in &manifest.videos {
+            let Some(video) = pending.remove(&archived.id) else {
+                continue;
+            };
+            let error = match (video.error, video.objects.keys().next()) {
+                (Some(error), _) => Some(error),
+                (None, Some((bucket, key))) => {
+                    Some(format!("{}/{} is missing from the archive", bucket.as_str(), key))
+                }
+                (None, None) => {
+                    let owner_id = match &archived.owner {
+                        Some(username) => match owners.get(username) {
+                            Some(owner_id) => *owner_id,
+                            None => {
+                                let owner_id = sqlx::query_scalar::<_, Uuid>(
+                                    "SELECT id FROM users WHERE tenant_id = $1 AND username = $2",
+                                )
+                                .bind(tenant.id)
+                                .bind(username)
+                                .fetch_optional(&self.db)
+                                .await?;
+                                owners.insert(username.clone(), owner_id);
+                                owner_id
+                            }
+                        },
+                        None => None,
+                    };
+
+                    self.insert_video(tenant, archived, video.id, owner_id.or(default_owner))
+                        .await
+                        .err()
+                        .map(|e| e.to_string())
+                }
+            };
+
+            match error {
+                Some(error) => {
+                    storage.purge_video(video.id).await?;
+                    tracing::warn!("Failed to import video {}: {}", archived.id, error);
+                    outcome.failed.push((archived.id, error));
+                }
+                None => {
+                    tracing::info!("Imported video {} as {}", archived.id, video.id);
+                    outcome.imported.push((archived.id, video.id));
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    async fn insert_video(
+        &self,
+        tenant: &Tenant,
+        video: &CatalogVideo,
+        id: Uuid,
+        owner_id: Option<Uuid>,
+    ) -> Result<(), BoxError> {
+        let mut content_keys = Vec::with_capacity(video.content_keys.len());
+        for key in &video.content_keys {
+            let encrypted_key = BASE64.decode(&key.encrypted_key)?;
+            let nonce = BASE64.decode(&key.nonce)?;
+            self.keys.check_sealed(&encrypted_key, &nonce)?;
+            content_keys.push((key.key_index, key.key_id, encrypted_key, nonce));
+        }
+
+        // Paths are keyed by video ID
+        let (archived_id, new_id) = (video.id.to_string(), id.to_string());
+        let rekey = |path: &Option<String>| path.as_ref().map(|path| path.replace(&archived_id, &new_id));
+        let mut resolutions = video.resolutions.0.clone();
+        for resolution in &mut resolutions {
+            resolution.segment_path = resolution.segment_path.replace(&archived_id, &new_id);
+        }
+
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SELECT set_config('videostream.actor', 'catalog import', true)")
+            .execute(&mut *tx)
+            .await?;
+
+        let category_id = category_by_path(&mut tx, tenant.id, &video.category).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO videos (
+                id, tenant_id, title, description, transcript, language, visibility, category_id,
+                custom_metadata, owner_id, original_filename, original_size, mime_type,
+                duration_seconds, rendition_size, original_path, manifest_path, thumbnail_path,
+                status, error_message, resolutions, encryption, created_at, processed_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                    $19, $20, $21, $22, $23, $24)
+            "#,
+        )
+        .bind(id)
+        .bind(tenant.id)
+        .bind(&video.title)
+        .bind(&video.description)
+        .bind(&video.transcript)
+        .bind(&video.language)
+        .bind(video.visibility)
+        .bind(category_id)
+        .bind(&video.custom_metadata)
+        .bind(owner_id)
+        .bind(&video.original_filename)
+        .bind(video.original_size)
+        .bind(&video.mime_type)
+        .bind(video.duration_seconds)
+        .bind(video.rendition_size)
+        .bind(rekey(&video.original_path))
+        .bind(rekey(&video.manifest_path))
+        .bind(rekey(&video.thumbnail_path))
+        .bind(&video.status)
+        .bind(&video.error_message)
+        .bind(sqlx::types::Json(&resolutions))
+        .bind(&video.encryption)
+        .bind(video.created_at)
+        .bind(video.processed_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tags (tenant_id, name)
+            SELECT $1, tag FROM unnest($2::text[]) AS t(tag)
+            ON CONFLICT (tenant_id, LOWER(name)) DO NOTHING
+            "#,
+        )
+        .bind(tenant.id)
+        .bind(&video.tags)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO video_tags (video_id, tag_id)
+            SELECT $1, id FROM tags
+            WHERE tenant_id = $2 AND LOWER(name) IN (SELECT LOWER(tag) FROM unnest($3::text[]) AS t(tag))
+            "#,
+        )
+        .bind(id)
+        .bind(tenant.id)
+        .bind(&video.tags)
+        .execute(&mut *tx)
+        .await?;
+
+        // Key IDs are signalled in the copied segments, so they are kept; they
+        // only need to be unique within the video
+        for (key_index, key_id, encrypted_key, nonce) in content_keys {
+            sqlx::query(
+                r#"
+                INSERT INTO content_keys (video_id, key_index, key_id, encrypted_key, nonce)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(id)
+            .bind(key_index)
+            .bind(key_id)
+            .bind(encrypted_key)
+            .bind(nonce)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to store content key {}: {}", key_id, e))?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    fn bucket_name<'a>(&self, storage: &'a StorageService, bucket: CatalogBucket) -> &'a str {
+        match bucket {
+            CatalogBucket::Videos => storage.videos_bucket(),
+            CatalogBucket::Segments => storage.segments_bucket(),
+            CatalogBucket::Manifests => storage.manifests_bucket(),
+        }
+    }
+}
+
+/// Read the manifest of an archive, skipping over the objects before it
+fn read_manifest(path: &Path) -> Result<CatalogManifest, BoxError> {
+    let mut archive = tar::Archive::new(File::open(path)?);
+    for entry in archive.entries_with_seek()? {
+        let entry = entry?;
+        if entry.path()?.as_os_str() != MANIFEST_NAME {
+            continue;
+        }
+
+        let manifest: CatalogManifest = serde_json::from_reader(entry)
+            .map_err(|e| format!("Invalid {}: {}", MANIFEST_NAME, e))?;
+        if manifest.format != FORMAT {
+            return Err(format!("{} is not a catalog archive", path.display()).into());
+        }
+        if manifest.version > FORMAT_VERSION {
+            return Err(format!(
+                "{} has layout version {}; this version reads up to {}",
+                path.display(),
+                manifest.version,
+                FORMAT_VERSION
+            )
+            .into());
+        }
+        return Ok(manifest);
+    }
+
+    Err(format!("{} has no {}", path.display(), MANIFEST_NAME).into())
+}
+
+/// Video ID, bucket and key of an `objects/{video_id}/{bucket}/{key}` entry
+fn parse_object_name(name: &str) -> Option<(Uuid, CatalogBucket, &str)> {
+    let rest = name.strip_prefix("objects/")?;
+    let (video_id, rest) = rest.split_once('/')?;
+    let (bucket, key) = rest.split_once('/')?;
+    Some((video_id.parse().ok()?, CatalogBucket::from_name(bucket)?, key))
+}
+
+/// Copy an archive entry to a file, returning its size and hex SHA-256
+fn copy_hashed(reader: &mut impl Read, mut file: &File) -> std::io::Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        file.write_all(&buffer[..read])?;
+        size += read as u64;
+    }
+    file.flush()?;
+
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+/// Point a playlist or manifest at the new ID of its video; its segment,
+/// playlist and key URLs carry the ID
+fn rewrite_video_id(path: &Path, from: Uuid, to: Uuid) -> std::io::Result<()> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Ok(());
+    };
+    std::fs::write(path, text.replace(&from.to_string(), &to.to_string()))
+}
+
+/// ID of the category at a path of names, outermost first, creating the
+/// categories that do not exist yet
+async fn category_by_path(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    path: &[String],
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let mut parent_id = None;
+    for name in path {
+        sqlx::query(
+            r#"
+            INSERT INTO categories (tenant_id, parent_id, name)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id, COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'), LOWER(name))
+            DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(parent_id)
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM categories
+            WHERE tenant_id = $1 AND parent_id IS NOT DISTINCT FROM $2 AND LOWER(name) = LOWER($3)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(parent_id)
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?;
+        parent_id = Some(id);
+    }
+
+    Ok(parent_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIDEO_ID: Uuid = uuid::uuid!("0b5e7a44-2d3c-4e8f-9a61-7c2f1d9e8b30");
+
+    #[test]
+    fn object_names_split_into_video_bucket_and_key() {
+        for bucket in CatalogBucket::ALL {
+            let key = format!("{}/720p/segment_000.ts", VIDEO_ID);
+            let name = format!("objects/{}/{}/{}", VIDEO_ID, bucket.as_str(), key);
+            assert_eq!(parse_object_name(&name), Some((VIDEO_ID, bucket, key.as_str())));
+        }
+    }
+
+    #[test]
+    fn other_entries_are_not_objects() {
+        for name in [
+            "manifest.json".to_string(),
+            format!("objects/{}", VIDEO_ID),
+            format!("objects/{}/videos", VIDEO_ID),
+            format!("objects/{}/thumbnails/a.jpg", VIDEO_ID),
+            "objects/not-a-uuid/videos/a.mp4".to_string(),
+            format!("files/{}/videos/a.mp4", VIDEO_ID),
+        ] {
+            assert_eq!(parse_object_name(&name), None, "{} is not an object", name);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::services::jobs::{retry_at, run_worker};
+use crate::services::{StorageService, TenantService};
+
+/// How long a claimed video is hidden from other collector passes
+const CLAIM_LEASE_SECS: f64 = 600.0;
+
+/// Background garbage collector for soft-deleted videos
+///
+/// `delete_video` only moves a video to the trash; once the retention period
+/// has passed this worker removes the stored objects, hard-deletes the row
+/// after storage is verified clean and records the purge in the deletion log.
+pub struct GarbageCollector {
+    storage: Arc<StorageService>,
+    tenants: Arc<TenantService>,
+    db: PgPool,
+    interval: Duration,
+    batch_size: i64,
+    retention_days: i64,
+    processing_timeout: Duration,
+}
+
+impl GarbageCollector {
+    /// Create a new garbage collector
+    pub fn new(
+        config: &Config,
+        storage: Arc<StorageService>,
+        tenants: Arc<TenantService>,
+        db: PgPool,
+    ) -> Self {
+        Self {
+            storage,
+            tenants,
+            db,
+            interval: Duration::from_secs(config.gc_interval_secs),
+            batch_size: config.gc_batch_size,
+            retention_days: config.trash_retention_days,
+            processing_timeout: Duration::from_secs(config.gc_processing_timeout_secs),
+        }
+    }
+
+    /// Start the background worker that purges deleted videos
+    pub async fn start_worker(self: Arc<Self>) {
+        let this = &self;
+        run_worker("garbage collection", self.interval, move || async move {
+            let purged = this.collect().await?;
+            if purged > 0 {
+                tracing::info!("Garbage collection purged {} videos", purged);
+            }
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(false)
+        })
+        .await
+    }
+
+    /// Run one collection pass, returning how many videos were purged
+    async fn collect(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let videos = self.claim_batch().await?;
+        let mut purged = 0;
+
+        for (video_id, tenant_id) in videos {
+            let Some(tenant) = self.tenants.get(tenant_id).await? else {
+                self.schedule_retry(video_id, &format!("Tenant {} not found", tenant_id))
+                    .await?;
+                continue;
+            };
+
+            match self.storage.for_tenant(&tenant).purge_video(video_id).await {
+                Ok(true) => {
+                    sqlx::query(
+                        r#"
+                        WITH purged AS (
+                            DELETE FROM videos
+                            WHERE id = $1 AND deleted_at IS NOT NULL
+                            RETURNING id, title, original_filename, deleted_by, deleted_at
+                        )
+                        INSERT INTO video_deletion_log
+                            (video_id, title, original_filename, deleted_by, deleted_at)
+                        SELECT id, title, original_filename, deleted_by, deleted_at FROM purged
+                        "#,
+                    )
+                    .bind(video_id)
+                    .execute(&self.db)
+                    .await?;
+
+                    tracing::info!("Video purged: {}", video_id);
+                    purged += 1;
+                }
+                Ok(false) => {
+                    self.schedule_retry(video_id, "Objects still present after purge")
+                        .await?;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to purge video {}: {}", video_id, e);
+                    self.schedule_retry(video_id, &e.to_string()).await?;
+                }
+            }
+        }
+
+        Ok(purged)
+    }
+
+    /// Claim a batch of deleted videos whose retention period has passed.
+    ///
+    /// Claimed rows get a lease in `purge_after` so concurrent collectors skip
+    /// them. Videos still being transcoded are left alone until the worker is
+    /// done writing their segments, unless they have not changed for longer
+    /// than `GC_PROCESSING_TIMEOUT_SECS`, in which case their transcode died.
+    async fn claim_batch(&self) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+        sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            UPDATE videos
+            SET purge_after = CURRENT_TIMESTAMP + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM videos
+                WHERE deleted_at IS NOT NULL
+                  AND deleted_at <= CURRENT_TIMESTAMP - make_interval(days => $3)
+                  AND (status <> 'processing'
+                       OR updated_at <= CURRENT_TIMESTAMP - make_interval(secs => $4))
+                  AND (purge_after IS NULL OR purge_after <= CURRENT_TIMESTAMP)
+                ORDER BY deleted_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, tenant_id
+            "#,
+        )
+        .bind(self.batch_size)
+        .bind(CLAIM_LEASE_SECS)
+        .bind(self.retention_days as i32)
+        .bind(self.processing_timeout.as_secs_f64())
+        .fetch_all(&self.db)
+        .await
+    }
+
+    /// Record a failed purge and push the next attempt out with exponential
+    /// backoff
+    async fn schedule_retry(&self, video_id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+        let attempts = sqlx::query_scalar::<_, i32>(&format!(
+            r#"
+            UPDATE videos
+            SET purge_attempts = purge_attempts + 1,
+                purge_error = $2,
+                purge_after = {}
+            WHERE id = $1
+            RETURNING purge_attempts
+            "#,
+            retry_at("purge_attempts")
+        ))
+        .bind(video_id)
+        .bind(error)
+        .fetch_one(&self.db)
+        .await?;
+
+        tracing::info!("Scheduled purge retry for video {} (attempt {})", video_id, attempts);
+        Ok(())
+    }
+}
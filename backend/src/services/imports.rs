@@ -0,0 +1,710 @@
+use futures::stream::{self, StreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{ImportOrigin, TranscodeJobMessage, WebhookEvent};
+use crate::services::jobs::{run_worker, POLL_INTERVAL};
+use crate::services::storage::ObjectStream;
+use crate::services::webhooks;
+use crate::services::{QuotaService, StorageService, TenantService, TranscoderService};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// How long a claimed import is hidden from other workers unless renewed;
+/// progress reports renew it while the file is being copied
+const CLAIM_LEASE_SECS: f64 = 900.0;
+
+/// Claims after which an import that keeps getting interrupted fails
+const MAX_ATTEMPTS: i32 = 3;
+
+/// How often progress is written while copying
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a source may send nothing before the import fails
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Redirects followed before an http(s) import fails
+const MAX_REDIRECTS: usize = 10;
+
+/// Where an import reads its file from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    Http(reqwest::Url),
+    /// Object of another bucket of the storage backend
+    S3 { bucket: String, key: String },
+    /// File under one of the `IMPORT_ALLOWED_PATHS`
+    Local(PathBuf),
+}
+
+impl ImportSource {
+    /// Last segment of the source's path, if it has one
+    pub fn file_name(&self) -> Option<String> {
+        let name = match self {
+            Self::Http(url) => url.path_segments()?.next_back()?.to_string(),
+            Self::S3 { key, .. } => key.rsplit('/').next()?.to_string(),
+            Self::Local(path) => path.file_name()?.to_str()?.to_string(),
+        };
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+impl fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(url) => write!(f, "{}", url),
+            Self::S3 { bucket, key } => write!(f, "s3://{}/{}", bucket, key),
+            Self::Local(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Whether a host is on `IMPORT_ALLOWED_HOSTS`
+fn is_allowed_host(allowed_hosts: &[String], host: &str) -> bool {
+    allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+/// Reject an http(s) URL whose host is not on a non-empty
+/// `IMPORT_ALLOWED_HOSTS`, or that is the address of a private network.
+/// Host names are checked as they resolve, by `PublicResolver`.
+fn check_url(allowed_hosts: &[String], url: &reqwest::Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{} is not an http(s) URL", url));
+    }
+    let host = url.host_str().ok_or_else(|| format!("{} has no host", url))?;
+    if is_allowed_host(allowed_hosts, host) {
+        return Ok(());
+    }
+    if !allowed_hosts.is_empty() {
+        return Err(format!("Host {} is not allowed for imports", host));
+    }
+
+    let address = host.trim_start_matches('[').trim_end_matches(']');
+    match address.parse::<IpAddr>() {
+        Ok(ip) if !is_public(ip) => Err(format!("Address {} is not allowed for imports", ip)),
+        _ => Ok(()),
+    }
+}
+
+/// Whether an address is routable on the internet, rather than loopback,
+/// link-local, private or otherwise reserved
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // "This network", and reserved including broadcast
+                || a == 0
+                || a >= 240
+                // Shared address space of carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b))
+                // IETF protocol assignments
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking
+                || (a == 198 && (b == 18 || b == 19)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let segments = ip.segments();
+                let first = segments[0];
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    // NAT64, which reaches any IPv4 address through the gateway
+                    || segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+                    // Unique local
+                    || (first & 0xfe00) == 0xfc00
+                    // Link-local
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolver of the import client, leaving out the private addresses of
+/// hosts that are not on `IMPORT_ALLOWED_HOSTS`. Checking the addresses
+/// connected to, rather than those seen when the import was requested,
+/// keeps a host from resolving to a public address first and a private one
+/// later.
+struct PublicResolver {
+    allowed_hosts: Arc<Vec<String>>,
+}
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let trusted = is_allowed_host(&self.allowed_hosts, name.as_str());
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| trusted || is_public(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Message of an error followed by those of its causes, which reqwest
+/// leaves out of its own
+fn with_causes(e: reqwest::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+/// Import claimed by a worker, with the video it fills in
+#[derive(FromRow)]
+struct ClaimedImport {
+    id: Uuid,
+    video_id: Uuid,
+    source: String,
+    origin: ImportOrigin,
+    expected_size: Option<i64>,
+    expected_sha256: Option<String>,
+    attempts: i32,
+    tenant_id: Uuid,
+    owner_id: Option<Uuid>,
+    original_path: String,
+    original_size: i64,
+    deleted: bool,
+}
+
+/// Imports of videos from URLs, other buckets and server-side paths
+///
+/// `POST /videos/import` records the import; workers claim it, stream the
+/// file to a temporary file while hashing it and reporting progress, verify
+/// its size and checksum, store it as the video's original and queue the
+/// usual transcoding job. An import whose worker died is claimed again once
+/// its lease runs out.
+pub struct ImportService {
+    storage: Arc<StorageService>,
+    tenants: Arc<TenantService>,
+    quotas: Arc<QuotaService>,
+    transcoder: Arc<TranscoderService>,
+    db: PgPool,
+    client: reqwest::Client,
+    temp_dir: PathBuf,
+    allowed_hosts: Arc<Vec<String>>,
+    allowed_paths: Vec<PathBuf>,
+    allowed_buckets: Vec<String>,
+}
+
+impl ImportService {
+    /// Create the import service
+    pub async fn new(
+        config: &Config,
+        storage: Arc<StorageService>,
+        tenants: Arc<TenantService>,
+        quotas: Arc<QuotaService>,
+        transcoder: Arc<TranscoderService>,
+        db: PgPool,
+    ) -> Result<Self, BoxError> {
+        let temp_dir = PathBuf::from(&config.temp_dir).join("imports");
+        tokio::fs::create_dir_all(&temp_dir).await?;
+
+        // Symlinks and `..` are resolved before paths are compared
+        let mut allowed_paths = Vec::new();
+        for path in config.import_allowed_paths.iter().map(PathBuf::from) {
+            match tokio::fs::canonicalize(&path).await {
+                Ok(path) => allowed_paths.push(path),
+                Err(e) => tracing::warn!("Ignoring import path {}: {}", path.display(), e),
+            }
+        }
+
+        // Every redirect is checked like the URL of the import, and host
+        // names resolve to public addresses only
+        let allowed_hosts = Arc::new(config.import_allowed_hosts.clone());
+        let redirect_hosts = allowed_hosts.clone();
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(30))
+            .user_agent(concat!("videostream-import/", env!("CARGO_PKG_VERSION")))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error(format!("More than {} redirects", MAX_REDIRECTS));
+                }
+                match check_url(&redirect_hosts, attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(e) => attempt.error(e),
+                }
+            }))
+            .dns_resolver(Arc::new(PublicResolver {
+                allowed_hosts: allowed_hosts.clone(),
+            }))
+            .build()?;
+
+        Ok(Self {
+            storage,
+            tenants,
+            quotas,
+            transcoder,
+            db,
+            client,
+            temp_dir,
+            allowed_hosts,
+            allowed_paths,
+            allowed_buckets: config.import_allowed_buckets.clone(),
+        })
+    }
+
+    /// Parse an import source, checking hosts, local paths and buckets
+    /// against the allowlists
+    pub async fn check_source(&self, source: &str) -> Result<ImportSource, String> {
+        self.parse_source(source, ImportOrigin::Request).await
+    }
+
+    /// Parse the source of an import. Those of the ingest, in its own
+    /// archive, skip the path and bucket allowlists.
+    async fn parse_source(&self, source: &str, origin: ImportOrigin) -> Result<ImportSource, String> {
+        let trusted = origin == ImportOrigin::Ingest;
+        let source = source.trim();
+
+        if let Some(location) = source.strip_prefix("s3://") {
+            let (bucket, key) = location
+                .split_once('/')
+                .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+                .ok_or_else(|| "S3 sources must be s3://bucket/key".to_string())?;
+            if !trusted && !self.allowed_buckets.iter().any(|allowed| allowed == bucket) {
+                return Err(format!("Bucket {} is not allowed for imports", bucket));
+            }
+            return Ok(ImportSource::S3 {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+            });
+        }
+
+        if source.starts_with('/') {
+            // Missing files get the same error as disallowed ones, so the
+            // check does not reveal what exists outside the allowlist
+            let rejected = || format!("{} is not a file under an allowed import path", source);
+            let path = tokio::fs::canonicalize(source).await.map_err(|_| rejected())?;
+            if !trusted && !self.allowed_paths.iter().any(|root| path.starts_with(root)) {
+                return Err(rejected());
+            }
+            let is_file = tokio::fs::metadata(&path).await.map(|m| m.is_file()).unwrap_or(false);
+            if !is_file {
+                return Err(rejected());
+            }
+            return Ok(ImportSource::Local(path));
+        }
+
+        let url = reqwest::Url::parse(source).map_err(|e| format!("Invalid import source: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(
+                "Import sources must be http(s) URLs, s3://bucket/key or absolute paths"
+                    .to_string(),
+            );
+        }
+        check_url(&self.allowed_hosts, &url)?;
+        Ok(ImportSource::Http(url))
+    }
+
+    /// Start a background worker that runs imports one at a time
+    pub async fn start_worker(self: Arc<Self>) {
+        let this = &self;
+        run_worker("import", POLL_INTERVAL, move || async move {
+            let imported = this.process_next_import().await?;
+            if let Some(video_id) = imported {
+                tracing::info!("Imported video: {}", video_id);
+            }
+            Ok::<_, BoxError>(imported.is_some())
+        })
+        .await
+    }
+
+    /// Claim and run the oldest waiting import, returning its video
+    async fn process_next_import(&self) -> Result<Option<Uuid>, BoxError> {
+        let Some(job) = self.claim().await? else {
+            return Ok(None);
+        };
+
+        // Named after the claim, so a worker that lost its lease cannot
+        // write into the file of the worker that took over
+        let work_path = self.temp_dir.join(format!("{}-{}", job.id, job.attempts));
+        let result = self.import(&job, &work_path).await;
+        if let Err(e) = tokio::fs::remove_file(&work_path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove {}: {}", work_path.display(), e);
+            }
+        }
+
+        match result {
+            Ok(()) => Ok(Some(job.video_id)),
+            Err(e) => {
+                self.fail(&job, &e.to_string()).await?;
+                Err(format!("Import of video {} failed: {}", job.video_id, e).into())
+            }
+        }
+    }
+
+    /// Copy the file of a claimed import into storage and queue its
+    /// transcoding
+    async fn import(&self, job: &ClaimedImport, work_path: &Path) -> Result<(), BoxError> {
+        if job.deleted {
+            return Err("Video was deleted".into());
+        }
+        if job.attempts > MAX_ATTEMPTS {
+            return Err(format!("Import was interrupted {} times", job.attempts - 1).into());
+        }
+
+        let source = self.parse_source(&job.source, job.origin).await?;
+        let tenant = self
+            .tenants
+            .get(job.tenant_id)
+            .await?
+            .ok_or_else(|| format!("Tenant {} not found", job.tenant_id))?;
+
+        tracing::info!("Importing video {} from {}", job.video_id, source);
+        let (stream, content_type) = self.open(&source).await?;
+        let announced = stream.content_length.map(|length| length as i64);
+        if let (Some(expected), Some(announced)) = (job.expected_size, announced) {
+            if expected != announced {
+                return Err(format!(
+                    "Source has {} bytes, expected {}",
+                    announced, expected
+                )
+                .into());
+            }
+        }
+        if announced.is_some() {
+            sqlx::query("UPDATE video_imports SET total_bytes = $2 WHERE id = $1")
+                .bind(job.id)
+                .bind(announced)
+                .execute(&self.db)
+                .await?;
+        }
+
+        let limit = self.quotas.upload_limit(job.tenant_id, job.owner_id).await?;
+        let (size, sha256) = self.download(job, stream, work_path, limit).await?;
+
+        if let Some(announced) = announced.filter(|announced| *announced != size) {
+            return Err(format!("Source ended after {} of {} bytes", size, announced).into());
+        }
+        if let Some(expected) = job.expected_size.filter(|expected| *expected != size) {
+            return Err(format!("File has {} bytes, expected {}", size, expected).into());
+        }
+        if let Some(expected) = job.expected_sha256.as_ref().filter(|expected| **expected != sha256) {
+            return Err(format!("File has SHA-256 {}, expected {}", sha256, expected).into());
+        }
+        self.quotas
+            .check_import(job.tenant_id, job.owner_id, job.original_size, size)
+            .await?;
+
+        let content_type = content_type
+            .filter(|content_type| content_type.starts_with("video/"))
+            .or_else(|| {
+                mime_guess::from_path(&job.original_path)
+                    .first()
+                    .map(|mime| mime.to_string())
+            })
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        self.renew(job).await?;
+        self.storage
+            .for_tenant(&tenant)
+            .upload_original_from_file(&job.original_path, work_path, &content_type)
+            .await?;
+
+        let mut tx = self.db.begin().await?;
+        let stored = sqlx::query(
+            r#"
+            UPDATE videos SET original_size = $2, mime_type = $3
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(job.video_id)
+        .bind(size)
+        .bind(&content_type)
+        .execute(&mut *tx)
+        .await?;
+        if stored.rows_affected() == 0 {
+            return Err("Video was deleted".into());
+        }
+
+        let completed = sqlx::query(
+            r#"
+            UPDATE video_imports
+            SET status = 'completed', bytes_received = $3, sha256 = $4,
+                lease_until = NULL, completed_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND attempts = $2
+            "#,
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(size)
+        .bind(&sha256)
+        .execute(&mut *tx)
+        .await?;
+        if completed.rows_affected() == 0 {
+            return Err("Import was claimed by another worker".into());
+        }
+
+        webhooks::emit(&mut tx, job.video_id, WebhookEvent::Uploaded).await?;
+        tx.commit().await?;
+
+        self.transcoder
+            .queue_job(TranscodeJobMessage {
+                video_id: job.video_id,
+                tenant_id: job.tenant_id,
+                original_path: job.original_path.clone(),
+                resolutions: vec!["360p".to_string(), "720p".to_string()],
+                request_id: None,
+            })
+            .await
+            .map_err(|e| format!("Failed to queue transcoding job: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Open a source for reading, with the content type it announces
+    async fn open(&self, source: &ImportSource) -> Result<(ObjectStream, Option<String>), BoxError> {
+        match source {
+            ImportSource::Http(url) => {
+                let response = self
+                    .client
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .map_err(with_causes)?
+                    .error_for_status()?;
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let content_length = response.content_length();
+                let body = stream::unfold(response, |mut response| async move {
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => Some((Ok(chunk), response)),
+                        Ok(None) => None,
+                        Err(e) => Some((Err(std::io::Error::other(e)), response)),
+                    }
+                })
+                .boxed();
+
+                Ok((ObjectStream { content_length, body }, content_type))
+            }
+            ImportSource::S3 { bucket, key } => {
+                Ok((self.storage.stream_external(bucket, key).await?, None))
+            }
+            ImportSource::Local(path) => {
+                let file = tokio::fs::File::open(path).await?;
+                let content_length = Some(file.metadata().await?.len());
+                let body = ReaderStream::new(file).boxed();
+
+                Ok((ObjectStream { content_length, body }, None))
+            }
+        }
+    }
+
+    /// Stream a source into `path`, returning its size and hex SHA-256
+    async fn download(
+        &self,
+        job: &ClaimedImport,
+        stream: ObjectStream,
+        path: &Path,
+        limit: Option<i64>,
+    ) -> Result<(i64, String), BoxError> {
+        let mut file = tokio::fs::File::create(path).await?;
+        let mut body = stream.body;
+        let mut hasher = Sha256::new();
+        let mut received: i64 = 0;
+        let mut reported = Instant::now();
+
+        while let Some(chunk) = tokio::time::timeout(STALL_TIMEOUT, body.next())
+            .await
+            .map_err(|_| format!("Source sent nothing for {} seconds", STALL_TIMEOUT.as_secs()))?
+        {
+            let chunk = chunk?;
+            received += chunk.len() as i64;
+            if let Some(limit) = limit.filter(|limit| received > *limit) {
+                return Err(format!("File exceeds the upload limit of {} bytes", limit).into());
+            }
+            if let Some(expected) = job.expected_size.filter(|expected| received > *expected) {
+                return Err(format!("File is larger than the expected {} bytes", expected).into());
+            }
+
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+
+            if reported.elapsed() >= PROGRESS_INTERVAL {
+                self.report_progress(job, received).await?;
+                reported = Instant::now();
+            }
+        }
+
+        file.flush().await?;
+        self.report_progress(job, received).await?;
+
+        Ok((received, hex::encode(hasher.finalize())))
+    }
+
+    /// Claim the oldest import that is waiting or whose worker's lease ran
+    /// out
+    async fn claim(&self) -> Result<Option<ClaimedImport>, sqlx::Error> {
+        sqlx::query_as::<_, ClaimedImport>(
+            r#"
+            UPDATE video_imports i
+            SET status = 'downloading',
+                attempts = i.attempts + 1,
+                bytes_received = 0,
+                error_message = NULL,
+                lease_until = CURRENT_TIMESTAMP + make_interval(secs => $1),
+                started_at = COALESCE(i.started_at, CURRENT_TIMESTAMP)
+            FROM videos v
+            WHERE v.id = i.video_id AND i.id = (
+                SELECT id FROM video_imports
+                WHERE status = 'pending'
+                   OR (status = 'downloading' AND lease_until <= CURRENT_TIMESTAMP)
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING i.id, i.video_id, i.source, i.origin, i.expected_size, i.expected_sha256,
+                      i.attempts, v.tenant_id, v.owner_id, v.original_path, v.original_size,
+                      v.deleted_at IS NOT NULL AS deleted
+            "#,
+        )
+        .bind(CLAIM_LEASE_SECS)
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    /// Record how much of the file has been copied, renewing the lease
+    async fn report_progress(&self, job: &ClaimedImport, received: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE video_imports
+            SET bytes_received = $3,
+                lease_until = CURRENT_TIMESTAMP + make_interval(secs => $4)
+            WHERE id = $1 AND attempts = $2
+            "#,
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(received)
+        .bind(CLAIM_LEASE_SECS)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Renew the lease of an import, before a step that reports no progress
+    async fn renew(&self, job: &ClaimedImport) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE video_imports
+            SET lease_until = CURRENT_TIMESTAMP + make_interval(secs => $3)
+            WHERE id = $1 AND attempts = $2
+            "#,
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(CLAIM_LEASE_SECS)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark an import and its video failed
+    async fn fail(&self, job: &ClaimedImport, error: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let current = sqlx::query(
+            r#"
+            UPDATE video_imports
+            SET status = 'failed', error_message = $3, lease_until = NULL
+            WHERE id = $1 AND attempts = $2
+            "#,
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+
+        // A worker that lost its lease leaves the video to the one that took over
+        if current.rows_affected() > 0 {
+            let failed = sqlx::query(
+                r#"
+                UPDATE videos SET status = 'failed', error_message = $2
+                WHERE id = $1 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(job.video_id)
+            .bind(format!("Import failed: {}", error))
+            .execute(&mut *tx)
+            .await?;
+
+            if failed.rows_affected() > 0 {
+                webhooks::emit(&mut tx, job.video_id, WebhookEvent::Failed).await?;
+            }
+        }
+
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> reqwest::Url {
+        reqwest::Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn private_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254",
+            "100.64.0.1", "0.0.0.0", "255.255.255.255", "::1", "::", "fd00::1", "fe80::1",
+            "::ffff:127.0.0.1", "192.0.0.170", "64:ff9b::7f00:1", "64:ff9b::808:808",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} is private", ip);
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn urls_of_private_addresses_are_rejected() {
+        assert!(check_url(&[], &url("http://127.0.0.1/video.mp4")).is_err());
+        assert!(check_url(&[], &url("http://[::1]:8080/video.mp4")).is_err());
+        assert!(check_url(&[], &url("http://169.254.169.254/latest/meta-data")).is_err());
+        assert!(check_url(&[], &url("file:///etc/passwd")).is_err());
+        assert!(check_url(&[], &url("https://8.8.8.8/video.mp4")).is_ok());
+        // Names are checked as they resolve
+        assert!(check_url(&[], &url("https://media.example.com/video.mp4")).is_ok());
+    }
+
+    #[test]
+    fn allowed_hosts_restrict_and_trust_hosts() {
+        let allowed = vec!["media.internal".to_string(), "10.0.0.5".to_string()];
+        assert!(check_url(&allowed, &url("https://MEDIA.internal/video.mp4")).is_ok());
+        assert!(check_url(&allowed, &url("http://10.0.0.5/video.mp4")).is_ok());
+        assert!(check_url(&allowed, &url("https://media.example.com/video.mp4")).is_err());
+        assert!(check_url(&allowed, &url("http://10.0.0.6/video.mp4")).is_err());
+    }
+}
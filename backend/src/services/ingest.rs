@@ -0,0 +1,502 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{ImportMetadata, Tenant};
+use crate::services::storage::{StorageError, StorageResult};
+use crate::services::{ImportSource, StorageService, TenantService};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// Extensions of sidecar files, in the order they are looked for
+const SIDECAR_EXTENSIONS: [&str; 2] = ["json", "xml"];
+
+/// Largest sidecar file read
+const MAX_SIDECAR_BYTES: u64 = 1024 * 1024;
+
+/// Directory ingested files are moved to: `INGEST_ARCHIVE_DIR`, or
+/// `archive` inside the watched directory
+pub fn archive_dir(config: &Config) -> Option<PathBuf> {
+    let watch_dir = config.ingest_watch_dir.as_ref()?;
+    Some(match &config.ingest_archive_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(watch_dir).join("archive"),
+    })
+}
+
+/// File picked up by the ingest, with the metadata of its sidecar
+pub struct IngestFile {
+    /// Where the file is imported from once archived
+    pub source: ImportSource,
+    pub size: i64,
+    /// Metadata from the sidecar, or why it could not be read
+    pub metadata: Result<ImportMetadata, String>,
+    /// The file and its sidecar, as (current, archived) locations
+    moves: Vec<(String, String)>,
+}
+
+/// Ingest of video files dropped into a watched directory or a bucket
+///
+/// Files are ingested as imports: the file and its sidecar are moved to
+/// the archive and the import copies the video from there, so a file is
+/// only ever picked up once.
+pub struct IngestService {
+    db: PgPool,
+    storage: Arc<StorageService>,
+    tenants: Arc<TenantService>,
+    watch_dir: Option<PathBuf>,
+    archive_dir: Option<PathBuf>,
+    scan_interval: Duration,
+    settle: Duration,
+    bucket: Option<String>,
+    prefix: String,
+    archive_prefix: String,
+    notify_token: Option<String>,
+    tenant: Option<String>,
+    owner: Option<String>,
+}
+
+impl IngestService {
+    /// Create the ingest service, creating the archive directory
+    pub async fn new(
+        config: &Config,
+        storage: Arc<StorageService>,
+        tenants: Arc<TenantService>,
+        db: PgPool,
+    ) -> Result<Self, BoxError> {
+        // Imports only accept absolute paths
+        let archive_dir = match archive_dir(config) {
+            Some(dir) => {
+                tokio::fs::create_dir_all(&dir).await?;
+                Some(tokio::fs::canonicalize(&dir).await?)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            db,
+            storage,
+            tenants,
+            watch_dir: config.ingest_watch_dir.as_ref().map(PathBuf::from),
+            archive_dir,
+            scan_interval: Duration::from_secs(config.ingest_scan_interval_secs.max(1)),
+            settle: Duration::from_secs(config.ingest_settle_secs),
+            bucket: config.ingest_bucket.clone(),
+            prefix: config.ingest_prefix.clone(),
+            archive_prefix: config.ingest_archive_prefix.clone(),
+            notify_token: config.ingest_notify_token.clone(),
+            tenant: config.ingest_tenant.clone(),
+            owner: config.ingest_owner.clone(),
+        })
+    }
+
+    /// Whether a directory is watched
+    pub fn watches_dir(&self) -> bool {
+        self.watch_dir.is_some()
+    }
+
+    /// How often the watched directory is scanned
+    pub fn scan_interval(&self) -> Duration {
+        self.scan_interval
+    }
+
+    /// Tenant and owner of ingested videos
+    pub async fn target(&self) -> Result<(Arc<Tenant>, Option<Uuid>), BoxError> {
+        let tenant = match &self.tenant {
+            Some(slug) => self.tenants.by_slug(slug).await?,
+            None => self.tenants.resolve(None).await?,
+        }
+        .ok_or("Ingest tenant not found")?;
+
+        let owner_id = match &self.owner {
+            Some(username) => Some(
+                sqlx::query_scalar::<_, Uuid>(
+                    "SELECT id FROM users WHERE tenant_id = $1 AND username = $2",
+                )
+                .bind(tenant.id)
+                .bind(username)
+                .fetch_optional(&self.db)
+                .await?
+                .ok_or_else(|| format!("Ingest owner {} not found", username))?,
+            ),
+            None => None,
+        };
+
+        Ok((tenant, owner_id))
+    }
+
+    /// Video files of the watched directory that have not been written to
+    /// for the settle time, oldest first.
+    ///
+    /// Only the directory itself is scanned, so the default archive inside
+    /// it is skipped, as are hidden files and sidecars.
+    pub async fn settled_files(&self) -> std::io::Result<Vec<PathBuf>> {
+        let Some(watch_dir) = &self.watch_dir else {
+            return Ok(Vec::new());
+        };
+
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(watch_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() || !is_video(&path.to_string_lossy()) {
+                continue;
+            }
+
+            let mut modified = metadata.modified()?;
+            for sidecar in local_sidecars(&path) {
+                if let Ok(metadata) = tokio::fs::metadata(&sidecar).await {
+                    modified = modified.max(metadata.modified()?);
+                }
+            }
+            let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+            if age >= self.settle {
+                files.push((modified, path));
+            }
+        }
+
+        files.sort();
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Read a file of the watched directory and its sidecar, and choose
+    /// where they are archived. `None` if the file is gone.
+    pub async fn local_file(&self, path: &Path) -> Result<Option<IngestFile>, BoxError> {
+        let Some(archive_dir) = &self.archive_dir else {
+            return Ok(None);
+        };
+        let size = match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.is_file() => metadata.len() as i64,
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // Each file gets its own directory, so names never collide
+        let target = archive_dir.join(Uuid::new_v4().to_string());
+        let archived = |path: &Path| -> Result<String, BoxError> {
+            let name = path.file_name().ok_or("File without a name")?;
+            Ok(target.join(name).to_string_lossy().into_owned())
+        };
+
+        let mut moves = vec![(path.to_string_lossy().into_owned(), archived(path)?)];
+        let mut metadata = Ok(ImportMetadata::default());
+        for sidecar in local_sidecars(path) {
+            let data = match tokio::fs::read(&sidecar).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            metadata = parse_sidecar(&sidecar.to_string_lossy(), &data);
+            moves.push((sidecar.to_string_lossy().into_owned(), archived(&sidecar)?));
+            break;
+        }
+
+        Ok(Some(IngestFile {
+            source: ImportSource::Local(PathBuf::from(&moves[0].1)),
+            size,
+            metadata,
+            moves,
+        }))
+    }
+
+    /// Move a file of the watched directory and its sidecar to the archive
+    pub async fn archive_local(&self, file: &IngestFile) -> std::io::Result<()> {
+        for (from, to) in &file.moves {
+            move_file(from, to).await?;
+        }
+        Ok(())
+    }
+
+    /// Move an archived file and its sidecar back to the watched directory,
+    /// for an ingest that could not be recorded
+    pub async fn restore_local(&self, file: &IngestFile) {
+        for (from, to) in &file.moves {
+            match move_file(to, from).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Failed to move {} back to {}: {}", to, from, e),
+            }
+        }
+    }
+
+    /// Check the token a bucket notification was sent with
+    pub fn check_token(&self, authorization: Option<&str>) -> bool {
+        let (Some(expected), Some(token)) = (&self.notify_token, authorization) else {
+            return false;
+        };
+        let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+
+        // Compared as MACs, so the comparison takes constant time
+        let mac = |message: &str| {
+            let mut mac = HmacSha256::new_from_slice(expected.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(message.as_bytes());
+            mac
+        };
+        mac(token).verify_slice(&mac(expected).finalize().into_bytes()).is_ok()
+    }
+
+    /// Whether a notified object is a video to ingest: in the ingest
+    /// bucket, under the prefix and outside the archive
+    pub fn accepts_object(&self, bucket: &str, key: &str) -> bool {
+        self.bucket.as_deref() == Some(bucket)
+            && key.starts_with(&self.prefix)
+            && !key.starts_with(&self.archive_prefix)
+            && is_video(key)
+    }
+
+    /// Read an object of the ingest bucket and its sidecar, and choose
+    /// where they are archived. `None` if the object is gone.
+    pub async fn bucket_file(&self, key: &str) -> Result<Option<IngestFile>, BoxError> {
+        let Some(bucket) = &self.bucket else {
+            return Ok(None);
+        };
+        let size = match self.storage.head_external(bucket, key).await {
+            Ok(meta) => meta.size as i64,
+            Err(StorageError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let target = format!("{}{}", self.archive_prefix, Uuid::new_v4());
+        let archived = |key: &str| {
+            let name = key.rsplit('/').next().unwrap_or(key);
+            format!("{}/{}", target, name)
+        };
+
+        let mut moves = vec![(key.to_string(), archived(key))];
+        let mut metadata = Ok(ImportMetadata::default());
+        for sidecar in sidecar_names(key) {
+            let data = match self.storage.head_external(bucket, &sidecar).await {
+                Ok(meta) if meta.size > MAX_SIDECAR_BYTES => {
+                    Err(StorageError::Backend(format!("Sidecar {} is too large", sidecar)))
+                }
+                Ok(_) => self.storage.get_external(bucket, &sidecar).await,
+                Err(e) => Err(e),
+            };
+            let data = match data {
+                Ok(data) => data,
+                Err(StorageError::NotFound(_)) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            metadata = parse_sidecar(&sidecar, &data);
+            moves.push((sidecar.clone(), archived(&sidecar)));
+            break;
+        }
+
+        Ok(Some(IngestFile {
+            source: ImportSource::S3 {
+                bucket: bucket.clone(),
+                key: moves[0].1.clone(),
+            },
+            size,
+            metadata,
+            moves,
+        }))
+    }
+
+    /// Move an object of the ingest bucket and its sidecar to the archive
+    pub async fn archive_object(&self, file: &IngestFile) -> StorageResult<()> {
+        let Some(bucket) = &self.bucket else {
+            return Ok(());
+        };
+        for (from, to) in &file.moves {
+            self.storage.move_external(bucket, from, to).await?;
+        }
+        Ok(())
+    }
+
+    /// Move an archived object and its sidecar back under the ingest
+    /// prefix, for an ingest that could not be recorded
+    pub async fn restore_object(&self, file: &IngestFile) {
+        let Some(bucket) = &self.bucket else {
+            return;
+        };
+        for (from, to) in &file.moves {
+            match self.storage.move_external(bucket, to, from).await {
+                Ok(()) | Err(StorageError::NotFound(_)) => {}
+                Err(e) => tracing::warn!("Failed to move {} back to {}: {}", to, from, e),
+            }
+        }
+    }
+}
+
+/// Move a file, creating the directory it moves to
+async fn move_file(from: &str, to: &str) -> std::io::Result<()> {
+    if let Some(parent) = Path::new(to).parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    // The archive may be on another filesystem
+    if tokio::fs::rename(from, to).await.is_err() {
+        tokio::fs::copy(from, to).await?;
+        tokio::fs::remove_file(from).await?;
+    }
+    Ok(())
+}
+
+/// Whether a file name has a video type
+fn is_video(name: &str) -> bool {
+    let hidden = name.rsplit('/').next().is_some_and(|name| name.starts_with('.'));
+    !hidden
+        && mime_guess::from_path(name)
+            .first()
+            .is_some_and(|mime| mime.type_() == mime_guess::mime::VIDEO)
+}
+
+/// Names the sidecar of a file may have: its name with a `.json` or `.xml`
+/// extension instead of its own
+fn sidecar_names(name: &str) -> Vec<String> {
+    let stem = match name.rfind('.') {
+        Some(idx) if !name[idx..].contains('/') => &name[..idx],
+        _ => name,
+    };
+    SIDECAR_EXTENSIONS
+        .iter()
+        .map(|extension| format!("{}.{}", stem, extension))
+        .collect()
+}
+
+fn local_sidecars(path: &Path) -> Vec<PathBuf> {
+    SIDECAR_EXTENSIONS
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .collect()
+}
+
+/// Parse a JSON or XML sidecar into the metadata of its video
+fn parse_sidecar(name: &str, data: &[u8]) -> Result<ImportMetadata, String> {
+    if data.len() as u64 > MAX_SIDECAR_BYTES {
+        return Err(format!("Sidecar {} is too large", name));
+    }
+    if name.ends_with(".json") {
+        return serde_json::from_slice(data).map_err(|e| format!("Invalid sidecar {}: {}", name, e));
+    }
+
+    let text = std::str::from_utf8(data).map_err(|e| format!("Invalid sidecar {}: {}", name, e))?;
+    parse_xml_sidecar(text).map_err(|e| format!("Invalid sidecar {}: {}", name, e))
+}
+
+/// Read an XML sidecar: a root element with one child per field, `<tag>`
+/// children inside `<tags>` and one child per key inside `<custom_metadata>`,
+/// whose values are kept as strings
+fn parse_xml_sidecar(text: &str) -> Result<ImportMetadata, String> {
+    let document = roxmltree::Document::parse(text).map_err(|e| e.to_string())?;
+    let mut metadata = ImportMetadata::default();
+
+    for field in document.root_element().children().filter(|node| node.is_element()) {
+        let value = field.text().unwrap_or_default().trim().to_string();
+        match field.tag_name().name() {
+            "title" => metadata.title = Some(value),
+            "description" => metadata.description = Some(value),
+            "visibility" => metadata.visibility = value.parse()?,
+            "language" => metadata.language = Some(value),
+            "tags" => {
+                metadata.tags = field
+                    .children()
+                    .filter(|node| node.has_tag_name("tag"))
+                    .map(|tag| tag.text().unwrap_or_default().trim().to_string())
+                    .collect();
+            }
+            "category_id" => {
+                metadata.category_id = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid category_id: {}", value))?,
+                );
+            }
+            "custom_metadata" => {
+                metadata.custom_metadata = Some(
+                    field
+                        .children()
+                        .filter(|node| node.is_element())
+                        .map(|entry| {
+                            let value = entry.text().unwrap_or_default().trim().to_string();
+                            (entry.tag_name().name().to_string(), serde_json::Value::String(value))
+                        })
+                        .collect(),
+                );
+            }
+            other => return Err(format!("Unknown field {}", other)),
+        }
+    }
+
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Visibility;
+
+    #[test]
+    fn xml_sidecars_fill_in_every_field() {
+        let metadata = parse_xml_sidecar(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <video>
+                <title> Launch keynote </title>
+                <description>Recorded on stage &amp; streamed</description>
+                <visibility>unlisted</visibility>
+                <language>english</language>
+                <tags><tag>events</tag><tag> keynote </tag></tags>
+                <category_id>6f1c1c7e-55a4-4f37-9d43-6c1e0f0ad6b2</category_id>
+                <custom_metadata><speaker>Ada</speaker><year>2026</year></custom_metadata>
+            </video>"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Launch keynote"));
+        assert_eq!(metadata.description.as_deref(), Some("Recorded on stage & streamed"));
+        assert_eq!(metadata.visibility, Visibility::Unlisted);
+        assert_eq!(metadata.language.as_deref(), Some("english"));
+        assert_eq!(metadata.tags, vec!["events", "keynote"]);
+        assert_eq!(
+            metadata.category_id,
+            Some(uuid::uuid!("6f1c1c7e-55a4-4f37-9d43-6c1e0f0ad6b2"))
+        );
+        // Custom values stay strings, for the metadata schema to check
+        assert_eq!(
+            serde_json::Value::Object(metadata.custom_metadata.unwrap()),
+            serde_json::json!({"speaker": "Ada", "year": "2026"})
+        );
+    }
+
+    #[test]
+    fn xml_sidecars_may_leave_fields_out() {
+        let metadata = parse_xml_sidecar("<video><title>Clip</title></video>").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Clip"));
+        assert_eq!(metadata.visibility, Visibility::default());
+        assert!(metadata.tags.is_empty());
+        assert!(metadata.custom_metadata.is_none());
+    }
+
+    #[test]
+    fn invalid_xml_sidecars_are_rejected() {
+        for xml in [
+            "<video><title>Unclosed</video>",
+            "<video><owner>mallory</owner></video>",
+            "<video><visibility>everyone</visibility></video>",
+            "<video><category_id>42</category_id></video>",
+        ] {
+            assert!(parse_xml_sidecar(xml).is_err(), "{} is rejected", xml);
+        }
+    }
+
+    #[test]
+    fn sidecars_are_parsed_by_extension() {
+        let json = parse_sidecar("clip.json", br#"{"title": "From JSON"}"#).unwrap();
+        assert_eq!(json.title.as_deref(), Some("From JSON"));
+        let xml = parse_sidecar("clip.xml", b"<video><title>From XML</title></video>").unwrap();
+        assert_eq!(xml.title.as_deref(), Some("From XML"));
+
+        let oversized = vec![b' '; MAX_SIDECAR_BYTES as usize + 1];
+        assert!(parse_sidecar("clip.json", &oversized).is_err());
+        assert!(parse_sidecar("clip.xml", &[0xff, 0xfe]).is_err());
+    }
+}
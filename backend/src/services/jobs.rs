@@ -0,0 +1,43 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// How long an idle worker waits before looking for work again
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Upper bound for the retry backoff after a failed attempt
+const MAX_BACKOFF_SECS: u32 = 3600;
+
+/// Run the passes of a background worker until the process exits. Each
+/// pass claims work with a lease, so that work of a worker that died is
+/// claimed again once its lease runs out, and returns whether to start the
+/// next pass at once; otherwise, and after a failed pass, the worker waits
+/// for `idle`.
+pub async fn run_worker<F, Fut, E>(name: &str, idle: Duration, mut pass: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<bool, E>>,
+    E: Display,
+{
+    tracing::info!("Starting {} worker...", name);
+
+    loop {
+        match pass().await {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => tracing::error!("Error in {} worker: {}", name, e),
+        }
+
+        tokio::time::sleep(idle).await;
+    }
+}
+
+/// SQL timestamp of the next attempt after the failed attempt counted by
+/// the `attempts` column: 30s doubling per attempt, capped at
+/// `MAX_BACKOFF_SECS`
+pub fn retry_at(attempts: &str) -> String {
+    format!(
+        "CURRENT_TIMESTAMP + make_interval(secs => LEAST(30 * power(2, LEAST({}, 16)), {}))",
+        attempts, MAX_BACKOFF_SECS
+    )
+}
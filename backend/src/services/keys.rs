@@ -0,0 +1,279 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use cbc::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::{Config, HlsEncryption};
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+
+/// Length of an HLS AES-128 content key in bytes
+pub const CONTENT_KEY_LEN: usize = 16;
+
+/// Content key of a video, in clear
+pub type ContentKey = [u8; CONTENT_KEY_LEN];
+
+/// System ID of the W3C common PSSH box, used by Clear Key
+pub const COMMON_SYSTEM_ID: Uuid = uuid::uuid!("1077efec-c0b2-4d02-ace3-3c1e52e2fb4b");
+
+/// A content key together with its key ID (KID)
+#[derive(Debug, Clone, Copy)]
+pub struct StoredKey {
+    pub key_id: Uuid,
+    pub key: ContentKey,
+}
+
+/// Per-video content keys for segment encryption.
+///
+/// Keys are generated on first use and stored in Postgres encrypted with
+/// AES-256-GCM under the master key from `Config`. With rotation enabled a
+/// new key is used every `rotation_segments` media segments.
+pub struct KeyService {
+    db: PgPool,
+    master: Option<Aes256Gcm>,
+    method: HlsEncryption,
+    rotation_segments: u64,
+}
+
+impl KeyService {
+    /// Create the key service; the master key is required once encryption is enabled
+    pub fn new(
+        config: &Config,
+        db: PgPool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let master = match config.content_key_master_key.as_deref() {
+            Some(hex_key) => {
+                let key = hex::decode(hex_key.trim())
+                    .map_err(|e| format!("CONTENT_KEY_MASTER_KEY is not valid hex: {}", e))?;
+                let cipher = Aes256Gcm::new_from_slice(&key)
+                    .map_err(|_| "CONTENT_KEY_MASTER_KEY must be 32 bytes (64 hex characters)")?;
+                Some(cipher)
+            }
+            None if config.hls_encryption != HlsEncryption::None => {
+                return Err("HLS encryption requires CONTENT_KEY_MASTER_KEY".into());
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            db,
+            master,
+            method: config.hls_encryption,
+            rotation_segments: config.hls_key_rotation_segments,
+        })
+    }
+
+    /// Encryption applied to newly transcoded videos
+    pub fn method(&self) -> HlsEncryption {
+        self.method
+    }
+
+    /// Index of the key protecting the segment with the given media sequence number
+    pub fn key_index(&self, sequence: u64) -> i32 {
+        match self.rotation_segments {
+            0 => 0,
+            period => (sequence / period) as i32,
+        }
+    }
+
+    /// Fetch a video's content key, generating and storing it if it does not exist yet
+    pub async fn get_or_create(
+        &self,
+        video_id: Uuid,
+        key_index: i32,
+    ) -> Result<StoredKey, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(key) = self.get(video_id, key_index).await? {
+            return Ok(key);
+        }
+
+        let mut key = [0u8; CONTENT_KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        let (nonce, encrypted) = self.seal(&key)?;
+
+        // A concurrent transcode of the same video may have won the race
+        sqlx::query(
+            r#"
+            INSERT INTO content_keys (video_id, key_index, encrypted_key, nonce)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (video_id, key_index) DO NOTHING
+            "#,
+        )
+        .bind(video_id)
+        .bind(key_index)
+        .bind(encrypted)
+        .bind(nonce.to_vec())
+        .execute(&self.db)
+        .await?;
+
+        self.get(video_id, key_index)
+            .await?
+            .ok_or_else(|| "Content key vanished after insert".into())
+    }
+
+    /// Fetch and decrypt a stored content key
+    pub async fn get(
+        &self,
+        video_id: Uuid,
+        key_index: i32,
+    ) -> Result<Option<StoredKey>, Box<dyn std::error::Error + Send + Sync>> {
+        let row = sqlx::query_as::<_, (Uuid, Vec<u8>, Vec<u8>)>(
+            r#"
+            SELECT key_id, encrypted_key, nonce FROM content_keys
+            WHERE video_id = $1 AND key_index = $2
+            "#,
+        )
+        .bind(video_id)
+        .bind(key_index)
+        .fetch_optional(&self.db)
+        .await?;
+
+        row.map(|(key_id, encrypted, nonce)| self.open(key_id, encrypted, nonce))
+            .transpose()
+    }
+
+    /// Fetch and decrypt the keys of a video with the given key IDs; unknown IDs are skipped
+    pub async fn find(
+        &self,
+        video_id: Uuid,
+        key_ids: &[Uuid],
+    ) -> Result<Vec<StoredKey>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, (Uuid, Vec<u8>, Vec<u8>)>(
+            r#"
+            SELECT key_id, encrypted_key, nonce FROM content_keys
+            WHERE video_id = $1 AND key_id = ANY($2)
+            ORDER BY key_index
+            "#,
+        )
+        .bind(video_id)
+        .bind(key_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter()
+            .map(|(key_id, encrypted, nonce)| self.open(key_id, encrypted, nonce))
+            .collect()
+    }
+
+    /// Check that a content key stored by another deployment opens with
+    /// this deployment's master key
+    pub fn check_sealed(
+        &self,
+        encrypted: &[u8],
+        nonce: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.open(Uuid::nil(), encrypted.to_vec(), nonce.to_vec())
+            .map(|_| ())
+    }
+
+    fn open(
+        &self,
+        key_id: Uuid,
+        encrypted: Vec<u8>,
+        nonce: Vec<u8>,
+    ) -> Result<StoredKey, Box<dyn std::error::Error + Send + Sync>> {
+        let nonce: [u8; 12] = nonce
+            .try_into()
+            .map_err(|_| "Stored content key has an invalid nonce")?;
+        let key = self
+            .master()?
+            .decrypt(&Nonce::from(nonce), encrypted.as_slice())
+            .map_err(|_| "Failed to decrypt content key; wrong master key?")?;
+
+        let key: ContentKey = key
+            .try_into()
+            .map_err(|_| "Stored content key has an invalid length")?;
+        Ok(StoredKey { key_id, key })
+    }
+
+    fn seal(
+        &self,
+        key: &ContentKey,
+    ) -> Result<([u8; 12], Vec<u8>), Box<dyn std::error::Error + Send + Sync>> {
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+
+        let encrypted = self
+            .master()?
+            .encrypt(&Nonce::from(nonce), key.as_slice())
+            .map_err(|_| "Failed to encrypt content key")?;
+
+        Ok((nonce, encrypted))
+    }
+
+    fn master(&self) -> Result<&Aes256Gcm, Box<dyn std::error::Error + Send + Sync>> {
+        self.master
+            .as_ref()
+            .ok_or_else(|| "CONTENT_KEY_MASTER_KEY is not configured".into())
+    }
+}
+
+/// Encrypt a whole media segment as required by HLS `METHOD=AES-128`
+///
+/// Without an explicit `IV` attribute the IV is the segment's media
+/// sequence number as a big-endian 128-bit integer.
+pub fn encrypt_segment(data: &[u8], key: &ContentKey, sequence: u64) -> Vec<u8> {
+    let iv = u128::from(sequence).to_be_bytes();
+    Aes128CbcEnc::new(key.into(), &iv.into()).encrypt_padded_vec_mut::<Pkcs7>(data)
+}
+
+/// Build a version 1 common PSSH box listing the given key IDs
+///
+/// This is the box Clear Key players read from the init segment, the DASH
+/// `cenc:pssh` element or an HLS `data:` key URI to learn which KIDs to request.
+pub fn common_pssh(key_ids: &[Uuid]) -> Vec<u8> {
+    // size + type + version/flags + system ID + KID count + KIDs + data size
+    let size = 4 + 4 + 4 + 16 + 4 + 16 * key_ids.len() + 4;
+
+    let mut pssh = Vec::with_capacity(size);
+    pssh.extend_from_slice(&(size as u32).to_be_bytes());
+    pssh.extend_from_slice(b"pssh");
+    pssh.extend_from_slice(&0x0100_0000u32.to_be_bytes());
+    pssh.extend_from_slice(COMMON_SYSTEM_ID.as_bytes());
+    pssh.extend_from_slice(&(key_ids.len() as u32).to_be_bytes());
+    for key_id in key_ids {
+        pssh.extend_from_slice(key_id.as_bytes());
+    }
+    pssh.extend_from_slice(&0u32.to_be_bytes());
+    pssh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbc::cipher::BlockDecryptMut;
+
+    type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+    const KEY: ContentKey = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+    #[test]
+    fn segments_use_the_sequence_number_as_iv() {
+        // openssl enc -aes-128-cbc -K 000102030405060708090a0b0c0d0e0f
+        //   -iv 00000000000000000000000000000007
+        assert_eq!(
+            hex::encode(encrypt_segment(b"segment data", &KEY, 7)),
+            "9686cfbeadc63ef9b7420bb4dc48ef9a"
+        );
+        assert_ne!(
+            encrypt_segment(b"segment data", &KEY, 7),
+            encrypt_segment(b"segment data", &KEY, 8)
+        );
+    }
+
+    #[test]
+    fn segments_are_padded_and_decrypt() {
+        for len in [0, 1, 15, 16, 17, 188 * 7] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let encrypted = encrypt_segment(&data, &KEY, 42);
+            assert_eq!(encrypted.len(), (len / 16 + 1) * 16);
+
+            let iv = 42u128.to_be_bytes();
+            let decrypted = Aes128CbcDec::new(&KEY.into(), &iv.into())
+                .decrypt_padded_vec_mut::<Pkcs7>(&encrypted)
+                .unwrap();
+            assert_eq!(decrypted, data);
+        }
+    }
+}
@@ -1,5 +1,30 @@
+pub mod api_keys;
+pub mod auth;
+pub mod bulk;
+pub mod catalog;
+pub mod gc;
+pub mod imports;
+pub mod ingest;
+pub mod jobs;
+pub mod keys;
+pub mod playback;
+pub mod quotas;
 pub mod storage;
+pub mod tenants;
 pub mod transcoder;
+pub mod webhooks;
 
+pub use api_keys::ApiKeyService;
+pub use auth::AuthService;
+pub use bulk::run_bulk_operations;
+pub use catalog::CatalogService;
+pub use gc::GarbageCollector;
+pub use imports::{ImportService, ImportSource};
+pub use ingest::IngestService;
+pub use keys::KeyService;
+pub use playback::PlaybackTokens;
+pub use quotas::QuotaService;
 pub use storage::StorageService;
+pub use tenants::TenantService;
 pub use transcoder::TranscoderService;
+pub use webhooks::WebhookDispatcher;
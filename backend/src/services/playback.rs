@@ -0,0 +1,351 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::net::IpAddr;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Errors returned when a playback token is rejected
+#[derive(Error, Debug)]
+pub enum TokenError {
+    #[error("malformed playback token")]
+    Malformed,
+
+    #[error("invalid playback token signature")]
+    BadSignature,
+
+    #[error("playback token expired")]
+    Expired,
+
+    #[error("playback token issued for another video")]
+    WrongVideo,
+
+    #[error("playback token bound to another client address")]
+    IpMismatch,
+
+    #[error("playback token bound to another user")]
+    UserMismatch,
+}
+
+/// Claims carried by a playback token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackClaims {
+    /// Video the token grants access to
+    pub vid: Uuid,
+    /// Expiry as a Unix timestamp
+    pub exp: i64,
+    /// Client address the token is bound to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    /// User the token is bound to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+}
+
+/// Issues and validates HMAC-signed playback tokens.
+///
+/// A token is `base64url(json claims) "." base64url(HMAC-SHA256)`, passed to
+/// the streaming routes as the `token` query parameter.
+pub struct PlaybackTokens {
+    secret: Vec<u8>,
+    ttl_secs: i64,
+}
+
+impl PlaybackTokens {
+    /// Create a token service from the configured secret and lifetime
+    pub fn new(config: &Config) -> Self {
+        Self {
+            secret: config.playback_token_secret.as_bytes().to_vec(),
+            ttl_secs: config.playback_token_ttl_secs,
+        }
+    }
+
+    /// Issue a token for a video, optionally bound to a client address and user
+    pub fn issue(
+        &self,
+        video_id: Uuid,
+        ip: Option<IpAddr>,
+        user: Option<String>,
+    ) -> (String, DateTime<Utc>) {
+        let expires_at = Utc::now() + chrono::Duration::seconds(self.ttl_secs);
+        let claims = PlaybackClaims {
+            vid: video_id,
+            exp: expires_at.timestamp(),
+            ip,
+            sub: user,
+        };
+
+        let payload = URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&claims).expect("claims serialize to JSON"));
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&payload).finalize().into_bytes());
+
+        (format!("{}.{}", payload, signature), expires_at)
+    }
+
+    /// Validate a token for a request to one of a video's assets
+    pub fn verify(
+        &self,
+        token: &str,
+        video_id: Uuid,
+        client_ip: Option<IpAddr>,
+        user: Option<&str>,
+    ) -> Result<PlaybackClaims, TokenError> {
+        let (payload, signature) = token.split_once('.').ok_or(TokenError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| TokenError::Malformed)?;
+
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+
+        let claims: PlaybackClaims = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or(TokenError::Malformed)?;
+
+        if claims.exp < Utc::now().timestamp() {
+            return Err(TokenError::Expired);
+        }
+        if claims.vid != video_id {
+            return Err(TokenError::WrongVideo);
+        }
+        if claims.ip.is_some() && claims.ip != client_ip {
+            return Err(TokenError::IpMismatch);
+        }
+        if claims.sub.is_some() && claims.sub.as_deref() != user {
+            return Err(TokenError::UserMismatch);
+        }
+
+        Ok(claims)
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// Append the playback token to every URI of an HLS playlist.
+///
+/// Covers plain URI lines (variant playlists and segments) as well as
+/// `URI="..."` attributes such as `EXT-X-MAP` and `EXT-X-MEDIA`.
+pub fn tokenize_playlist(playlist: &str, token: &str) -> String {
+    rewrite_playlist_uris(playlist, |uri| with_token(uri, token))
+}
+
+/// Append the playback token to the segment templates of a DASH manifest
+pub fn tokenize_mpd(mpd: &str, token: &str) -> String {
+    let mut output = String::with_capacity(mpd.len());
+    let mut rest = mpd;
+
+    while let Some((start, attribute)) = [" media=\"", " initialization=\""]
+        .iter()
+        .filter_map(|attribute| rest.find(attribute).map(|start| (start, attribute)))
+        .min_by_key(|(start, _)| *start)
+    {
+        let value_start = start + attribute.len();
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let value_end = value_start + len;
+
+        let uri = &rest[value_start..value_end];
+        // `&` must be escaped inside an XML attribute
+        let separator = if uri.contains('?') { "&amp;" } else { "?" };
+
+        output.push_str(&rest[..value_start]);
+        output.push_str(&format!("{}{}token={}", uri, separator, token));
+        rest = &rest[value_end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Rewrite every URI of an HLS playlist with `f`.
+///
+/// `data:` URIs, such as the PSSH of Clear Key `EXT-X-KEY` tags, are left as is.
+pub fn rewrite_playlist_uris(playlist: &str, f: impl Fn(&str) -> String) -> String {
+    let rewrite = |uri: &str| {
+        if uri.starts_with("data:") {
+            uri.to_string()
+        } else {
+            f(uri)
+        }
+    };
+
+    let mut output = String::with_capacity(playlist.len());
+
+    for line in playlist.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            output.push_str(line);
+        } else if !trimmed.starts_with('#') {
+            output.push_str(&rewrite(trimmed));
+        } else if let Some(start) = trimmed.find("URI=\"") {
+            let value_start = start + "URI=\"".len();
+            match trimmed[value_start..].find('"') {
+                Some(len) => {
+                    let value_end = value_start + len;
+                    output.push_str(&trimmed[..value_start]);
+                    output.push_str(&rewrite(&trimmed[value_start..value_end]));
+                    output.push_str(&trimmed[value_end..]);
+                }
+                None => output.push_str(line),
+            }
+        } else {
+            output.push_str(line);
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+fn with_token(uri: &str, token: &str) -> String {
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{}{}token={}", uri, separator, token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(ttl_secs: i64) -> PlaybackTokens {
+        PlaybackTokens {
+            secret: b"secret".to_vec(),
+            ttl_secs,
+        }
+    }
+
+    #[test]
+    fn tokens_verify_for_their_video() {
+        let tokens = tokens(60);
+        let video = Uuid::new_v4();
+        let (token, _) = tokens.issue(video, None, None);
+
+        let claims = tokens.verify(&token, video, None, None).unwrap();
+        assert_eq!(claims.vid, video);
+        assert!(matches!(
+            tokens.verify(&token, Uuid::new_v4(), None, None),
+            Err(TokenError::WrongVideo)
+        ));
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let tokens = tokens(-1);
+        let video = Uuid::new_v4();
+        let (token, _) = tokens.issue(video, None, None);
+
+        assert!(matches!(tokens.verify(&token, video, None, None), Err(TokenError::Expired)));
+    }
+
+    #[test]
+    fn tokens_are_bound_to_their_address_and_user() {
+        let tokens = tokens(60);
+        let video = Uuid::new_v4();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let (token, _) = tokens.issue(video, Some(ip), Some("alice".to_string()));
+
+        assert!(tokens.verify(&token, video, Some(ip), Some("alice")).is_ok());
+        assert!(matches!(
+            tokens.verify(&token, video, Some("203.0.113.8".parse().unwrap()), Some("alice")),
+            Err(TokenError::IpMismatch)
+        ));
+        assert!(matches!(
+            tokens.verify(&token, video, None, Some("alice")),
+            Err(TokenError::IpMismatch)
+        ));
+        assert!(matches!(
+            tokens.verify(&token, video, Some(ip), Some("bob")),
+            Err(TokenError::UserMismatch)
+        ));
+
+        // Unbound tokens play from any address
+        let (token, _) = tokens.issue(video, None, None);
+        assert!(tokens.verify(&token, video, Some(ip), Some("bob")).is_ok());
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let tokens = tokens(60);
+        let video = Uuid::new_v4();
+        let (token, _) = tokens.issue(video, None, None);
+        let (payload, signature) = token.split_once('.').unwrap();
+
+        let claims = PlaybackClaims { vid: video, exp: i64::MAX, ip: None, sub: None };
+        let forged = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap());
+        assert!(matches!(
+            tokens.verify(&format!("{}.{}", forged, signature), video, None, None),
+            Err(TokenError::BadSignature)
+        ));
+        let other = PlaybackTokens { secret: b"other".to_vec(), ttl_secs: 60 };
+        assert!(matches!(other.verify(&token, video, None, None), Err(TokenError::BadSignature)));
+        assert!(matches!(tokens.verify(payload, video, None, None), Err(TokenError::Malformed)));
+        assert!(matches!(
+            tokens.verify(&format!("{}.!!", payload), video, None, None),
+            Err(TokenError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn playlist_uris_get_the_token() {
+        let playlist = "#EXTM3U\n\
+            #EXT-X-MAP:URI=\"init.mp4\"\n\
+            #EXTINF:6.0,\n\
+            segment_000.ts\n\
+            \n\
+            #EXT-X-STREAM-INF:BANDWIDTH=800000\n\
+            720p/playlist.m3u8?v=2\n";
+
+        assert_eq!(
+            tokenize_playlist(playlist, "tok"),
+            "#EXTM3U\n\
+            #EXT-X-MAP:URI=\"init.mp4?token=tok\"\n\
+            #EXTINF:6.0,\n\
+            segment_000.ts?token=tok\n\
+            \n\
+            #EXT-X-STREAM-INF:BANDWIDTH=800000\n\
+            720p/playlist.m3u8?v=2&token=tok\n"
+        );
+    }
+
+    #[test]
+    fn mpd_segment_templates_get_the_token() {
+        let mpd = r#"<SegmentTemplate timescale="1000" initialization="init-$RepresentationID$.mp4" media="chunk-$Number$.m4s"/>
+<SegmentTemplate media="seg.m4s?v=2" startNumber="1"/>
+<BaseURL>https://cdn.example.com/</BaseURL>"#;
+
+        assert_eq!(
+            tokenize_mpd(mpd, "tok"),
+            r#"<SegmentTemplate timescale="1000" initialization="init-$RepresentationID$.mp4?token=tok" media="chunk-$Number$.m4s?token=tok"/>
+<SegmentTemplate media="seg.m4s?v=2&amp;token=tok" startNumber="1"/>
+<BaseURL>https://cdn.example.com/</BaseURL>"#
+        );
+    }
+
+    #[test]
+    fn data_uris_keep_their_pssh() {
+        let playlist = "#EXT-X-KEY:METHOD=SAMPLE-AES,URI=\"data:text/plain;base64,AAAA\",KEYFORMAT=\"org.w3.clearkey\"\n\
+            segment_000.m4s\n";
+
+        assert_eq!(
+            tokenize_playlist(playlist, "tok"),
+            "#EXT-X-KEY:METHOD=SAMPLE-AES,URI=\"data:text/plain;base64,AAAA\",KEYFORMAT=\"org.w3.clearkey\"\n\
+            segment_000.m4s?token=tok\n"
+        );
+    }
+}
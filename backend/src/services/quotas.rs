@@ -0,0 +1,362 @@
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{QuotaLimits, Usage};
+
+/// Errors returned by quota checks
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    #[error("upload of {size} bytes exceeds the {scope} limit of {limit} bytes")]
+    UploadTooLarge { scope: &'static str, size: i64, limit: i64 },
+
+    #[error("quota exceeded: {0}")]
+    Exceeded(String),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Limits and usage of one of the scopes a quota applies to
+struct Account {
+    scope: &'static str,
+    limits: QuotaLimits,
+    usage: Usage,
+}
+
+/// Per-user and per-tenant usage limits.
+///
+/// Usage is computed from the `videos` table, counting videos in the trash
+/// bin until they are purged. A user's limits come from their row in
+/// `quotas`, or from the configured defaults; a tenant's from the row
+/// without a user. Uploads and transcodes must stay within both.
+pub struct QuotaService {
+    db: PgPool,
+    defaults: QuotaLimits,
+}
+
+impl QuotaService {
+    /// Create the quota service
+    pub fn new(config: &Config, db: PgPool) -> Self {
+        Self {
+            db,
+            defaults: config.default_quota.clone(),
+        }
+    }
+
+    /// Limits of a user: their own, or the configured defaults
+    pub async fn user_limits(&self, user_id: Uuid) -> Result<QuotaLimits, sqlx::Error> {
+        self.user_limits_with(&self.db, user_id).await
+    }
+
+    async fn user_limits_with(
+        &self,
+        executor: impl PgExecutor<'_>,
+        user_id: Uuid,
+    ) -> Result<QuotaLimits, sqlx::Error> {
+        let limits = sqlx::query_as::<_, QuotaLimits>(
+            r#"
+            SELECT max_original_bytes, max_rendition_bytes, max_videos, max_upload_bytes, max_duration_seconds
+            FROM quotas WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(limits.unwrap_or_else(|| self.defaults.clone()))
+    }
+
+    /// Limits of a tenant as a whole
+    pub async fn tenant_limits(&self, tenant_id: Uuid) -> Result<QuotaLimits, sqlx::Error> {
+        tenant_limits(&self.db, tenant_id).await
+    }
+
+    /// Set the limits of a user of a tenant
+    pub async fn set_user_limits(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        limits: &QuotaLimits,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO quotas (tenant_id, user_id, max_original_bytes, max_rendition_bytes, max_videos, max_upload_bytes, max_duration_seconds)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id) WHERE user_id IS NOT NULL DO UPDATE
+            SET max_original_bytes = EXCLUDED.max_original_bytes,
+                max_rendition_bytes = EXCLUDED.max_rendition_bytes,
+                max_videos = EXCLUDED.max_videos,
+                max_upload_bytes = EXCLUDED.max_upload_bytes,
+                max_duration_seconds = EXCLUDED.max_duration_seconds,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(limits.max_original_bytes)
+        .bind(limits.max_rendition_bytes)
+        .bind(limits.max_videos)
+        .bind(limits.max_upload_bytes)
+        .bind(limits.max_duration_seconds)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Set the limits of a tenant as a whole
+    pub async fn set_tenant_limits(&self, tenant_id: Uuid, limits: &QuotaLimits) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO quotas (tenant_id, max_original_bytes, max_rendition_bytes, max_videos, max_upload_bytes, max_duration_seconds)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id) WHERE user_id IS NULL DO UPDATE
+            SET max_original_bytes = EXCLUDED.max_original_bytes,
+                max_rendition_bytes = EXCLUDED.max_rendition_bytes,
+                max_videos = EXCLUDED.max_videos,
+                max_upload_bytes = EXCLUDED.max_upload_bytes,
+                max_duration_seconds = EXCLUDED.max_duration_seconds,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(limits.max_original_bytes)
+        .bind(limits.max_rendition_bytes)
+        .bind(limits.max_videos)
+        .bind(limits.max_upload_bytes)
+        .bind(limits.max_duration_seconds)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop the limits of a user, reverting them to the defaults
+    pub async fn reset_user_limits(&self, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM quotas WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Storage consumed by the videos of a tenant, or of one owner in it
+    pub async fn usage(&self, tenant_id: Uuid, owner_id: Option<Uuid>) -> Result<Usage, sqlx::Error> {
+        usage_except(&self.db, tenant_id, owner_id, None).await
+    }
+
+    /// Reject an upload of `size` bytes by `user_id` exceeding a limit
+    pub async fn check_upload(&self, tenant_id: Uuid, user_id: Uuid, size: i64) -> Result<(), QuotaError> {
+        let mut conn = self.db.acquire().await?;
+        self.check_upload_with(&mut conn, tenant_id, user_id, size).await
+    }
+
+    /// Check an upload like `check_upload` within a transaction, holding the
+    /// tenant's quota until the transaction ends so that concurrent uploads
+    /// count each other's videos
+    pub async fn lock_upload(
+        &self,
+        conn: &mut PgConnection,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        size: i64,
+    ) -> Result<(), QuotaError> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("quota:{}", tenant_id))
+            .execute(&mut *conn)
+            .await?;
+
+        self.check_upload_with(conn, tenant_id, user_id, size).await
+    }
+
+    async fn check_upload_with(
+        &self,
+        conn: &mut PgConnection,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        size: i64,
+    ) -> Result<(), QuotaError> {
+        for account in self.accounts(conn, tenant_id, Some(user_id), None).await? {
+            if let Some(limit) = account.limits.max_upload_bytes.filter(|limit| size > *limit) {
+                return Err(QuotaError::UploadTooLarge { scope: account.scope, size, limit });
+            }
+            account.check(
+                "video count",
+                account.usage.videos + 1,
+                account.limits.max_videos,
+            )?;
+            account.check(
+                "original storage",
+                account.usage.original_bytes + size,
+                account.limits.max_original_bytes,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Smallest single-upload limit of the user and tenant, if any
+    pub async fn upload_limit(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Option<Uuid>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut limit = self.tenant_limits(tenant_id).await?.max_upload_bytes;
+        if let Some(owner_id) = owner_id {
+            limit = match (limit, self.user_limits(owner_id).await?.max_upload_bytes) {
+                (Some(tenant), Some(user)) => Some(tenant.min(user)),
+                (tenant, user) => tenant.or(user),
+            };
+        }
+
+        Ok(limit)
+    }
+
+    /// Reject an imported file of `size` bytes too large for the owner or
+    /// tenant. The video is already counted, with `recorded` original bytes
+    /// until the import completes.
+    pub async fn check_import(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Option<Uuid>,
+        recorded: i64,
+        size: i64,
+    ) -> Result<(), QuotaError> {
+        let mut conn = self.db.acquire().await?;
+        for account in self.accounts(&mut conn, tenant_id, owner_id, None).await? {
+            if let Some(limit) = account.limits.max_upload_bytes.filter(|limit| size > *limit) {
+                return Err(QuotaError::UploadTooLarge { scope: account.scope, size, limit });
+            }
+            account.check(
+                "original storage",
+                account.usage.original_bytes - recorded + size,
+                account.limits.max_original_bytes,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reject transcoding a video longer than the owner or tenant allows
+    pub async fn check_duration(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Option<Uuid>,
+        duration_seconds: f64,
+    ) -> Result<(), QuotaError> {
+        let mut scopes = vec![("tenant", self.tenant_limits(tenant_id).await?)];
+        if let Some(owner_id) = owner_id {
+            scopes.push(("user", self.user_limits(owner_id).await?));
+        }
+
+        for (scope, limits) in scopes {
+            if let Some(limit) = limits.max_duration_seconds.filter(|limit| duration_seconds > *limit) {
+                return Err(QuotaError::Exceeded(format!(
+                    "video is {:.0} seconds long, the {} limit is {:.0} seconds",
+                    duration_seconds, scope, limit
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject storing `bytes` of renditions for a video exceeding the
+    /// rendition storage of its owner or tenant
+    pub async fn check_renditions(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Option<Uuid>,
+        video_id: Uuid,
+        bytes: i64,
+    ) -> Result<(), QuotaError> {
+        let mut conn = self.db.acquire().await?;
+        for account in self.accounts(&mut conn, tenant_id, owner_id, Some(video_id)).await? {
+            account.check(
+                "rendition storage",
+                account.usage.rendition_bytes + bytes,
+                account.limits.max_rendition_bytes,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Limits and usage of the tenant and, if given, the user, leaving out
+    /// the renditions of `except`
+    async fn accounts(
+        &self,
+        conn: &mut PgConnection,
+        tenant_id: Uuid,
+        user_id: Option<Uuid>,
+        except: Option<Uuid>,
+    ) -> Result<Vec<Account>, sqlx::Error> {
+        let mut accounts = Vec::new();
+
+        if let Some(user_id) = user_id {
+            accounts.push(Account {
+                scope: "user",
+                limits: self.user_limits_with(&mut *conn, user_id).await?,
+                usage: usage_except(&mut *conn, tenant_id, Some(user_id), except).await?,
+            });
+        }
+        accounts.push(Account {
+            scope: "tenant",
+            limits: tenant_limits(&mut *conn, tenant_id).await?,
+            usage: usage_except(&mut *conn, tenant_id, None, except).await?,
+        });
+
+        Ok(accounts)
+    }
+}
+
+async fn tenant_limits(executor: impl PgExecutor<'_>, tenant_id: Uuid) -> Result<QuotaLimits, sqlx::Error> {
+    let limits = sqlx::query_as::<_, QuotaLimits>(
+        r#"
+        SELECT max_original_bytes, max_rendition_bytes, max_videos, max_upload_bytes, max_duration_seconds
+        FROM quotas WHERE tenant_id = $1 AND user_id IS NULL
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(limits.unwrap_or_default())
+}
+
+async fn usage_except(
+    executor: impl PgExecutor<'_>,
+    tenant_id: Uuid,
+    owner_id: Option<Uuid>,
+    except: Option<Uuid>,
+) -> Result<Usage, sqlx::Error> {
+    sqlx::query_as::<_, Usage>(
+        r#"
+        SELECT COUNT(*) AS videos,
+               COALESCE(SUM(original_size), 0)::BIGINT AS original_bytes,
+               COALESCE(SUM(rendition_size) FILTER (WHERE id IS DISTINCT FROM $3), 0)::BIGINT AS rendition_bytes
+        FROM videos
+        WHERE tenant_id = $1 AND ($2::uuid IS NULL OR owner_id = $2)
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(owner_id)
+    .bind(except)
+    .fetch_one(executor)
+    .await
+}
+
+impl Account {
+    fn check(&self, what: &str, total: i64, limit: Option<i64>) -> Result<(), QuotaError> {
+        match limit {
+            Some(limit) if total > limit => Err(QuotaError::Exceeded(format!(
+                "{} of {} would exceed the {} limit of {}",
+                what, total, self.scope, limit
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
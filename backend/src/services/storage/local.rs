@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::{ByteRange, ObjectInfo, ObjectMeta, ObjectStream, Storage, StorageError, StorageResult};
+use crate::config::Config;
+
+/// Local filesystem storage backend
+///
+/// Each bucket is a directory under `root` and object keys are relative
+/// paths inside it.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// Create a new filesystem backend rooted at the configured directory
+    pub async fn new(config: &Config) -> StorageResult<Self> {
+        let root = PathBuf::from(&config.local_storage_root);
+        tokio::fs::create_dir_all(&root).await?;
+
+        Ok(Self { root })
+    }
+
+    /// Resolve a bucket to its directory
+    fn bucket_path(&self, bucket: &str) -> StorageResult<PathBuf> {
+        if !is_relative_path(bucket) || bucket.contains('/') {
+            return Err(StorageError::InvalidKey(bucket.to_string()));
+        }
+
+        Ok(self.root.join(bucket))
+    }
+
+    /// Resolve a bucket/key pair to a path, rejecting keys that escape the bucket
+    fn object_path(&self, bucket: &str, key: &str) -> StorageResult<PathBuf> {
+        if !is_relative_path(key) {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+
+        Ok(self.bucket_path(bucket)?.join(key))
+    }
+}
+
+/// Whether `s` is a non-empty relative path without `.`/`..` components
+fn is_relative_path(s: &str) -> bool {
+    !s.is_empty()
+        && Path::new(s)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn open_error(bucket: &str, key: &str, e: std::io::Error) -> StorageError {
+    match e.kind() {
+        ErrorKind::NotFound => StorageError::NotFound(format!("{}/{}", bucket, key)),
+        _ => StorageError::Io(e),
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, bucket: &str, key: &str, data: Bytes, _content_type: &str) -> StorageResult<()> {
+        let path = self.object_path(bucket, key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&path, &data).await?;
+        Ok(())
+    }
+
+    async fn put_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        source: &Path,
+        _content_type: &str,
+    ) -> StorageResult<()> {
+        let path = self.object_path(bucket, key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::copy(source, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, bucket: &str, key: &str) -> StorageResult<Bytes> {
+        let path = self.object_path(bucket, key)?;
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(|e| open_error(bucket, key, e))?;
+
+        Ok(Bytes::from(data))
+    }
+
+    async fn head(&self, bucket: &str, key: &str) -> StorageResult<ObjectMeta> {
+        let path = self.object_path(bucket, key)?;
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| open_error(bucket, key, e))?;
+
+        if !metadata.is_file() {
+            return Err(StorageError::NotFound(format!("{}/{}", bucket, key)));
+        }
+
+        let last_modified = metadata.modified().ok().map(chrono::DateTime::<chrono::Utc>::from);
+
+        // Size and modification time change whenever the file is rewritten
+        let etag = last_modified.map(|t| {
+            format!("\"{:x}-{:x}\"", metadata.len(), t.timestamp_nanos_opt().unwrap_or_default())
+        });
+
+        Ok(ObjectMeta {
+            size: metadata.len(),
+            etag,
+            content_type: mime_guess::from_path(&path).first().map(|m| m.to_string()),
+            last_modified,
+        })
+    }
+
+    async fn stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        range: Option<ByteRange>,
+    ) -> StorageResult<ObjectStream> {
+        let path = self.object_path(bucket, key)?;
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| open_error(bucket, key, e))?;
+        let metadata = file.metadata().await?;
+
+        if !metadata.is_file() {
+            return Err(StorageError::NotFound(format!("{}/{}", bucket, key)));
+        }
+
+        let Some(range) = range else {
+            return Ok(ObjectStream {
+                content_length: Some(metadata.len()),
+                body: ReaderStream::new(file).boxed(),
+            });
+        };
+
+        file.seek(SeekFrom::Start(range.start)).await?;
+
+        Ok(ObjectStream {
+            content_length: Some(range.len()),
+            body: ReaderStream::new(file.take(range.len())).boxed(),
+        })
+    }
+
+    async fn list(&self, bucket: &str, prefix: &str) -> StorageResult<Vec<ObjectInfo>> {
+        let bucket_root = self.bucket_path(bucket)?;
+
+        // Only walk the deepest directory fully covered by the prefix
+        let start_dir = match prefix.rfind('/') {
+            Some(idx) => self.object_path(bucket, &prefix[..idx])?,
+            None => bucket_root.clone(),
+        };
+
+        let mut objects = Vec::new();
+        let mut pending = vec![start_dir];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let file_type = entry.file_type().await?;
+                let path = entry.path();
+
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_file() {
+                    let Ok(relative) = path.strip_prefix(&bucket_root) else {
+                        continue;
+                    };
+                    let key = relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+
+                    if key.starts_with(prefix) {
+                        objects.push(ObjectInfo {
+                            key,
+                            size: entry.metadata().await?.len(),
+                        });
+                    }
+                }
+            }
+        }
+
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn copy(&self, bucket: &str, from: &str, to: &str) -> StorageResult<()> {
+        let source = self.object_path(bucket, from)?;
+        let path = self.object_path(bucket, to)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::copy(&source, &path)
+            .await
+            .map_err(|e| open_error(bucket, from, e))?;
+        Ok(())
+    }
+
+    async fn delete(&self, bucket: &str, keys: &[String]) -> StorageResult<()> {
+        let bucket_root = self.bucket_path(bucket)?;
+
+        for key in keys {
+            let path = self.object_path(bucket, key)?;
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            // Prune directories left empty, stopping at the bucket root
+            let mut dir = path.parent();
+            while let Some(current) = dir {
+                if current == bucket_root || tokio::fs::remove_dir(current).await.is_err() {
+                    break;
+                }
+                dir = current.parent();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage(root: &Path) -> LocalStorage {
+        LocalStorage { root: root.to_path_buf() }
+    }
+
+    #[test]
+    fn keys_stay_inside_their_bucket() {
+        let storage = storage(Path::new("/srv/storage"));
+        assert_eq!(
+            storage.object_path("videos", "abc/original.mp4").unwrap(),
+            PathBuf::from("/srv/storage/videos/abc/original.mp4")
+        );
+        for key in ["", "../secrets", "abc/../../secrets", "/etc/passwd", "./abc"] {
+            assert!(storage.object_path("videos", key).is_err(), "{:?} is rejected", key);
+        }
+    }
+
+    #[test]
+    fn buckets_are_single_directories() {
+        let storage = storage(Path::new("/srv/storage"));
+        assert_eq!(storage.bucket_path("videos").unwrap(), PathBuf::from("/srv/storage/videos"));
+        for bucket in ["", ".", "..", "videos/abc", "/videos"] {
+            assert!(storage.bucket_path(bucket).is_err(), "{:?} is rejected", bucket);
+            assert!(storage.object_path(bucket, "abc").is_err(), "{:?} is rejected", bucket);
+        }
+    }
+
+    #[tokio::test]
+    async fn escaping_keys_are_not_written() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(root.path());
+        let data = Bytes::from_static(b"data");
+
+        let escaped = storage.put("videos", "../escaped", data.clone(), "text/plain").await;
+        assert!(matches!(escaped, Err(StorageError::InvalidKey(_))));
+        assert!(!root.path().join("escaped").exists());
+
+        storage.put("videos", "abc/object", data.clone(), "text/plain").await.unwrap();
+        assert_eq!(storage.get("videos", "abc/object").await.unwrap(), data);
+    }
+}
@@ -0,0 +1,371 @@
+pub mod local;
+pub mod s3;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::{Config, StorageBackend};
+use crate::models::Tenant;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Errors returned by storage backends
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("Object not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid object key: {0}")]
+    InvalidKey(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+}
+
+pub type StorageResult<T> = std::result::Result<T, StorageError>;
+
+/// Metadata of a stored object returned by listings
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+}
+
+/// Metadata of a single stored object
+#[derive(Debug, Clone)]
+pub struct ObjectMeta {
+    pub size: u64,
+    pub etag: Option<String>,
+    pub content_type: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Inclusive byte range of an object, as in an HTTP `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes covered by the range
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Streaming body of a stored object
+pub struct ObjectStream {
+    pub content_length: Option<u64>,
+    pub body: BoxStream<'static, std::io::Result<Bytes>>,
+}
+
+/// Object storage backend
+///
+/// Buckets are plain names: S3 buckets for [`S3Storage`], top-level
+/// directories for [`LocalStorage`].
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Store an in-memory object
+    async fn put(&self, bucket: &str, key: &str, data: Bytes, content_type: &str)
+        -> StorageResult<()>;
+
+    /// Store an object from a local file
+    async fn put_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()>;
+
+    /// Read a whole object into memory
+    async fn get(&self, bucket: &str, key: &str) -> StorageResult<Bytes>;
+
+    /// Read an object's metadata without its body
+    async fn head(&self, bucket: &str, key: &str) -> StorageResult<ObjectMeta>;
+
+    /// Open an object, or a byte range of it, as a stream
+    async fn stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        range: Option<ByteRange>,
+    ) -> StorageResult<ObjectStream>;
+
+    /// List every object whose key starts with `prefix`
+    async fn list(&self, bucket: &str, prefix: &str) -> StorageResult<Vec<ObjectInfo>>;
+
+    /// Copy an object to another key of the same bucket
+    async fn copy(&self, bucket: &str, from: &str, to: &str) -> StorageResult<()>;
+
+    /// Delete a set of objects; missing keys are not an error
+    async fn delete(&self, bucket: &str, keys: &[String]) -> StorageResult<()>;
+}
+
+/// Storage service for videos, segments and manifests
+#[derive(Clone)]
+pub struct StorageService {
+    backend: Arc<dyn Storage>,
+    bucket_videos: String,
+    bucket_segments: String,
+    bucket_manifests: String,
+    /// Prefix of every object key, scoping the service to a tenant
+    key_prefix: String,
+}
+
+impl StorageService {
+    /// Create a new storage service using the backend selected in `config`
+    pub async fn new(config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let backend: Arc<dyn Storage> = match config.storage_backend {
+            StorageBackend::S3 => Arc::new(S3Storage::new(config)),
+            StorageBackend::Local => Arc::new(LocalStorage::new(config).await?),
+        };
+
+        Ok(Self::with_backend(backend, config))
+    }
+
+    /// Create a storage service on top of an existing backend
+    pub fn with_backend(backend: Arc<dyn Storage>, config: &Config) -> Self {
+        Self {
+            backend,
+            bucket_videos: config.minio_bucket_videos.clone(),
+            bucket_segments: config.minio_bucket_segments.clone(),
+            bucket_manifests: config.minio_bucket_manifests.clone(),
+            key_prefix: String::new(),
+        }
+    }
+
+    /// Storage scoped to a tenant: its buckets, with keys under its prefix.
+    ///
+    /// Keys passed to the scoped service are relative to the tenant, so
+    /// callers keep addressing objects as `{video_id}/...`.
+    pub fn for_tenant(&self, tenant: &Tenant) -> Self {
+        let prefix = tenant.key_prefix().trim_matches('/');
+        Self {
+            backend: self.backend.clone(),
+            bucket_videos: tenant.videos_bucket.clone().unwrap_or_else(|| self.bucket_videos.clone()),
+            bucket_segments: tenant
+                .segments_bucket
+                .clone()
+                .unwrap_or_else(|| self.bucket_segments.clone()),
+            bucket_manifests: tenant
+                .manifests_bucket
+                .clone()
+                .unwrap_or_else(|| self.bucket_manifests.clone()),
+            key_prefix: if prefix.is_empty() { String::new() } else { format!("{}/", prefix) },
+        }
+    }
+
+    /// Full object key of a key relative to the tenant
+    fn object_key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+
+    /// Upload original video file from file path
+    pub async fn upload_original_from_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()> {
+        self.backend
+            .put_file(&self.bucket_videos, &self.object_key(key), file_path, content_type)
+            .await?;
+
+        tracing::info!("Uploaded original video: {}", key);
+        Ok(())
+    }
+
+    /// Download original video file
+    pub async fn download_original(&self, key: &str) -> StorageResult<Vec<u8>> {
+        let data = self.backend.get(&self.bucket_videos, &self.object_key(key)).await?;
+        Ok(data.to_vec())
+    }
+
+    /// Upload segment from file path
+    pub async fn upload_segment_from_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()> {
+        self.backend
+            .put_file(&self.bucket_segments, &self.object_key(key), file_path, content_type)
+            .await
+    }
+
+    /// Upload HLS manifest file
+    pub async fn upload_manifest(&self, key: &str, data: Vec<u8>) -> StorageResult<()> {
+        self.backend
+            .put(
+                &self.bucket_manifests,
+                &self.object_key(key),
+                Bytes::from(data),
+                "application/vnd.apple.mpegurl",
+            )
+            .await?;
+
+        tracing::info!("Uploaded manifest: {}", key);
+        Ok(())
+    }
+
+    /// Upload an HLS playlist or DASH manifest from file path
+    pub async fn upload_manifest_from_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()> {
+        self.backend
+            .put_file(&self.bucket_manifests, &self.object_key(key), file_path, content_type)
+            .await?;
+
+        tracing::info!("Uploaded manifest: {}", key);
+        Ok(())
+    }
+
+    /// Name of the bucket holding original video files
+    pub fn videos_bucket(&self) -> &str {
+        &self.bucket_videos
+    }
+
+    /// Name of the bucket holding segments and thumbnails
+    pub fn segments_bucket(&self) -> &str {
+        &self.bucket_segments
+    }
+
+    /// Name of the bucket holding HLS/DASH manifests
+    pub fn manifests_bucket(&self) -> &str {
+        &self.bucket_manifests
+    }
+
+    /// Download an HLS/DASH manifest
+    pub async fn download_manifest(&self, key: &str) -> StorageResult<Bytes> {
+        self.backend.get(&self.bucket_manifests, &self.object_key(key)).await
+    }
+
+    /// Store an object of any bucket from a local file, by key relative to the tenant
+    pub async fn upload_object_from_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        file_path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()> {
+        self.backend
+            .put_file(bucket, &self.object_key(key), file_path, content_type)
+            .await
+    }
+
+    /// List the objects under `prefix` in any bucket, with keys relative to the tenant
+    pub async fn list_objects(&self, bucket: &str, prefix: &str) -> StorageResult<Vec<ObjectInfo>> {
+        let objects = self.backend.list(bucket, &self.object_key(prefix)).await?;
+        Ok(objects
+            .into_iter()
+            .filter_map(|object| {
+                let key = object.key.strip_prefix(&self.key_prefix)?.to_string();
+                Some(ObjectInfo { key, ..object })
+            })
+            .collect())
+    }
+
+    /// Read the metadata of an object in any bucket, by key relative to the tenant
+    pub async fn head_object(&self, bucket: &str, key: &str) -> StorageResult<ObjectMeta> {
+        self.backend.head(bucket, &self.object_key(key)).await
+    }
+
+    /// Open an object of any bucket, or a byte range of it, for streaming
+    pub async fn stream_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        range: Option<ByteRange>,
+    ) -> StorageResult<ObjectStream> {
+        self.backend.stream(bucket, &self.object_key(key), range).await
+    }
+
+    /// Open an object by its full key, outside the tenant's prefix
+    pub async fn stream_external(&self, bucket: &str, key: &str) -> StorageResult<ObjectStream> {
+        self.backend.stream(bucket, key, None).await
+    }
+
+    /// Read a whole object by its full key, outside the tenant's prefix
+    pub async fn get_external(&self, bucket: &str, key: &str) -> StorageResult<Bytes> {
+        self.backend.get(bucket, key).await
+    }
+
+    /// Read an object's metadata by its full key, outside the tenant's prefix
+    pub async fn head_external(&self, bucket: &str, key: &str) -> StorageResult<ObjectMeta> {
+        self.backend.head(bucket, key).await
+    }
+
+    /// Move an object to another key of its bucket, by full keys outside the
+    /// tenant's prefix
+    pub async fn move_external(&self, bucket: &str, from: &str, to: &str) -> StorageResult<()> {
+        self.backend.copy(bucket, from, to).await?;
+        self.backend.delete(bucket, &[from.to_string()]).await
+    }
+
+    /// Delete every object under `prefix` in `bucket`, returning how many were removed
+    pub async fn delete_prefix(&self, bucket: &str, prefix: &str) -> StorageResult<usize> {
+        let objects = self.backend.list(bucket, &self.object_key(prefix)).await?;
+        let bytes: u64 = objects.iter().map(|object| object.size).sum();
+        let keys: Vec<String> = objects.into_iter().map(|object| object.key).collect();
+
+        if !keys.is_empty() {
+            self.backend.delete(bucket, &keys).await?;
+        }
+
+        tracing::info!(
+            "Deleted {} objects ({} bytes) under {}/{}",
+            keys.len(),
+            bytes,
+            bucket,
+            self.object_key(prefix)
+        );
+        Ok(keys.len())
+    }
+
+    /// Check whether any object remains under `prefix` in `bucket`
+    pub async fn prefix_is_empty(&self, bucket: &str, prefix: &str) -> StorageResult<bool> {
+        Ok(self.backend.list(bucket, &self.object_key(prefix)).await?.is_empty())
+    }
+
+    /// Delete every stored object belonging to a video.
+    ///
+    /// All objects of a video (original, segments, thumbnail, playlists) are
+    /// keyed under `{video_id}/` within the tenant, so the purge is a prefix
+    /// delete per bucket.
+    /// Returns `true` once every bucket has been verified empty for the video.
+    pub async fn purge_video(&self, video_id: Uuid) -> StorageResult<bool> {
+        let prefix = format!("{}/", video_id);
+        let buckets = [
+            &self.bucket_videos,
+            &self.bucket_segments,
+            &self.bucket_manifests,
+        ];
+
+        for bucket in buckets {
+            self.delete_prefix(bucket, &prefix).await?;
+        }
+
+        for bucket in buckets {
+            if !self.prefix_is_empty(bucket, &prefix).await? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
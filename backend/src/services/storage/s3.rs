@@ -0,0 +1,265 @@
+use async_trait::async_trait;
+use aws_config::Region;
+use aws_credential_types::Credentials;
+use aws_sdk_s3::{
+    config::Builder as S3ConfigBuilder,
+    error::SdkError,
+    operation::{get_object::GetObjectError, head_object::HeadObjectError},
+    primitives::ByteStream,
+    types::{Delete, ObjectIdentifier},
+    Client,
+};
+use bytes::Bytes;
+use futures::StreamExt;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::path::Path;
+use tokio_util::io::ReaderStream;
+
+use super::{ByteRange, ObjectInfo, ObjectMeta, ObjectStream, Storage, StorageError, StorageResult};
+use crate::config::Config;
+
+/// Maximum number of keys accepted by a single `DeleteObjects` call
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Characters escaped in the `x-amz-copy-source` header; `/` separates the
+/// bucket and the key's segments and stays as is
+const COPY_SOURCE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// MinIO/S3 storage backend
+pub struct S3Storage {
+    client: Client,
+}
+
+impl S3Storage {
+    /// Create a new S3 backend for the configured MinIO endpoint
+    pub fn new(config: &Config) -> Self {
+        Self {
+            client: Self::build_client(config, &config.minio_endpoint),
+        }
+    }
+
+    fn build_client(config: &Config, endpoint: &str) -> Client {
+        // Create credentials
+        let credentials = Credentials::new(
+            &config.minio_access_key,
+            &config.minio_secret_key,
+            None,
+            None,
+            "minio",
+        );
+
+        // Build S3 config for MinIO
+        let s3_config = S3ConfigBuilder::new()
+            .endpoint_url(endpoint)
+            .region(Region::new("us-east-1"))
+            .credentials_provider(credentials)
+            .force_path_style(true)
+            .build();
+
+        Client::from_conf(s3_config)
+    }
+}
+
+fn backend_error(e: impl std::fmt::Display) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
+
+fn get_error(bucket: &str, key: &str, e: SdkError<GetObjectError>) -> StorageError {
+    match e.as_service_error() {
+        Some(GetObjectError::NoSuchKey(_)) => StorageError::NotFound(format!("{}/{}", bucket, key)),
+        _ => backend_error(e),
+    }
+}
+
+fn head_error(bucket: &str, key: &str, e: SdkError<HeadObjectError>) -> StorageError {
+    match e.as_service_error() {
+        Some(HeadObjectError::NotFound(_)) => StorageError::NotFound(format!("{}/{}", bucket, key)),
+        _ => backend_error(e),
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, bucket: &str, key: &str, data: Bytes, content_type: &str) -> StorageResult<()> {
+        self.client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(data))
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn put_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        content_type: &str,
+    ) -> StorageResult<()> {
+        let body = ByteStream::from_path(path).await.map_err(backend_error)?;
+
+        self.client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn get(&self, bucket: &str, key: &str) -> StorageResult<Bytes> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| get_error(bucket, key, e))?;
+
+        let data = response.body.collect().await.map_err(backend_error)?;
+        Ok(data.into_bytes())
+    }
+
+    async fn head(&self, bucket: &str, key: &str) -> StorageResult<ObjectMeta> {
+        let response = self
+            .client
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| head_error(bucket, key, e))?;
+
+        Ok(ObjectMeta {
+            size: response.content_length().unwrap_or(0).max(0) as u64,
+            etag: response.e_tag().map(|s| s.to_string()),
+            content_type: response.content_type().map(|s| s.to_string()),
+            last_modified: response
+                .last_modified()
+                .and_then(|t| chrono::DateTime::from_timestamp(t.secs(), t.subsec_nanos())),
+        })
+    }
+
+    async fn stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        range: Option<ByteRange>,
+    ) -> StorageResult<ObjectStream> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_range(range.map(|r| format!("bytes={}-{}", r.start, r.end)))
+            .send()
+            .await
+            .map_err(|e| get_error(bucket, key, e))?;
+
+        Ok(ObjectStream {
+            content_length: response.content_length().and_then(|len| u64::try_from(len).ok()),
+            body: ReaderStream::new(response.body.into_async_read()).boxed(),
+        })
+    }
+
+    async fn list(&self, bucket: &str, prefix: &str) -> StorageResult<Vec<ObjectInfo>> {
+        let mut objects = Vec::new();
+        let mut continuation_token: Option<String> = None;
+
+        // ListObjectsV2 returns at most 1,000 keys per page
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token.take())
+                .send()
+                .await
+                .map_err(backend_error)?;
+
+            objects.extend(response.contents().iter().filter_map(|object| {
+                Some(ObjectInfo {
+                    key: object.key()?.to_string(),
+                    size: object.size().unwrap_or(0).max(0) as u64,
+                })
+            }));
+
+            match response.next_continuation_token() {
+                Some(token) if response.is_truncated().unwrap_or(false) => {
+                    continuation_token = Some(token.to_string());
+                }
+                _ => break,
+            }
+        }
+
+        Ok(objects)
+    }
+
+    async fn copy(&self, bucket: &str, from: &str, to: &str) -> StorageResult<()> {
+        self.client
+            .copy_object()
+            .copy_source(utf8_percent_encode(&format!("{}/{}", bucket, from), COPY_SOURCE).to_string())
+            .bucket(bucket)
+            .key(to)
+            .send()
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, bucket: &str, keys: &[String]) -> StorageResult<()> {
+        for chunk in keys.chunks(DELETE_BATCH_SIZE) {
+            let objects = chunk
+                .iter()
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(backend_error)?;
+
+            let delete = Delete::builder()
+                .set_objects(Some(objects))
+                .quiet(true)
+                .build()
+                .map_err(backend_error)?;
+
+            let output = self
+                .client
+                .delete_objects()
+                .bucket(bucket)
+                .delete(delete)
+                .send()
+                .await
+                .map_err(backend_error)?;
+
+            // Per-key failures are reported in the body of a successful response
+            if let Some(error) = output.errors().first() {
+                return Err(StorageError::Backend(format!(
+                    "Failed to delete {} of {} objects in {} (first: {} {})",
+                    output.errors().len(),
+                    chunk.len(),
+                    bucket,
+                    error.key().unwrap_or_default(),
+                    error.message().unwrap_or_default(),
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::Tenant;
+
+/// How long the tenant table is cached before it is read again
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+struct TenantCache {
+    loaded_at: Instant,
+    tenants: Vec<Arc<Tenant>>,
+}
+
+/// Tenant lookup by ID and by host name.
+///
+/// Tenants change rarely and are resolved on almost every request, so the
+/// whole table is cached and re-read every `CACHE_TTL`.
+pub struct TenantService {
+    db: PgPool,
+    default_slug: String,
+    cache: RwLock<Option<TenantCache>>,
+}
+
+impl TenantService {
+    /// Create the tenant service
+    pub fn new(config: &Config, db: PgPool) -> Self {
+        Self {
+            db,
+            default_slug: config.default_tenant.clone(),
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Drop the cached tenants, so changes made by this instance apply at once
+    pub async fn invalidate(&self) {
+        *self.cache.write().await = None;
+    }
+
+    /// Find a tenant by ID
+    pub async fn get(&self, id: Uuid) -> Result<Option<Arc<Tenant>>, sqlx::Error> {
+        Ok(self.tenants().await?.into_iter().find(|tenant| tenant.id == id))
+    }
+
+    /// Find a tenant by slug
+    pub async fn by_slug(&self, slug: &str) -> Result<Option<Arc<Tenant>>, sqlx::Error> {
+        Ok(self.tenants().await?.into_iter().find(|tenant| tenant.slug == slug))
+    }
+
+    /// Find the tenant a host name is assigned to
+    pub async fn by_host(&self, host: &str) -> Result<Option<Arc<Tenant>>, sqlx::Error> {
+        Ok(self
+            .tenants()
+            .await?
+            .into_iter()
+            .find(|tenant| tenant.hostnames.iter().any(|h| h.eq_ignore_ascii_case(host))))
+    }
+
+    /// Tenant of a request without a token: the one assigned to its host
+    /// name, or the default tenant
+    pub async fn resolve(&self, host: Option<&str>) -> Result<Option<Arc<Tenant>>, sqlx::Error> {
+        if let Some(host) = host {
+            if let Some(tenant) = self.by_host(host).await? {
+                return Ok(Some(tenant));
+            }
+        }
+
+        Ok(self
+            .tenants()
+            .await?
+            .into_iter()
+            .find(|tenant| tenant.slug == self.default_slug))
+    }
+
+    async fn tenants(&self) -> Result<Vec<Arc<Tenant>>, sqlx::Error> {
+        if let Some(cache) = self.cache.read().await.as_ref() {
+            if cache.loaded_at.elapsed() < CACHE_TTL {
+                return Ok(cache.tenants.clone());
+            }
+        }
+
+        let tenants: Vec<Arc<Tenant>> = sqlx::query_as::<_, Tenant>("SELECT * FROM tenants")
+            .fetch_all(&self.db)
+            .await?
+            .into_iter()
+            .map(Arc::new)
+            .collect();
+
+        *self.cache.write().await = Some(TenantCache {
+            loaded_at: Instant::now(),
+            tenants: tenants.clone(),
+        });
+
+        Ok(tenants)
+    }
+}
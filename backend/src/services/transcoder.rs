@@ -1,15 +1,20 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
 use redis::{AsyncCommands, Client as RedisClient};
 use sqlx::PgPool;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
+use tracing::Instrument;
 use uuid::Uuid;
 
-use crate::config::Config;
-use crate::models::{Resolution, TranscodeJobMessage};
-use crate::services::StorageService;
+use crate::config::{Config, HlsEncryption};
+use crate::models::{Resolution, TranscodeJobMessage, WebhookEvent};
+use crate::services::keys::{common_pssh, encrypt_segment, ContentKey, StoredKey};
+use crate::services::playback::rewrite_playlist_uris;
+use crate::services::webhooks;
+use crate::services::{KeyService, QuotaService, StorageService, TenantService};
 
 /// Resolution configuration for transcoding
 #[derive(Debug, Clone)]
@@ -58,8 +63,12 @@ pub struct TranscoderService {
     redis_client: RedisClient,
     ffmpeg_path: String,
     ffprobe_path: String,
+    packager_path: String,
     temp_dir: PathBuf,
     storage: Arc<StorageService>,
+    tenants: Arc<TenantService>,
+    quotas: Arc<QuotaService>,
+    keys: Arc<KeyService>,
     db: PgPool,
 }
 
@@ -68,6 +77,9 @@ impl TranscoderService {
     pub async fn new(
         config: &Config,
         storage: Arc<StorageService>,
+        tenants: Arc<TenantService>,
+        quotas: Arc<QuotaService>,
+        keys: Arc<KeyService>,
         db: PgPool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let redis_client = RedisClient::open(config.redis_url.as_str())?;
@@ -80,8 +92,12 @@ impl TranscoderService {
             redis_client,
             ffmpeg_path: config.ffmpeg_path.clone(),
             ffprobe_path: config.ffprobe_path.clone(),
+            packager_path: config.packager_path.clone(),
             temp_dir,
             storage,
+            tenants,
+            quotas,
+            keys,
             db,
         })
     }
@@ -136,24 +152,56 @@ impl TranscoderService {
         };
 
         let job: TranscodeJobMessage = serde_json::from_str(&job_json)?;
+
+        // Log the job with the request that queued it
+        let span = tracing::info_span!(
+            "transcode",
+            video_id = %job.video_id,
+            request_id = job.request_id.as_deref().unwrap_or("-"),
+        );
+        self.process_job(job).instrument(span).await
+    }
+
+    /// Transcode the video of a job and record the outcome
+    async fn process_job(
+        &self,
+        job: TranscodeJobMessage,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
         let video_id = job.video_id;
 
-        // Update status to processing
-        sqlx::query("UPDATE videos SET status = 'processing' WHERE id = $1")
-            .bind(video_id)
-            .execute(&self.db)
-            .await?;
+        // Update status to processing, skipping videos deleted while queued
+        let mut tx = self.db.begin().await?;
+        let claimed = sqlx::query_scalar::<_, Option<Uuid>>(
+            r#"
+            UPDATE videos SET status = 'processing'
+            WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL
+            RETURNING owner_id
+            "#,
+        )
+        .bind(video_id)
+        .bind(job.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(owner_id) = claimed else {
+            tracing::info!("Skipping transcoding for deleted video: {}", video_id);
+            return Ok(None);
+        };
+        webhooks::emit(&mut tx, video_id, WebhookEvent::Processing).await?;
+        tx.commit().await?;
 
         // Process the video
-        match self.transcode_video(&job).await {
-            Ok(resolutions) => {
+        match self.transcode_video(&job, owner_id).await {
+            Ok((resolutions, rendition_size)) => {
                 // Update video with success
+                let mut tx = self.db.begin().await?;
                 sqlx::query(
                     r#"
                     UPDATE videos 
                     SET status = 'ready', 
                         resolutions = $2,
                         manifest_path = $3,
+                        rendition_size = $4,
                         processed_at = CURRENT_TIMESTAMP
                     WHERE id = $1
                     "#,
@@ -161,11 +209,15 @@ impl TranscoderService {
                 .bind(video_id)
                 .bind(sqlx::types::Json(resolutions))
                 .bind(format!("{}/master.m3u8", video_id))
-                .execute(&self.db)
+                .bind(rendition_size)
+                .execute(&mut *tx)
                 .await?;
+                webhooks::emit(&mut tx, video_id, WebhookEvent::Ready).await?;
+                tx.commit().await?;
             }
             Err(e) => {
                 // Update video with failure
+                let mut tx = self.db.begin().await?;
                 sqlx::query(
                     r#"
                     UPDATE videos 
@@ -176,8 +228,10 @@ impl TranscoderService {
                 )
                 .bind(video_id)
                 .bind(e.to_string())
-                .execute(&self.db)
+                .execute(&mut *tx)
                 .await?;
+                webhooks::emit(&mut tx, video_id, WebhookEvent::Failed).await?;
+                tx.commit().await?;
 
                 return Err(e);
             }
@@ -186,17 +240,29 @@ impl TranscoderService {
         Ok(Some(video_id))
     }
 
-    /// Transcode a video to HLS with multiple resolutions
+    /// Transcode a video to HLS with multiple resolutions, returning the
+    /// renditions and the number of bytes stored for them
+    ///
+    /// Fails before uploading anything that would take the owner or tenant
+    /// over their duration or rendition storage quota.
     async fn transcode_video(
         &self,
         job: &TranscodeJobMessage,
-    ) -> Result<Vec<Resolution>, Box<dyn std::error::Error + Send + Sync>> {
+        owner_id: Option<Uuid>,
+    ) -> Result<(Vec<Resolution>, i64), Box<dyn std::error::Error + Send + Sync>> {
         let video_id = job.video_id;
+        let tenant = self
+            .tenants
+            .get(job.tenant_id)
+            .await?
+            .ok_or_else(|| format!("Tenant {} not found", job.tenant_id))?;
+        let storage = self.storage.for_tenant(&tenant);
+
         let work_dir = self.temp_dir.join(video_id.to_string());
         tokio::fs::create_dir_all(&work_dir).await?;
 
         // Download original video
-        let original_data = self.storage.download_original(&job.original_path).await?;
+        let original_data = storage.download_original(&job.original_path).await?;
         let input_path = work_dir.join("original");
         tokio::fs::write(&input_path, &original_data).await?;
 
@@ -210,7 +276,12 @@ impl TranscoderService {
             .execute(&self.db)
             .await?;
 
+        self.quotas.check_duration(job.tenant_id, owner_id, duration).await?;
+
         let mut resolutions = Vec::new();
+        let mut encoded_bytes = 0;
+        let mut rendition_size = 0;
+        let common_encryption = self.keys.method().is_common_encryption();
 
         // Transcode each requested resolution
         for res_name in &job.resolutions {
@@ -220,13 +291,28 @@ impl TranscoderService {
                 let output_dir = work_dir.join(&res_config.name);
                 tokio::fs::create_dir_all(&output_dir).await?;
 
-                // Run FFmpeg for this resolution
-                self.run_ffmpeg_hls(&input_path, &output_dir, &res_config)
+                // Run FFmpeg for this resolution; segments are referenced by
+                // absolute URL because playlists and segments live in
+                // different buckets
+                let segment_prefix = format!("{}/{}", video_id, res_config.name);
+                let segment_base_url = format!("/segments/{}/", segment_prefix);
+                self.run_ffmpeg_hls(&input_path, &output_dir, &res_config, &segment_base_url)
                     .await?;
 
-                // Upload segments to MinIO
-                let segment_prefix = format!("{}/{}", video_id, res_config.name);
-                self.upload_segments(&output_dir, &segment_prefix).await?;
+                if self.keys.method() == HlsEncryption::Aes128 {
+                    self.encrypt_rendition(&output_dir, video_id).await?;
+                }
+
+                encoded_bytes += dir_size(&output_dir).await?;
+                self.quotas
+                    .check_renditions(job.tenant_id, owner_id, video_id, encoded_bytes)
+                    .await?;
+
+                // Upload segments to storage; CENC renditions are uploaded
+                // once packaged
+                if !common_encryption {
+                    rendition_size += self.upload_segments(&storage, &output_dir, &segment_prefix).await?;
+                }
 
                 resolutions.push(Resolution {
                     name: res_config.name.clone(),
@@ -238,22 +324,37 @@ impl TranscoderService {
             }
         }
 
-        // Generate and upload master playlist
-        let master_playlist = self.generate_master_playlist(&resolutions, &video_id.to_string());
-        self.storage
-            .upload_manifest(
-                &format!("{}/master.m3u8", video_id),
-                master_playlist.into_bytes(),
-            )
-            .await?;
+        if common_encryption {
+            let with_audio = self.has_audio_stream(&input_path).await?;
+            rendition_size += self
+                .package_cmaf(&storage, &work_dir, video_id, &resolutions, with_audio)
+                .await?;
+        } else {
+            // Generate and upload master playlist
+            let master_playlist =
+                self.generate_master_playlist(&resolutions, &video_id.to_string());
+            rendition_size += master_playlist.len() as i64;
+            storage
+                .upload_manifest(
+                    &format!("{}/master.m3u8", video_id),
+                    master_playlist.into_bytes(),
+                )
+                .await?;
+        }
 
         // Generate thumbnail
-        self.generate_thumbnail(&input_path, &work_dir, video_id).await?;
+        rendition_size += self.generate_thumbnail(&storage, &input_path, &work_dir, video_id).await?;
+
+        sqlx::query("UPDATE videos SET encryption = $2 WHERE id = $1")
+            .bind(video_id)
+            .bind(self.keys.method().as_str())
+            .execute(&self.db)
+            .await?;
 
         // Cleanup temp files
         tokio::fs::remove_dir_all(&work_dir).await?;
 
-        Ok(resolutions)
+        Ok((resolutions, rendition_size))
     }
 
     /// Get video duration using ffprobe
@@ -284,12 +385,36 @@ impl TranscoderService {
         Ok(duration)
     }
 
+    /// Check whether the input has an audio stream using ffprobe
+    async fn has_audio_stream(
+        &self,
+        input_path: &Path,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let output = Command::new(&self.ffprobe_path)
+            .args([
+                "-v", "quiet",
+                "-select_streams", "a",
+                "-show_entries", "stream=index",
+                "-of", "csv=p=0",
+            ])
+            .arg(input_path)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err("ffprobe failed".into());
+        }
+
+        Ok(!output.stdout.trim_ascii().is_empty())
+    }
+
     /// Run FFmpeg to create HLS segments with VP9 codec
     async fn run_ffmpeg_hls(
         &self,
         input_path: &Path,
         output_dir: &Path,
         config: &ResolutionConfig,
+        segment_base_url: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let playlist_path = output_dir.join("playlist.m3u8");
         let segment_pattern = output_dir.join("segment_%03d.webm");
@@ -322,6 +447,7 @@ impl TranscoderService {
                 "-hls_list_size", "0",
                 "-hls_segment_type", "fmp4",
                 "-hls_segment_filename", segment_pattern.to_str().unwrap(),
+                "-hls_base_url", segment_base_url,
                 "-hls_flags", "independent_segments",
                 // Output
                 playlist_path.to_str().unwrap(),
@@ -349,13 +475,204 @@ impl TranscoderService {
         Ok(())
     }
 
-    /// Upload all segments from a directory
+    /// Encrypt the segments of a rendition in place with AES-128 and add
+    /// `EXT-X-KEY` tags to its playlist.
+    ///
+    /// The tag is emitted whenever the key index changes (see
+    /// `KeyService::key_index`) and after `EXT-X-MAP`, so the initialization
+    /// section stays in clear. Key URIs point at the key server route.
+    async fn encrypt_rendition(
+        &self,
+        output_dir: &Path,
+        video_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let playlist_path = output_dir.join("playlist.m3u8");
+        let playlist = tokio::fs::read_to_string(&playlist_path).await?;
+
+        let mut output = String::with_capacity(playlist.len() + 256);
+        let mut sequence: u64 = 0;
+        let mut current_key: Option<(i32, ContentKey)> = None;
+
+        for line in playlist.lines() {
+            let trimmed = line.trim();
+
+            if let Some(value) = trimmed.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                sequence = value.trim().parse().unwrap_or(0);
+            } else if trimmed.starts_with("#EXTINF") {
+                let key_index = self.keys.key_index(sequence);
+                if current_key.map(|(index, _)| index) != Some(key_index) {
+                    let StoredKey { key, .. } = self.keys.get_or_create(video_id, key_index).await?;
+                    output.push_str(&format!(
+                        "#EXT-X-KEY:METHOD=AES-128,URI=\"/keys/{}/{}\"\n",
+                        video_id, key_index
+                    ));
+                    current_key = Some((key_index, key));
+                }
+            } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                let (_, key) = current_key.ok_or("Segment without a preceding EXTINF tag")?;
+                let filename = trimmed.rsplit('/').next().unwrap_or(trimmed);
+                let segment_path = output_dir.join(filename);
+
+                let data = tokio::fs::read(&segment_path).await?;
+                tokio::fs::write(&segment_path, encrypt_segment(&data, &key, sequence)).await?;
+                sequence += 1;
+            }
+
+            output.push_str(line);
+            output.push('\n');
+        }
+
+        tokio::fs::write(&playlist_path, output).await?;
+        Ok(())
+    }
+
+    /// Package the renditions as CMAF with common encryption (CENC) and
+    /// upload the result, returning the bytes uploaded.
+    ///
+    /// The fMP4 output of `run_ffmpeg_hls` is joined back into one fragmented
+    /// MP4 per rendition and handed to Shaka Packager, which encrypts video
+    /// per rendition and audio once, from the highest rendition, with key 0
+    /// of the video. The packager writes the common PSSH box into the init
+    /// segments and the DASH manifest; the HLS playlists signal the same box
+    /// as a Clear Key `data:` URI.
+    async fn package_cmaf(
+        &self,
+        storage: &StorageService,
+        work_dir: &Path,
+        video_id: Uuid,
+        resolutions: &[Resolution],
+        with_audio: bool,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let scheme = self.keys.method().as_str().ok_or("Encryption is disabled")?;
+        let content_key = self.keys.get_or_create(video_id, 0).await?;
+        let package_dir = work_dir.join("cmaf");
+
+        let mut streams = Vec::new();
+        let mut tracks = Vec::new();
+        for res in resolutions {
+            let input = self.join_fmp4(&work_dir.join(&res.name)).await?;
+            streams.push(format!(
+                "in={},stream=video,init_segment={name}/init.mp4,segment_template={name}/$Number$.m4s,playlist_name={name}/playlist.m3u8",
+                input.display(),
+                name = res.name,
+            ));
+            tracks.push(res.name.clone());
+        }
+
+        if with_audio {
+            let source = resolutions.last().ok_or("No rendition to take audio from")?;
+            streams.push(format!(
+                "in={},stream=audio,init_segment=audio/init.mp4,segment_template=audio/$Number$.m4s,playlist_name=audio/playlist.m3u8,hls_group_id=audio,hls_name=Audio",
+                work_dir.join(&source.name).join("source.mp4").display(),
+            ));
+            tracks.push("audio".to_string());
+        }
+
+        for track in &tracks {
+            tokio::fs::create_dir_all(package_dir.join(track)).await?;
+        }
+
+        let output = Command::new(&self.packager_path)
+            .current_dir(&package_dir)
+            .args(&streams)
+            .args([
+                "--segment_duration", "4",
+                "--protection_scheme", scheme,
+                "--enable_raw_key_encryption",
+                "--keys",
+                &format!(
+                    "label=:key_id={}:key={}",
+                    content_key.key_id.simple(),
+                    hex::encode(content_key.key)
+                ),
+                "--protection_systems", "CommonSystem",
+                "--clear_lead", "0",
+                "--hls_master_playlist_output", "master.m3u8",
+                "--mpd_output", "manifest.mpd",
+                "--base_urls", &format!("/segments/{}/", video_id),
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Packager failed with status {}: {}",
+                output.status,
+                stderr.lines().last().unwrap_or_default()
+            )
+            .into());
+        }
+
+        // Segments live in another bucket than the playlists, so point every
+        // URI at its route explicitly
+        let key_tag = clear_key_tag(self.keys.method(), &content_key);
+        for track in &tracks {
+            let playlist_path = package_dir.join(track).join("playlist.m3u8");
+            let playlist = tokio::fs::read_to_string(&playlist_path).await?;
+            let base_url = format!("/segments/{}/{}/", video_id, track);
+            let playlist = rewrite_playlist_uris(&playlist, |uri| format!("{}{}", base_url, uri));
+            tokio::fs::write(&playlist_path, signal_clear_key(&playlist, &key_tag)).await?;
+        }
+
+        let master_path = package_dir.join("master.m3u8");
+        let master = tokio::fs::read_to_string(&master_path).await?;
+        let master = rewrite_playlist_uris(&master, |uri| format!("/manifests/{}/{}", video_id, uri));
+        tokio::fs::write(&master_path, master).await?;
+
+        let mut uploaded = self.upload_segments(storage, &package_dir, &video_id.to_string()).await?;
+        for track in &tracks {
+            uploaded += self
+                .upload_segments(storage, &package_dir.join(track), &format!("{}/{}", video_id, track))
+                .await?;
+        }
+
+        Ok(uploaded)
+    }
+
+    /// Concatenate the init section and segments of an fMP4 HLS rendition
+    /// into a single fragmented MP4, `source.mp4`, in the same directory
+    async fn join_fmp4(
+        &self,
+        output_dir: &Path,
+    ) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let playlist = tokio::fs::read_to_string(output_dir.join("playlist.m3u8")).await?;
+        let filename = |uri: &str| uri.rsplit('/').next().unwrap_or(uri).to_string();
+
+        let mut parts = Vec::new();
+        for line in playlist.lines().map(str::trim) {
+            if let Some(map) = line.strip_prefix("#EXT-X-MAP:") {
+                let uri = map
+                    .split_once("URI=\"")
+                    .and_then(|(_, rest)| rest.split_once('"'))
+                    .map(|(uri, _)| uri)
+                    .ok_or("EXT-X-MAP without a URI")?;
+                parts.push(filename(uri));
+            } else if !line.is_empty() && !line.starts_with('#') {
+                parts.push(filename(line));
+            }
+        }
+
+        let source_path = output_dir.join("source.mp4");
+        let mut source = tokio::fs::File::create(&source_path).await?;
+        for part in parts {
+            let data = tokio::fs::read(output_dir.join(part)).await?;
+            source.write_all(&data).await?;
+        }
+        source.flush().await?;
+
+        Ok(source_path)
+    }
+
+    /// Upload all segments from a directory, returning the bytes uploaded
     async fn upload_segments(
         &self,
+        storage: &StorageService,
         output_dir: &Path,
         prefix: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
         let mut entries = tokio::fs::read_dir(output_dir).await?;
+        let mut uploaded = 0;
 
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
@@ -365,6 +682,8 @@ impl TranscoderService {
 
                 let content_type = if filename.ends_with(".m3u8") {
                     "application/vnd.apple.mpegurl"
+                } else if filename.ends_with(".mpd") {
+                    "application/dash+xml"
                 } else if filename.ends_with(".webm") {
                     "video/webm"
                 } else if filename.ends_with(".m4s") || filename.ends_with(".mp4") {
@@ -373,20 +692,22 @@ impl TranscoderService {
                     "application/octet-stream"
                 };
 
-                // Upload playlist to manifests bucket, segments to segments bucket
-                if filename.ends_with(".m3u8") {
-                    self.storage
-                        .upload_manifest_from_file(&key, &path)
+                // Upload playlists and DASH manifests to manifests bucket,
+                // segments to segments bucket
+                if filename.ends_with(".m3u8") || filename.ends_with(".mpd") {
+                    storage
+                        .upload_manifest_from_file(&key, &path, content_type)
                         .await?;
                 } else {
-                    self.storage
+                    storage
                         .upload_segment_from_file(&key, &path, content_type)
                         .await?;
                 }
+                uploaded += entry.metadata().await?.len() as i64;
             }
         }
 
-        Ok(())
+        Ok(uploaded)
     }
 
     /// Generate master HLS playlist
@@ -399,19 +720,21 @@ impl TranscoderService {
                 "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}x{},NAME=\"{}\"\n",
                 bandwidth, res.width, res.height, res.name
             ));
-            playlist.push_str(&format!("/segments/{}/{}/playlist.m3u8\n\n", video_id, res.name));
+            // Variant playlists sit next to the master playlist in the manifests bucket
+            playlist.push_str(&format!("/manifests/{}/{}/playlist.m3u8\n\n", video_id, res.name));
         }
 
         playlist
     }
 
-    /// Generate video thumbnail
+    /// Generate video thumbnail, returning its size; 0 when none could be extracted
     async fn generate_thumbnail(
         &self,
+        storage: &StorageService,
         input_path: &Path,
         work_dir: &Path,
         video_id: Uuid,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
         let thumbnail_path = work_dir.join("thumbnail.jpg");
 
         let status = Command::new(&self.ffmpeg_path)
@@ -428,7 +751,7 @@ impl TranscoderService {
 
         if status.success() && thumbnail_path.exists() {
             let key = format!("{}/thumbnail.jpg", video_id);
-            self.storage
+            storage
                 .upload_segment_from_file(&key, &thumbnail_path, "image/jpeg")
                 .await?;
 
@@ -437,8 +760,60 @@ impl TranscoderService {
                 .bind(&key)
                 .execute(&self.db)
                 .await?;
+
+            return Ok(tokio::fs::metadata(&thumbnail_path).await?.len() as i64);
         }
 
-        Ok(())
+        Ok(0)
+    }
+}
+
+/// Total size of the files directly in a directory
+async fn dir_size(dir: &Path) -> std::io::Result<i64> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut size = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if metadata.is_file() {
+            size += metadata.len() as i64;
+        }
+    }
+    Ok(size)
+}
+
+/// `EXT-X-KEY` tag signalling Clear Key common encryption with its PSSH box
+fn clear_key_tag(method: HlsEncryption, content_key: &StoredKey) -> String {
+    let hls_method = match method {
+        HlsEncryption::Cbcs => "SAMPLE-AES",
+        _ => "SAMPLE-AES-CTR",
+    };
+
+    format!(
+        "#EXT-X-KEY:METHOD={},URI=\"data:text/plain;base64,{}\",KEYID=0x{},KEYFORMAT=\"org.w3.clearkey\",KEYFORMATVERSIONS=\"1\"",
+        hls_method,
+        STANDARD.encode(common_pssh(&[content_key.key_id])),
+        content_key.key_id.simple(),
+    )
+}
+
+/// Replace the `EXT-X-KEY` tags of a packaged playlist with a single Clear Key tag
+fn signal_clear_key(playlist: &str, key_tag: &str) -> String {
+    let mut output = String::with_capacity(playlist.len() + key_tag.len());
+    let mut signalled = false;
+
+    for line in playlist.lines() {
+        if line.starts_with("#EXT-X-KEY:") {
+            if !signalled {
+                output.push_str(key_tag);
+                output.push('\n');
+                signalled = true;
+            }
+            continue;
+        }
+
+        output.push_str(line);
+        output.push('\n');
     }
+
+    output
 }
@@ -0,0 +1,302 @@
+use chrono::Utc;
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{WebhookDeliveryStatus, WebhookEvent};
+use crate::services::jobs::{retry_at, run_worker};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a claimed delivery is hidden from other dispatcher passes
+const CLAIM_LEASE_SECS: f64 = 300.0;
+
+/// Longest part of an error response body kept in the delivery log
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// Queue `event` of a video for every active webhook of its tenant that
+/// subscribes to it.
+///
+/// Deliveries are written to the outbox on `conn`, so when called inside
+/// the transaction making the change they are sent if and only if it
+/// commits. The payload describes the video as of that transaction.
+pub async fn emit(
+    conn: &mut PgConnection,
+    video_id: Uuid,
+    event: WebhookEvent,
+) -> Result<(), sqlx::Error> {
+    let queued = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, event_id, payload)
+        SELECT w.id, $2, $3, jsonb_build_object(
+            'id', $3,
+            'event', $2,
+            'created_at', CURRENT_TIMESTAMP,
+            'data', jsonb_build_object('video', jsonb_build_object(
+                'id', v.id,
+                'title', v.title,
+                'status', v.status,
+                'visibility', v.visibility,
+                'owner_id', v.owner_id,
+                'duration_seconds', v.duration_seconds,
+                'resolutions', (
+                    SELECT COALESCE(jsonb_agg(r->'name'), '[]'::jsonb)
+                    FROM jsonb_array_elements(COALESCE(v.resolutions, '[]'::jsonb)) AS r
+                ),
+                'error_message', v.error_message,
+                'version', v.version,
+                'created_at', v.created_at,
+                'updated_at', v.updated_at,
+                'processed_at', v.processed_at,
+                'deleted_at', v.deleted_at
+            ))
+        )
+        FROM videos v
+        JOIN webhooks w ON w.tenant_id = v.tenant_id
+        WHERE v.id = $1 AND w.active AND (cardinality(w.events) = 0 OR $2 = ANY(w.events))
+        "#,
+    )
+    .bind(video_id)
+    .bind(event.as_str())
+    .bind(Uuid::new_v4())
+    .execute(conn)
+    .await?;
+
+    if queued.rows_affected() > 0 {
+        tracing::debug!(
+            "Queued {} for video {} to {} webhooks",
+            event.as_str(),
+            video_id,
+            queued.rows_affected()
+        );
+    }
+    Ok(())
+}
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}` under a webhook's secret, sent as
+/// `X-Webhook-Signature: sha256=<hex>`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Delivery claimed from the outbox, with its webhook's endpoint
+#[derive(FromRow)]
+struct PendingDelivery {
+    id: Uuid,
+    event: String,
+    payload: serde_json::Value,
+    url: String,
+    secret: String,
+}
+
+/// Failed attempt: the response status, if any, and what went wrong
+type DeliveryError = (Option<i32>, String);
+
+/// Background worker sending the webhook outbox
+///
+/// Pending deliveries are POSTed to their webhook; anything but a 2xx
+/// response is retried with exponential backoff until the delivery runs out
+/// of attempts and is marked failed. Deliveries of disabled webhooks wait
+/// until the webhook is enabled again.
+pub struct WebhookDispatcher {
+    db: PgPool,
+    client: reqwest::Client,
+    interval: Duration,
+    batch_size: i64,
+    max_attempts: i32,
+}
+
+impl WebhookDispatcher {
+    /// Create a new webhook dispatcher
+    pub fn new(config: &Config, db: PgPool) -> Result<Self, reqwest::Error> {
+        // Redirects are not followed, so a delivery only ever reaches the
+        // configured URL
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.webhook_timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!("videostream-webhooks/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        Ok(Self {
+            db,
+            client,
+            interval: Duration::from_secs(config.webhook_interval_secs),
+            batch_size: config.webhook_batch_size,
+            max_attempts: config.webhook_max_attempts,
+        })
+    }
+
+    /// Start the background worker that sends pending deliveries
+    pub async fn start_worker(self: Arc<Self>) {
+        let this = &self;
+        run_worker("webhook delivery", self.interval, move || async move {
+            let delivered = this.dispatch().await?;
+            if delivered > 0 {
+                tracing::info!("Delivered {} webhook events", delivered);
+            }
+            Ok::<_, sqlx::Error>(false)
+        })
+        .await
+    }
+
+    /// Send one batch of deliveries concurrently, returning how many succeeded
+    async fn dispatch(&self) -> Result<usize, sqlx::Error> {
+        let deliveries = self.claim_batch().await?;
+        let results = join_all(deliveries.iter().map(|delivery| self.deliver(delivery))).await;
+        let mut delivered = 0;
+
+        for (delivery, result) in deliveries.iter().zip(results) {
+            match result {
+                Ok(status) => {
+                    self.mark_delivered(delivery.id, status).await?;
+                    delivered += 1;
+                }
+                Err((status, error)) => {
+                    tracing::warn!(
+                        "Webhook delivery {} to {} failed: {}",
+                        delivery.id,
+                        delivery.url,
+                        error
+                    );
+                    self.schedule_retry(delivery.id, status, &error).await?;
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// POST a delivery to its webhook, returning the response status
+    async fn deliver(&self, delivery: &PendingDelivery) -> Result<i32, DeliveryError> {
+        let body = serde_json::to_vec(&delivery.payload).map_err(|e| (None, e.to_string()))?;
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(&delivery.secret, timestamp, &body);
+
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Webhook-Delivery", delivery.id.to_string())
+            .header("X-Webhook-Event", &delivery.event)
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(status.as_u16() as i32);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let excerpt: String = body.chars().take(MAX_ERROR_BODY_CHARS).collect();
+        Err((Some(status.as_u16() as i32), format!("HTTP {}: {}", status, excerpt.trim())))
+    }
+
+    /// Claim a batch of pending deliveries that are due.
+    ///
+    /// Claimed rows get a lease in `next_attempt_at` so concurrent
+    /// dispatchers skip them; a dispatcher dying mid-delivery leaves the
+    /// delivery to be retried once the lease runs out.
+    async fn claim_batch(&self) -> Result<Vec<PendingDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, PendingDelivery>(
+            r#"
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+            FROM webhooks w
+            WHERE w.id = d.webhook_id AND d.id IN (
+                SELECT pending.id FROM webhook_deliveries pending
+                JOIN webhooks active ON active.id = pending.webhook_id
+                WHERE pending.status = 'pending'
+                  AND pending.next_attempt_at <= CURRENT_TIMESTAMP
+                  AND active.active
+                ORDER BY pending.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF pending SKIP LOCKED
+            )
+            RETURNING d.id, d.event, d.payload, w.url, w.secret
+            "#,
+        )
+        .bind(self.batch_size)
+        .bind(CLAIM_LEASE_SECS)
+        .fetch_all(&self.db)
+        .await
+    }
+
+    /// Record a successful delivery
+    async fn mark_delivered(&self, id: Uuid, response_status: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'delivered',
+                attempts = attempts + 1,
+                last_attempt_at = CURRENT_TIMESTAMP,
+                response_status = $2,
+                last_error = NULL,
+                delivered_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(response_status)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt and push the next one out with exponential
+    /// backoff, or give up once the delivery is out of attempts
+    async fn schedule_retry(
+        &self,
+        id: Uuid,
+        response_status: Option<i32>,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let updated = sqlx::query_as::<_, (i32, WebhookDeliveryStatus)>(&format!(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1,
+                last_attempt_at = CURRENT_TIMESTAMP,
+                response_status = $2,
+                last_error = $3,
+                status = CASE WHEN attempts + 1 >= $4
+                              THEN 'failed'::webhook_delivery_status ELSE status END,
+                next_attempt_at = {}
+            WHERE id = $1
+            RETURNING attempts, status
+            "#,
+            retry_at("attempts")
+        ))
+        .bind(id)
+        .bind(response_status)
+        .bind(error)
+        .bind(self.max_attempts)
+        .fetch_optional(&self.db)
+        .await?;
+
+        match updated {
+            Some((attempts, WebhookDeliveryStatus::Failed)) => {
+                tracing::warn!("Webhook delivery {} failed after {} attempts", id, attempts)
+            }
+            Some((attempts, _)) => {
+                tracing::info!("Scheduled retry of webhook delivery {} (attempt {})", id, attempts)
+            }
+            // The webhook was deleted in the meantime
+            None => {}
+        }
+        Ok(())
+    }
+}
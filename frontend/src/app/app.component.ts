@@ -1,10 +1,12 @@
 import { Component } from '@angular/core';
-import { RouterOutlet, RouterLink, RouterLinkActive } from '@angular/router';
+import { CommonModule } from '@angular/common';
+import { Router, RouterOutlet, RouterLink, RouterLinkActive } from '@angular/router';
+import { AuthService } from './services/auth.service';
 
 @Component({
     selector: 'app-root',
     standalone: true,
-    imports: [RouterOutlet, RouterLink, RouterLinkActive],
+    imports: [CommonModule, RouterOutlet, RouterLink, RouterLinkActive],
     template: `
     <div class="app-layout">
       <!-- Header -->
@@ -25,6 +27,18 @@ import { RouterOutlet, RouterLink, RouterLinkActive } from '@angular/router';
                 <span class="nav-icon">⬆️</span>
                 Upload
               </a>
+              <ng-container *ngIf="auth.user$ | async as user; else loggedOut">
+                <a href="#" class="nav-link" (click)="logout($event)">
+                  <span class="nav-icon">👤</span>
+                  {{ user.username }} · Log out
+                </a>
+              </ng-container>
+              <ng-template #loggedOut>
+                <a routerLink="/login" routerLinkActive="active" class="nav-link">
+                  <span class="nav-icon">🔑</span>
+                  Log in
+                </a>
+              </ng-template>
             </nav>
           </div>
         </div>
@@ -158,4 +172,17 @@ import { RouterOutlet, RouterLink, RouterLinkActive } from '@angular/router';
 })
 export class AppComponent {
     title = 'VideoStream';
+
+    constructor(
+        public auth: AuthService,
+        private router: Router
+    ) { }
+
+    logout(event: Event) {
+        event.preventDefault();
+        this.auth.logout().subscribe({
+            complete: () => this.router.navigate(['/']),
+            error: () => this.router.navigate(['/']),
+        });
+    }
 }
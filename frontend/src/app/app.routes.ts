@@ -29,6 +29,13 @@ export const routes: Routes = [
                 (m) => m.VideoEditComponent
             ),
     },
+    {
+        path: 'login',
+        loadComponent: () =>
+            import('./components/login/login.component').then(
+                (m) => m.LoginComponent
+            ),
+    },
     {
         path: '**',
         redirectTo: '',
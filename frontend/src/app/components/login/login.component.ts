@@ -0,0 +1,172 @@
+import { Component } from '@angular/core';
+import { CommonModule } from '@angular/common';
+import { FormsModule } from '@angular/forms';
+import { ActivatedRoute, Router } from '@angular/router';
+import { AuthService } from '../../services/auth.service';
+
+@Component({
+    selector: 'app-login',
+    standalone: true,
+    imports: [CommonModule, FormsModule],
+    template: `
+    <div class="login-page">
+      <form class="login-form" (ngSubmit)="submit()">
+        <h1>{{ mode === 'login' ? 'Log In' : 'Create Account' }}</h1>
+
+        <!-- Username -->
+        <div class="form-group">
+          <label class="form-label" for="username">
+            {{ mode === 'login' ? 'Username or email' : 'Username' }}
+          </label>
+          <input
+            type="text"
+            id="username"
+            class="form-input"
+            [(ngModel)]="username"
+            name="username"
+            autocomplete="username"
+            required />
+        </div>
+
+        <!-- Email (registration only) -->
+        <div *ngIf="mode === 'register'" class="form-group">
+          <label class="form-label" for="email">Email</label>
+          <input
+            type="email"
+            id="email"
+            class="form-input"
+            [(ngModel)]="email"
+            name="email"
+            autocomplete="email"
+            required />
+        </div>
+
+        <!-- Password -->
+        <div class="form-group">
+          <label class="form-label" for="password">Password</label>
+          <input
+            type="password"
+            id="password"
+            class="form-input"
+            [(ngModel)]="password"
+            name="password"
+            [autocomplete]="mode === 'login' ? 'current-password' : 'new-password'"
+            required />
+        </div>
+
+        <!-- Error Message -->
+        <div *ngIf="errorMessage" class="error-message">
+          <span>❌</span>
+          <p>{{ errorMessage }}</p>
+        </div>
+
+        <button
+          type="submit"
+          class="btn btn-primary"
+          [disabled]="submitting || !username || !password">
+          <span *ngIf="!submitting">{{ mode === 'login' ? 'Log In' : 'Create Account' }}</span>
+          <span *ngIf="submitting">Please wait...</span>
+        </button>
+
+        <button type="button" class="switch-mode" (click)="toggleMode()">
+          {{ mode === 'login' ? 'No account yet? Register' : 'Already registered? Log in' }}
+        </button>
+      </form>
+    </div>
+  `,
+    styles: [`
+    .login-page {
+      max-width: 420px;
+      margin: 0 auto;
+      padding: var(--spacing-xl) 0;
+    }
+
+    .login-form {
+      display: flex;
+      flex-direction: column;
+      gap: var(--spacing-md);
+      background: var(--color-bg-card);
+      border: 1px solid var(--color-border);
+      border-radius: var(--radius-xl);
+      padding: var(--spacing-xl);
+    }
+
+    .login-form h1 {
+      font-size: var(--font-size-2xl);
+      margin-bottom: var(--spacing-sm);
+    }
+
+    .error-message {
+      display: flex;
+      align-items: center;
+      gap: var(--spacing-md);
+      padding: var(--spacing-md);
+      border-radius: var(--radius-md);
+      background: rgba(239, 68, 68, 0.1);
+      border: 1px solid var(--color-error);
+      color: var(--color-error);
+    }
+
+    .switch-mode {
+      background: none;
+      border: none;
+      color: var(--color-text-secondary);
+      cursor: pointer;
+      font-size: var(--font-size-sm);
+    }
+
+    .switch-mode:hover {
+      color: var(--color-text-primary);
+    }
+  `],
+})
+export class LoginComponent {
+    mode: 'login' | 'register' = 'login';
+    username = '';
+    email = '';
+    password = '';
+    submitting = false;
+    errorMessage = '';
+
+    constructor(
+        private auth: AuthService,
+        private route: ActivatedRoute,
+        private router: Router
+    ) { }
+
+    toggleMode() {
+        this.mode = this.mode === 'login' ? 'register' : 'login';
+        this.errorMessage = '';
+    }
+
+    submit() {
+        this.submitting = true;
+        this.errorMessage = '';
+
+        if (this.mode === 'register') {
+            this.auth.register(this.username, this.email, this.password).subscribe({
+                next: () => this.logIn(),
+                error: (err) => this.fail(err),
+            });
+        } else {
+            this.logIn();
+        }
+    }
+
+    private logIn() {
+        this.auth.login(this.username, this.password).subscribe({
+            next: () => {
+                this.submitting = false;
+                const returnUrl = this.route.snapshot.queryParamMap.get('returnUrl') || '/';
+                this.router.navigateByUrl(returnUrl);
+            },
+            error: (err) => this.fail(err),
+        });
+    }
+
+    private fail(err: any) {
+        console.error('Authentication failed:', err);
+        this.submitting = false;
+        this.errorMessage = err.error?.error || 'Authentication failed';
+    }
+}
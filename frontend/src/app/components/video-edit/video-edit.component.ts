@@ -2,7 +2,7 @@ import { Component, OnInit, OnDestroy } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { ActivatedRoute, Router, RouterLink } from '@angular/router';
-import { VideoService, Video } from '../../services/video.service';
+import { VideoService, Video, Visibility } from '../../services/video.service';
 import { Subject, takeUntil } from 'rxjs';
 
 @Component({
@@ -61,6 +61,15 @@ import { Subject, takeUntil } from 'rxjs';
             </textarea>
           </div>
 
+          <div class="form-group">
+            <label class="form-label" for="visibility">Visibility</label>
+            <select id="visibility" class="form-input" [(ngModel)]="editVisibility" name="visibility">
+              <option value="public">Public</option>
+              <option value="unlisted">Unlisted (anyone with the link)</option>
+              <option value="private">Private (only you and people you share with)</option>
+            </select>
+          </div>
+
           <!-- Video Info (Read-only) -->
           <div class="video-info-section">
             <h3>Video Information</h3>
@@ -306,6 +315,7 @@ export class VideoEditComponent implements OnInit, OnDestroy {
 
     editTitle = '';
     editDescription = '';
+    editVisibility: Visibility = 'public';
     errorMessage = '';
     successMessage = '';
 
@@ -340,6 +350,7 @@ export class VideoEditComponent implements OnInit, OnDestroy {
                     this.video = video;
                     this.editTitle = video.title;
                     this.editDescription = video.description || '';
+                    this.editVisibility = video.visibility;
                     this.loading = false;
                 },
                 error: (err) => {
@@ -357,10 +368,15 @@ export class VideoEditComponent implements OnInit, OnDestroy {
         this.successMessage = '';
 
         this.videoService
-            .updateVideo(this.video.id, {
-                title: this.editTitle,
-                description: this.editDescription || undefined,
-            })
+            .updateVideo(
+                this.video.id,
+                {
+                    title: this.editTitle,
+                    description: this.editDescription || null,
+                    visibility: this.editVisibility,
+                },
+                this.video.version
+            )
             .pipe(takeUntil(this.destroy$))
             .subscribe({
                 next: (updatedVideo) => {
@@ -376,7 +392,10 @@ export class VideoEditComponent implements OnInit, OnDestroy {
                 error: (err) => {
                     console.error('Failed to save changes:', err);
                     this.saving = false;
-                    this.errorMessage = 'Failed to save changes. Please try again.';
+                    this.errorMessage =
+                        err.status === 412
+                            ? 'This video was changed elsewhere. Reload it before saving again.'
+                            : 'Failed to save changes. Please try again.';
                 },
             });
     }
@@ -298,7 +298,7 @@ export class VideoListComponent implements OnInit, OnDestroy {
     loading = true;
     totalVideos = 0;
     hasMore = false;
-    private offset = 0;
+    private nextCursor?: string;
     private limit = 12;
     private destroy$ = new Subject<void>();
     private refreshInterval$ = interval(10000); // Refresh every 10 seconds
@@ -326,13 +326,14 @@ export class VideoListComponent implements OnInit, OnDestroy {
     loadVideos() {
         this.loading = true;
         this.videoService
-            .getVideos(this.limit, this.offset)
+            .getVideos(this.limit)
             .pipe(takeUntil(this.destroy$))
             .subscribe({
                 next: (response) => {
                     this.videos = response.videos;
                     this.totalVideos = response.total;
-                    this.hasMore = this.videos.length < response.total;
+                    this.nextCursor = response.next_cursor;
+                    this.hasMore = !!response.next_cursor;
                     this.loading = false;
                 },
                 error: (err) => {
@@ -344,25 +345,27 @@ export class VideoListComponent implements OnInit, OnDestroy {
 
     refreshVideos() {
         this.videoService
-            .getVideos(this.limit, 0)
+            .getVideos(this.limit)
             .pipe(takeUntil(this.destroy$))
             .subscribe({
                 next: (response) => {
                     this.videos = response.videos;
                     this.totalVideos = response.total;
+                    this.nextCursor = response.next_cursor;
+                    this.hasMore = !!response.next_cursor;
                 },
             });
     }
 
     loadMore() {
-        this.offset += this.limit;
         this.videoService
-            .getVideos(this.limit, this.offset)
+            .getVideos(this.limit, this.nextCursor)
             .pipe(takeUntil(this.destroy$))
             .subscribe({
                 next: (response) => {
                     this.videos = [...this.videos, ...response.videos];
-                    this.hasMore = this.videos.length < response.total;
+                    this.nextCursor = response.next_cursor;
+                    this.hasMore = !!response.next_cursor;
                 },
             });
     }
@@ -323,6 +323,9 @@ export class VideoPlayerComponent implements OnInit, OnDestroy, AfterViewInit {
     levels: Hls.Level[] = [];
     currentLevel = -1;
 
+    // Clear Key license server of common-encrypted videos
+    private licenseUrl: string | null = null;
+
     // Math for template
     Math = Math;
 
@@ -365,6 +368,9 @@ export class VideoPlayerComponent implements OnInit, OnDestroy, AfterViewInit {
                     if (video.status === 'ready' && video.streaming_url) {
                         // Wait for view to initialize
                         setTimeout(() => this.initializePlayer(), 100);
+                    } else if (video.status === 'ready') {
+                        // Private and encrypted videos need a tokenized URL
+                        this.loadPlayback(id);
                     }
 
                     // Poll for status if still processing
@@ -380,6 +386,25 @@ export class VideoPlayerComponent implements OnInit, OnDestroy, AfterViewInit {
             });
     }
 
+    loadPlayback(id: string) {
+        this.videoService
+            .getPlayback(id)
+            .pipe(takeUntil(this.destroy$))
+            .subscribe({
+                next: (playback) => {
+                    if (this.video) {
+                        this.video.streaming_url = playback.streaming_url;
+                        this.licenseUrl = playback.license_url ?? null;
+                        setTimeout(() => this.initializePlayer(), 100);
+                    }
+                },
+                error: (err) => {
+                    console.error('Failed to get playback URL:', err);
+                    this.error = 'Video is not available for playback';
+                },
+            });
+    }
+
     pollStatus(id: string) {
         const interval = setInterval(() => {
             this.videoService
@@ -416,6 +441,12 @@ export class VideoPlayerComponent implements OnInit, OnDestroy, AfterViewInit {
                 enableWorker: true,
                 lowLatencyMode: false,
                 backBufferLength: 90,
+                ...(this.licenseUrl && {
+                    emeEnabled: true,
+                    drmSystems: {
+                        'org.w3.clearkey': { licenseUrl: this.licenseUrl },
+                    },
+                }),
             });
 
             this.hls!.loadSource(streamUrl);
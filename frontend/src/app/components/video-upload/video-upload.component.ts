@@ -2,7 +2,7 @@ import { Component } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { Router } from '@angular/router';
-import { VideoService, UploadProgress } from '../../services/video.service';
+import { VideoService, UploadProgress, Visibility } from '../../services/video.service';
 
 @Component({
     selector: 'app-video-upload',
@@ -77,6 +77,15 @@ import { VideoService, UploadProgress } from '../../services/video.service';
             </textarea>
           </div>
 
+          <div class="form-group">
+            <label class="form-label" for="visibility">Visibility</label>
+            <select id="visibility" class="form-input" [(ngModel)]="visibility" name="visibility">
+              <option value="public">Public</option>
+              <option value="unlisted">Unlisted (anyone with the link)</option>
+              <option value="private">Private (only you and people you share with)</option>
+            </select>
+          </div>
+
           <!-- Upload Progress -->
           <div *ngIf="uploading" class="progress-container">
             <div class="progress-header">
@@ -328,6 +337,7 @@ export class VideoUploadComponent {
     selectedFile: File | null = null;
     title = '';
     description = '';
+    visibility: Visibility = 'public';
     isDragging = false;
     uploading = false;
     uploadProgress = 0;
@@ -405,7 +415,7 @@ export class VideoUploadComponent {
         this.errorMessage = '';
 
         this.videoService
-            .uploadVideo(this.selectedFile, this.title, this.description)
+            .uploadVideo(this.selectedFile, this.title, this.description, this.visibility)
             .subscribe({
                 next: (event) => {
                     if ('progress' in event) {
@@ -421,7 +431,10 @@ export class VideoUploadComponent {
                 error: (err) => {
                     console.error('Upload failed:', err);
                     this.uploading = false;
-                    this.errorMessage = 'Upload failed. Please try again.';
+                    // Quota violations carry a message worth showing
+                    this.errorMessage = err.status === 413 || err.status === 403
+                        ? err.error?.error ?? 'Upload rejected by your quota.'
+                        : 'Upload failed. Please try again.';
                 },
             });
     }
@@ -438,6 +451,7 @@ export class VideoUploadComponent {
         this.selectedFile = null;
         this.title = '';
         this.description = '';
+        this.visibility = 'public';
         this.uploadSuccess = false;
         this.uploadProgress = 0;
     }
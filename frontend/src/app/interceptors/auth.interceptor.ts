@@ -0,0 +1,46 @@
+import { HttpErrorResponse, HttpInterceptorFn, HttpRequest } from '@angular/common/http';
+import { inject } from '@angular/core';
+import { Router } from '@angular/router';
+import { catchError, switchMap, throwError } from 'rxjs';
+import { AuthService } from '../services/auth.service';
+
+/**
+ * Attach the access token to API requests and refresh it once on 401
+ */
+export const authInterceptor: HttpInterceptorFn = (req, next) => {
+    const auth = inject(AuthService);
+    const router = inject(Router);
+
+    if (!req.url.startsWith('/api/') || req.url.startsWith('/api/auth/')) {
+        return next(req);
+    }
+
+    const withToken = (request: HttpRequest<unknown>) => {
+        const token = auth.accessToken;
+        return token
+            ? request.clone({ setHeaders: { Authorization: `Bearer ${token}` } })
+            : request;
+    };
+
+    return next(withToken(req)).pipe(
+        catchError((error: HttpErrorResponse) => {
+            if (error.status !== 401) {
+                return throwError(() => error);
+            }
+
+            if (!auth.isLoggedIn) {
+                router.navigate(['/login'], { queryParams: { returnUrl: router.url } });
+                return throwError(() => error);
+            }
+
+            return auth.refresh().pipe(
+                switchMap(() => next(withToken(req))),
+                catchError((refreshError) => {
+                    auth.clear();
+                    router.navigate(['/login'], { queryParams: { returnUrl: router.url } });
+                    return throwError(() => refreshError);
+                })
+            );
+        })
+    );
+};
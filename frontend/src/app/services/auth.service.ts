@@ -0,0 +1,119 @@
+import { Injectable } from '@angular/core';
+import { HttpClient } from '@angular/common/http';
+import { BehaviorSubject, Observable, finalize, of, shareReplay, tap, throwError } from 'rxjs';
+
+export interface User {
+    id: string;
+    username: string;
+    email: string;
+    role: 'viewer' | 'uploader' | 'editor' | 'admin';
+    created_at: string;
+}
+
+export interface TokenResponse {
+    access_token: string;
+    token_type: string;
+    expires_in: number;
+    refresh_token: string;
+    refresh_expires_at: string;
+    user: User;
+}
+
+const ACCESS_TOKEN_KEY = 'videostream.access_token';
+const REFRESH_TOKEN_KEY = 'videostream.refresh_token';
+const USER_KEY = 'videostream.user';
+
+@Injectable({
+    providedIn: 'root',
+})
+export class AuthService {
+    private apiUrl = '/api/auth';
+    private userSubject = new BehaviorSubject<User | null>(this.storedUser());
+    private refreshing$: Observable<TokenResponse> | null = null;
+
+    /** Currently logged-in user, or null */
+    user$ = this.userSubject.asObservable();
+
+    constructor(private http: HttpClient) { }
+
+    get accessToken(): string | null {
+        return localStorage.getItem(ACCESS_TOKEN_KEY);
+    }
+
+    get isLoggedIn(): boolean {
+        return this.userSubject.value !== null;
+    }
+
+    /**
+     * Create an account
+     */
+    register(username: string, email: string, password: string): Observable<User> {
+        return this.http.post<User>(`${this.apiUrl}/register`, { username, email, password });
+    }
+
+    /**
+     * Log in with a username or email address
+     */
+    login(username: string, password: string): Observable<TokenResponse> {
+        return this.http
+            .post<TokenResponse>(`${this.apiUrl}/login`, { username, password })
+            .pipe(tap((tokens) => this.store(tokens)));
+    }
+
+    /**
+     * Exchange the refresh token for new tokens; concurrent callers share one request
+     */
+    refresh(): Observable<TokenResponse> {
+        const refreshToken = localStorage.getItem(REFRESH_TOKEN_KEY);
+        if (!refreshToken) {
+            return throwError(() => new Error('Not logged in'));
+        }
+
+        if (!this.refreshing$) {
+            this.refreshing$ = this.http
+                .post<TokenResponse>(`${this.apiUrl}/refresh`, { refresh_token: refreshToken })
+                .pipe(
+                    tap((tokens) => this.store(tokens)),
+                    finalize(() => (this.refreshing$ = null)),
+                    shareReplay(1)
+                );
+        }
+
+        return this.refreshing$;
+    }
+
+    /**
+     * Revoke the refresh token and forget the session
+     */
+    logout(): Observable<void> {
+        const refreshToken = localStorage.getItem(REFRESH_TOKEN_KEY);
+        this.clear();
+
+        if (!refreshToken) {
+            return of(undefined);
+        }
+        return this.http.post<void>(`${this.apiUrl}/logout`, { refresh_token: refreshToken });
+    }
+
+    /**
+     * Forget the session locally
+     */
+    clear() {
+        localStorage.removeItem(ACCESS_TOKEN_KEY);
+        localStorage.removeItem(REFRESH_TOKEN_KEY);
+        localStorage.removeItem(USER_KEY);
+        this.userSubject.next(null);
+    }
+
+    private store(tokens: TokenResponse) {
+        localStorage.setItem(ACCESS_TOKEN_KEY, tokens.access_token);
+        localStorage.setItem(REFRESH_TOKEN_KEY, tokens.refresh_token);
+        localStorage.setItem(USER_KEY, JSON.stringify(tokens.user));
+        this.userSubject.next(tokens.user);
+    }
+
+    private storedUser(): User | null {
+        const user = localStorage.getItem(USER_KEY);
+        return user ? (JSON.parse(user) as User) : null;
+    }
+}
@@ -9,14 +9,25 @@ export interface Video {
     original_filename: string;
     original_size: number;
     duration_seconds: number | null;
+    rendition_size: number;
     status: 'pending' | 'processing' | 'ready' | 'failed';
     resolutions: Resolution[];
+    visibility: Visibility;
+    encryption: string | null;
+    owner_id: string | null;
+    language: string;
+    tags: string[];
+    category_id: string | null;
+    custom_metadata: Record<string, unknown>;
     streaming_url: string | null;
     thumbnail_url: string | null;
     created_at: string;
     updated_at: string;
+    version: number;
 }
 
+export type Visibility = 'public' | 'unlisted' | 'private';
+
 export interface Resolution {
     name: string;
     width: number;
@@ -28,6 +39,17 @@ export interface Resolution {
 export interface VideoListResponse {
     videos: Video[];
     total: number;
+    next_cursor?: string;
+}
+
+export interface PlaybackInfo {
+    video_id: string;
+    token: string;
+    expires_at: string;
+    streaming_url: string;
+    thumbnail_url?: string;
+    dash_url?: string;
+    license_url?: string;
 }
 
 export interface UploadProgress {
@@ -47,10 +69,12 @@ export class VideoService {
     /**
      * Get list of all videos
      */
-    getVideos(limit = 20, offset = 0): Observable<VideoListResponse> {
-        return this.http.get<VideoListResponse>(this.apiUrl, {
-            params: { limit: limit.toString(), offset: offset.toString() },
-        });
+    getVideos(limit = 20, cursor?: string): Observable<VideoListResponse> {
+        const params: Record<string, string> = { limit: limit.toString() };
+        if (cursor) {
+            params['cursor'] = cursor;
+        }
+        return this.http.get<VideoListResponse>(this.apiUrl, { params });
     }
 
     /**
@@ -60,13 +84,21 @@ export class VideoService {
         return this.http.get<Video>(`${this.apiUrl}/${id}`);
     }
 
+    /**
+     * Get a signed, expiring streaming URL (required for private videos)
+     */
+    getPlayback(id: string): Observable<PlaybackInfo> {
+        return this.http.get<PlaybackInfo>(`${this.apiUrl}/${id}/playback`);
+    }
+
     /**
      * Upload a new video with progress tracking
      */
     uploadVideo(
         file: File,
         title: string,
-        description?: string
+        description?: string,
+        visibility: Visibility = 'public'
     ): Observable<UploadProgress | Video> {
         const formData = new FormData();
         formData.append('file', file);
@@ -74,6 +106,7 @@ export class VideoService {
         if (description) {
             formData.append('description', description);
         }
+        formData.append('visibility', visibility);
 
         return this.http
             .post<Video>(this.apiUrl, formData, {
@@ -105,9 +138,16 @@ export class VideoService {
      */
     updateVideo(
         id: string,
-        data: { title?: string; description?: string }
+        data: { title?: string; description?: string | null; visibility?: Visibility },
+        version?: number
     ): Observable<Video> {
-        return this.http.put<Video>(`${this.apiUrl}/${id}`, data);
+        // A merge patch can clear the description; If-Match fails with 412
+        // when someone else changed the video since it was loaded
+        const headers: Record<string, string> = { 'Content-Type': 'application/merge-patch+json' };
+        if (version !== undefined) {
+            headers['If-Match'] = `"${version}"`;
+        }
+        return this.http.patch<Video>(`${this.apiUrl}/${id}`, data, { headers });
     }
 
     /**
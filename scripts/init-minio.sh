@@ -20,9 +20,9 @@ mc mb myminio/videos --ignore-existing
 mc mb myminio/segments --ignore-existing
 mc mb myminio/manifests --ignore-existing
 
-# Set anonymous download policy for segments and manifests (for streaming)
-mc anonymous set download myminio/segments
-mc anonymous set download myminio/manifests
+# Buckets stay private; manifests and segments are streamed through the backend
+mc anonymous set none myminio/segments
+mc anonymous set none myminio/manifests
 
 echo "MinIO buckets created and configured successfully!"
 